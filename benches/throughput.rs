@@ -0,0 +1,274 @@
+//! Throughput of the terminal hot path, in the spirit of vtebench.
+//!
+//! Every workload is run through copa + `Performer` + `Crosswords`, and then
+//! once more rendering a frame with `State::prepare_term` every
+//! `FRAME_BYTES`. Criterion reports the results in MiB/s.
+//!
+//! The `prepare_term` group needs a window and a GPU adapter, it is skipped
+//! when no display or no adapter is available.
+//!
+//! ```sh
+//! cargo bench -p rio-benches
+//! ```
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use rioterm::crosswords::Crosswords;
+use rioterm::event::{EventP, EventProxy, VoidListener};
+use rioterm::performer::handler::ParserProcessor;
+use rioterm::screen::Screen;
+use std::fmt::Write;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use winit::window::WindowId;
+
+const COLUMNS: usize = 120;
+const LINES: usize = 40;
+
+/// Approximate size of every workload.
+const WORKLOAD_BYTES: usize = 0x10_0000;
+
+/// Bytes processed between two frames in the `prepare_term` group.
+const FRAME_BYTES: usize = 0x1_0000;
+
+/// Small xorshift generator, keeps the workloads the same across runs.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, max: usize) -> usize {
+        (self.next() % max as u64) as usize
+    }
+}
+
+fn repeat_until_full(mut write: impl FnMut(&mut String)) -> Vec<u8> {
+    let mut out = String::with_capacity(WORKLOAD_BYTES + 0x1000);
+    while out.len() < WORKLOAD_BYTES {
+        write(&mut out);
+    }
+    out.into_bytes()
+}
+
+/// Lines of printable ASCII, like `cat` of a log file.
+fn dense_ascii() -> Vec<u8> {
+    let mut rng = Rng(1);
+    repeat_until_full(|out| {
+        for _ in 0..rng.below(COLUMNS * 2) {
+            out.push((b' ' + rng.below(95) as u8) as char);
+        }
+        out.push_str("\r\n");
+    })
+}
+
+/// Mix of accented latin, wide CJK, emoji and combining characters.
+fn unicode() -> Vec<u8> {
+    const CHARS: &[char] = &[
+        'á', 'ç', 'ö', 'ß', 'Ж', 'λ', '日', '本', '語', '한', '🦀', '😀', '\u{301}', '─',
+        '│', '█',
+    ];
+    let mut rng = Rng(2);
+    repeat_until_full(|out| {
+        for _ in 0..COLUMNS / 2 {
+            out.push(CHARS[rng.below(CHARS.len())]);
+        }
+        out.push_str("\r\n");
+    })
+}
+
+/// A foreground and background color change for every cell.
+fn sgr_colors() -> Vec<u8> {
+    let mut rng = Rng(3);
+    repeat_until_full(|out| {
+        for _ in 0..COLUMNS {
+            let _ = write!(
+                out,
+                "\x1b[38;5;{}m\x1b[48;2;{};{};{}m{}",
+                rng.below(256),
+                rng.below(256),
+                rng.below(256),
+                rng.below(256),
+                (b'a' + rng.below(26) as u8) as char,
+            );
+        }
+        out.push_str("\x1b[0m\r\n");
+    })
+}
+
+/// Scrolling inside of a region in the middle of the screen.
+fn scrolling_region() -> Vec<u8> {
+    let mut rng = Rng(4);
+    let mut out = format!(
+        "\x1b[{};{}r\x1b[{}H",
+        LINES / 4,
+        LINES * 3 / 4,
+        LINES * 3 / 4
+    );
+    out.push_str(
+        &String::from_utf8(repeat_until_full(|out| {
+            for _ in 0..rng.below(COLUMNS) {
+                out.push((b'a' + rng.below(26) as u8) as char);
+            }
+            out.push_str("\r\n");
+        }))
+        .unwrap(),
+    );
+    out.push_str("\x1b[r");
+    out.into_bytes()
+}
+
+/// Full screen redraws in the alternate screen, like a TUI would do.
+fn alt_screen() -> Vec<u8> {
+    let mut rng = Rng(5);
+    repeat_until_full(|out| {
+        out.push_str("\x1b[?1049h\x1b[H\x1b[2J");
+        for line in 1..=LINES {
+            let _ = write!(out, "\x1b[{line};1H\x1b[{}m", 30 + rng.below(8));
+            for _ in 0..COLUMNS {
+                out.push((b'a' + rng.below(26) as u8) as char);
+            }
+        }
+        out.push_str("\x1b[0m\x1b[?1049l");
+    })
+}
+
+/// A cursor jump for every printed character.
+fn cursor_motion() -> Vec<u8> {
+    let mut rng = Rng(6);
+    repeat_until_full(|out| {
+        let _ = write!(
+            out,
+            "\x1b[{};{}H{}",
+            1 + rng.below(LINES),
+            1 + rng.below(COLUMNS),
+            (b'a' + rng.below(26) as u8) as char,
+        );
+    })
+}
+
+fn workloads() -> Vec<(&'static str, Vec<u8>)> {
+    vec![
+        ("dense_ascii", dense_ascii()),
+        ("unicode", unicode()),
+        ("sgr_colors", sgr_colors()),
+        ("scrolling_region", scrolling_region()),
+        ("alt_screen", alt_screen()),
+        ("cursor_motion", cursor_motion()),
+    ]
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse");
+
+    for (name, bytes) in workloads() {
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &bytes, |b, bytes| {
+            let mut terminal =
+                Crosswords::new(COLUMNS, LINES, VoidListener {}, WindowId::from(0));
+            let mut parser = ParserProcessor::new();
+            b.iter(|| parser.advance_bytes(&mut terminal, bytes));
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_prepare_term(c: &mut Criterion) {
+    let event_loop =
+        match winit::event_loop::EventLoopBuilder::<EventP>::with_user_event().build() {
+            Ok(event_loop) => event_loop,
+            Err(err) => {
+                eprintln!(
+                    "Skipping prepare_term benchmarks, no display available: {err}"
+                );
+                return;
+            }
+        };
+
+    // Sugarloaf can't start without an adapter, like on CI runners and VMs.
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
+        backends: wgpu::util::backend_bits_from_env().unwrap_or(wgpu::Backends::all()),
+        ..Default::default()
+    });
+    if futures::executor::block_on(
+        instance.request_adapter(&wgpu::RequestAdapterOptions::default()),
+    )
+    .is_none()
+    {
+        eprintln!("Skipping prepare_term benchmarks, no GPU adapter available");
+        return;
+    }
+
+    let config = Rc::new(rio_config::Config::default());
+    let window = match winit::window::WindowBuilder::new()
+        .with_visible(false)
+        .build(&event_loop)
+    {
+        Ok(window) => window,
+        Err(err) => {
+            eprintln!("Skipping prepare_term benchmarks, unable to open a window: {err}");
+            return;
+        }
+    };
+    let mut font_database = sugarloaf::font::loader::Database::new();
+    font_database.load_system_fonts();
+    let font_database = std::sync::Arc::new(font_database);
+    let mut screen = match futures::executor::block_on(Screen::new(
+        &window,
+        &config,
+        EventProxy::new(event_loop.create_proxy()),
+        &font_database,
+    )) {
+        Ok(screen) => screen,
+        Err(err) => {
+            eprintln!(
+                "Skipping prepare_term benchmarks, unable to create the screen: {err}"
+            );
+            return;
+        }
+    };
+
+    let columns = screen.sugarloaf.layout.columns;
+    let lines = screen.sugarloaf.layout.lines;
+
+    let mut group = c.benchmark_group("prepare_term");
+
+    for (name, bytes) in workloads() {
+        group.throughput(Throughput::Bytes(bytes.len() as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(name), &bytes, |b, bytes| {
+            let mut terminal =
+                Crosswords::new(columns, lines, VoidListener {}, WindowId::from(0));
+            let mut parser = ParserProcessor::new();
+
+            b.iter_custom(|iters| {
+                let mut elapsed = Duration::ZERO;
+                for _ in 0..iters {
+                    for frame in bytes.chunks(FRAME_BYTES) {
+                        let start = Instant::now();
+                        parser.advance_bytes(&mut terminal, frame);
+                        screen.state.prepare_term(
+                            terminal.snapshot(),
+                            &mut screen.sugarloaf,
+                            &screen.context_manager,
+                        );
+                        elapsed += start.elapsed();
+
+                        // Flushes everything queued by `prepare_term`, the GPU
+                        // work is not part of the measurement.
+                        screen.sugarloaf.render();
+                    }
+                }
+                elapsed
+            });
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_prepare_term);
+criterion_main!(benches);
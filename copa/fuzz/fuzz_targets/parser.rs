@@ -0,0 +1,129 @@
+//! Feed arbitrary bytes through the parser and record every action.
+//!
+//! Besides not panicking, the recorded actions must stay within the limits
+//! the parser promises to its `Perform` implementation, and feeding the bytes
+//! in slices through `advance_bytes` must record the very same actions.
+#![no_main]
+
+use copa::{Params, Parser, Perform};
+use libfuzzer_sys::fuzz_target;
+
+/// Maximum number of intermediates handed to a `Perform`.
+const MAX_INTERMEDIATES: usize = 2;
+/// Maximum number of OSC parameters handed to a `Perform`.
+const MAX_OSC_PARAMS: usize = 16;
+/// Maximum number of CSI/DCS parameters handed to a `Perform`.
+const MAX_PARAMS: usize = 32;
+
+/// Payloads are never read back, they are kept around for debugging crashes.
+#[allow(dead_code)]
+#[derive(Debug, PartialEq)]
+enum Action {
+    Print(char),
+    Execute(u8),
+    Hook(Vec<Vec<u16>>, Vec<u8>, bool, char),
+    Put(u8),
+    Unhook,
+    Osc(Vec<Vec<u8>>, bool),
+    Csi(Vec<Vec<u16>>, Vec<u8>, bool, char),
+    Esc(Vec<u8>, bool, u8),
+}
+
+#[derive(Default)]
+struct Recorder {
+    actions: Vec<Action>,
+    hooked: bool,
+}
+
+fn check_params(params: &Params, intermediates: &[u8]) -> Vec<Vec<u16>> {
+    assert!(params.len() <= MAX_PARAMS);
+    assert!(intermediates.len() <= MAX_INTERMEDIATES);
+    params.iter().map(|param| param.to_vec()).collect()
+}
+
+impl Perform for Recorder {
+    fn print(&mut self, c: char) {
+        self.actions.push(Action::Print(c));
+    }
+
+    fn print_str(&mut self, s: &str) {
+        assert!(!s.is_empty());
+        for c in s.chars() {
+            assert!(c as u32 >= 0x20, "control {c:?} in a printable run");
+            self.print(c);
+        }
+    }
+
+    fn execute(&mut self, byte: u8) {
+        self.actions.push(Action::Execute(byte));
+    }
+
+    fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, c: char) {
+        assert!(!self.hooked, "hook without a previous unhook");
+        self.hooked = true;
+
+        let params = check_params(params, intermediates);
+        self.actions
+            .push(Action::Hook(params, intermediates.to_vec(), ignore, c));
+    }
+
+    fn put(&mut self, byte: u8) {
+        assert!(self.hooked, "put outside of a device control string");
+        self.actions.push(Action::Put(byte));
+    }
+
+    fn unhook(&mut self) {
+        assert!(self.hooked, "unhook without a previous hook");
+        self.hooked = false;
+        self.actions.push(Action::Unhook);
+    }
+
+    fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+        assert!(params.len() <= MAX_OSC_PARAMS);
+        let params = params.iter().map(|param| param.to_vec()).collect();
+        self.actions.push(Action::Osc(params, bell_terminated));
+    }
+
+    fn csi_dispatch(
+        &mut self,
+        params: &Params,
+        intermediates: &[u8],
+        ignore: bool,
+        c: char,
+    ) {
+        let params = check_params(params, intermediates);
+        self.actions
+            .push(Action::Csi(params, intermediates.to_vec(), ignore, c));
+    }
+
+    fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+        assert!(intermediates.len() <= MAX_INTERMEDIATES);
+        self.actions
+            .push(Action::Esc(intermediates.to_vec(), ignore, byte));
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    let mut parser = Parser::new();
+    let mut recorder = Recorder::default();
+
+    for byte in data {
+        parser.advance(&mut recorder, *byte);
+    }
+
+    // A byte dispatches at most the exit action of the previous state
+    // (unhook or OSC end) plus its own action.
+    assert!(recorder.actions.len() <= data.len() * 2);
+
+    // Split the input somewhere to cover sequences spanning multiple reads.
+    let split = data.first().map_or(0, |byte| *byte as usize % data.len());
+    let mut parser = Parser::new();
+    let mut batched = Recorder::default();
+    assert_eq!(parser.advance_bytes(&mut batched, &data[..split]), split);
+    assert_eq!(
+        parser.advance_bytes(&mut batched, &data[split..]),
+        data.len() - split
+    );
+
+    assert_eq!(batched.actions, recorder.actions);
+});
@@ -40,6 +40,7 @@ use arrayvec::ArrayVec;
 
 mod definitions;
 mod params;
+mod scan;
 mod table;
 mod utf8;
 
@@ -145,6 +146,64 @@ impl<const OSC_RAW_BUF_SIZE: usize> Parser<OSC_RAW_BUF_SIZE> {
         self.perform_state_change(performer, state, action, byte);
     }
 
+    /// Advance the parser state with a slice of bytes
+    ///
+    /// Behaves like calling [`advance`] for every byte, except that runs of
+    /// printable characters in the ground state are handed to
+    /// [`Perform::print_str`] at once.
+    ///
+    /// Returns the number of bytes processed, which is only less than
+    /// `bytes.len()` when [`Perform::terminated`] asked to stop.
+    ///
+    /// [`advance`]: struct.Parser.html#method.advance
+    /// [`Perform::print_str`]: trait.Perform.html#method.print_str
+    /// [`Perform::terminated`]: trait.Perform.html#method.terminated
+    pub fn advance_bytes<P: Perform>(
+        &mut self,
+        performer: &mut P,
+        bytes: &[u8],
+    ) -> usize {
+        let mut i = 0;
+
+        // End of the current run without C0 controls, kept around so a run is
+        // only scanned once even if it contains invalid UTF-8.
+        let mut run_end = 0;
+
+        while i < bytes.len() {
+            if let State::Ground = self.state {
+                if i >= run_end {
+                    run_end = i + scan::printable_len(&bytes[i..]);
+                }
+
+                let run = &bytes[i..run_end];
+                let valid = match core::str::from_utf8(run) {
+                    Ok(valid) => valid,
+                    // Safe since everything up to `valid_up_to` was just validated.
+                    Err(err) => unsafe {
+                        core::str::from_utf8_unchecked(&run[..err.valid_up_to()])
+                    },
+                };
+
+                if !valid.is_empty() {
+                    performer.print_str(valid);
+                    i += valid.len();
+                    continue;
+                }
+            }
+
+            // Controls, escape sequences and invalid or partial UTF-8 go
+            // through the state machine one byte at a time.
+            self.advance(performer, bytes[i]);
+            i += 1;
+
+            if performer.terminated() {
+                break;
+            }
+        }
+
+        i
+    }
+
     #[inline]
     fn process_utf8<P>(&mut self, performer: &mut P, byte: u8)
     where
@@ -395,6 +454,18 @@ pub trait Perform {
     /// Draw a character to the screen and update states.
     fn print(&mut self, _c: char) {}
 
+    /// Draw a run of characters to the screen and update states.
+    ///
+    /// Only called by [`Parser::advance_bytes`], the string never contains C0
+    /// controls. Defaults to calling `print` for every character.
+    ///
+    /// [`Parser::advance_bytes`]: struct.Parser.html#method.advance_bytes
+    fn print_str(&mut self, s: &str) {
+        for c in s.chars() {
+            self.print(c);
+        }
+    }
+
     /// Execute a C0 or C1 control function.
     fn execute(&mut self, _byte: u8) {}
 
@@ -448,6 +519,16 @@ pub trait Perform {
     /// The `ignore` flag indicates that more than two intermediates arrived and
     /// subsequent characters were ignored.
     fn esc_dispatch(&mut self, _intermediates: &[u8], _ignore: bool, _byte: u8) {}
+
+    /// Whether [`Parser::advance_bytes`] should stop processing the remaining bytes.
+    ///
+    /// Checked after every byte going through the state machine, the bytes left
+    /// over can be passed to the parser again once the performer is ready.
+    ///
+    /// [`Parser::advance_bytes`]: struct.Parser.html#method.advance_bytes
+    fn terminated(&self) -> bool {
+        false
+    }
 }
 
 #[cfg(all(test, feature = "no_std"))]
@@ -523,6 +604,146 @@ mod tests {
         }
     }
 
+    #[derive(Default)]
+    struct Printer {
+        printed: Vec<char>,
+        runs: usize,
+        dispatcher: Dispatcher,
+        terminate_on_execute: bool,
+        executed: Vec<u8>,
+    }
+
+    impl Perform for Printer {
+        fn print(&mut self, c: char) {
+            self.printed.push(c);
+        }
+
+        fn print_str(&mut self, s: &str) {
+            self.printed.extend(s.chars());
+            self.runs += 1;
+        }
+
+        fn execute(&mut self, byte: u8) {
+            self.executed.push(byte);
+        }
+
+        fn osc_dispatch(&mut self, params: &[&[u8]], bell_terminated: bool) {
+            self.dispatcher.osc_dispatch(params, bell_terminated);
+        }
+
+        fn csi_dispatch(
+            &mut self,
+            params: &Params,
+            intermediates: &[u8],
+            ignore: bool,
+            c: char,
+        ) {
+            self.dispatcher
+                .csi_dispatch(params, intermediates, ignore, c);
+        }
+
+        fn esc_dispatch(&mut self, intermediates: &[u8], ignore: bool, byte: u8) {
+            self.dispatcher.esc_dispatch(intermediates, ignore, byte);
+        }
+
+        fn hook(&mut self, params: &Params, intermediates: &[u8], ignore: bool, c: char) {
+            self.dispatcher.hook(params, intermediates, ignore, c);
+        }
+
+        fn put(&mut self, byte: u8) {
+            self.dispatcher.put(byte);
+        }
+
+        fn unhook(&mut self) {
+            self.dispatcher.unhook();
+        }
+
+        fn terminated(&self) -> bool {
+            self.terminate_on_execute && !self.executed.is_empty()
+        }
+    }
+
+    static MIXED_BYTES: &[u8] = b"hello\x1b[1;31mw\xc3\xb6rld\x7f\r\n\
+        \x1b]2;t\xc3\xaftle\x07\xe6\x97\xa5\xe6\x9c\xac\x1bP1$r\x1b\\\xc3\x28\xff\
+        \xe2\x82abc\x18\xf0\x9f\x92\x96";
+
+    #[test]
+    fn advance_bytes_matches_advance() {
+        let mut expected = Printer::default();
+        let mut parser = Parser::new();
+        for byte in MIXED_BYTES {
+            parser.advance(&mut expected, *byte);
+        }
+
+        // Split the input at every position to cover sequences across reads.
+        for split in 0..=MIXED_BYTES.len() {
+            let mut printer = Printer::default();
+            let mut parser = Parser::new();
+
+            let processed = parser.advance_bytes(&mut printer, &MIXED_BYTES[..split]);
+            assert_eq!(processed, split);
+            let processed = parser.advance_bytes(&mut printer, &MIXED_BYTES[split..]);
+            assert_eq!(processed, MIXED_BYTES.len() - split);
+
+            assert_eq!(printer.printed, expected.printed);
+            assert_eq!(printer.executed, expected.executed);
+            assert_eq!(
+                printer.dispatcher.dispatched,
+                expected.dispatcher.dispatched
+            );
+        }
+    }
+
+    #[test]
+    fn advance_bytes_invalid_utf8() {
+        // Invalid bytes are dropped or replaced exactly like in `advance`.
+        static INPUT: &[u8] = b"a\xc3\x28b\xffc\xe2\x82\x28d\xed\xa0\x80e";
+        let mut expected = Printer::default();
+        let mut parser = Parser::new();
+        for byte in INPUT {
+            parser.advance(&mut expected, *byte);
+        }
+
+        let mut printer = Printer::default();
+        let mut parser = Parser::new();
+        parser.advance_bytes(&mut printer, INPUT);
+
+        assert_eq!(printer.printed, expected.printed);
+        assert!(printer.printed.contains(&'�'));
+    }
+
+    #[test]
+    fn advance_bytes_prints_runs() {
+        let mut printer = Printer::default();
+        let mut parser = Parser::new();
+
+        parser.advance_bytes(
+            &mut printer,
+            "a long ascii line, ünïcödé too\r\n".as_bytes(),
+        );
+
+        assert_eq!(printer.runs, 1);
+        assert_eq!(printer.executed, vec![b'\r', b'\n']);
+        assert_eq!(
+            printer.printed.iter().collect::<std::string::String>(),
+            "a long ascii line, ünïcödé too"
+        );
+    }
+
+    #[test]
+    fn advance_bytes_terminated() {
+        let mut printer = Printer {
+            terminate_on_execute: true,
+            ..Default::default()
+        };
+        let mut parser = Parser::new();
+
+        let processed = parser.advance_bytes(&mut printer, b"ab\ncd\n");
+
+        assert_eq!(processed, 3);
+        assert_eq!(printer.printed, vec!['a', 'b']);
+    }
+
     #[test]
     fn parse_osc() {
         let mut dispatcher = Dispatcher::default();
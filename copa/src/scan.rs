@@ -0,0 +1,106 @@
+//! Scanning for printable runs in the ground state.
+//!
+//! Anything below 0x20 is a C0 control (including CAN, SUB and ESC) and has to
+//! go through the state machine, every other byte is either printable ASCII or
+//! part of an UTF-8 sequence.
+
+/// Width of the chunks compared at once.
+const CHUNK: usize = 16;
+
+/// Length of the prefix of `bytes` which doesn't contain any C0 control.
+#[inline]
+pub fn printable_len(bytes: &[u8]) -> usize {
+    let mut offset = 0;
+
+    while offset + CHUNK <= bytes.len() {
+        if let Some(index) = first_control(&bytes[offset..offset + CHUNK]) {
+            return offset + index;
+        }
+        offset += CHUNK;
+    }
+
+    bytes[offset..]
+        .iter()
+        .position(|byte| *byte < 0x20)
+        .map_or(bytes.len(), |index| offset + index)
+}
+
+/// Position of the first C0 control inside a chunk.
+#[cfg(target_arch = "x86_64")]
+#[inline]
+fn first_control(chunk: &[u8]) -> Option<usize> {
+    use core::arch::x86_64::{
+        __m128i, _mm_cmpeq_epi8, _mm_loadu_si128, _mm_max_epu8, _mm_movemask_epi8,
+        _mm_set1_epi8,
+    };
+
+    debug_assert_eq!(chunk.len(), CHUNK);
+
+    // SSE2 is part of the x86_64 baseline, so this is always available.
+    //
+    // Bytes at or above 0x20 are left untouched by an unsigned max with 0x20.
+    let mask = unsafe {
+        let bytes = _mm_loadu_si128(chunk.as_ptr() as *const __m128i);
+        let printable = _mm_cmpeq_epi8(_mm_max_epu8(bytes, _mm_set1_epi8(0x20)), bytes);
+        _mm_movemask_epi8(printable) as u32
+    };
+
+    if mask == 0xffff {
+        None
+    } else {
+        Some((!mask).trailing_zeros() as usize)
+    }
+}
+
+/// Position of the first C0 control inside a chunk.
+#[cfg(not(target_arch = "x86_64"))]
+#[inline]
+fn first_control(chunk: &[u8]) -> Option<usize> {
+    const LOW: u64 = u64::from_ne_bytes([0x01; 8]);
+    const HIGH: u64 = u64::from_ne_bytes([0x80; 8]);
+
+    debug_assert_eq!(chunk.len(), CHUNK);
+
+    for (index, word) in chunk.chunks_exact(8).enumerate() {
+        let word = u64::from_ne_bytes(word.try_into().unwrap());
+
+        // Flags every byte below 0x20, bytes past the first hit may be false
+        // positives so the exact position is found with a plain scan.
+        if word.wrapping_sub(LOW * 0x20) & !word & HIGH != 0 {
+            let word = &chunk[index * 8..index * 8 + 8];
+            return word
+                .iter()
+                .position(|byte| *byte < 0x20)
+                .map(|position| index * 8 + position);
+        }
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn printable_len_matches_scalar_scan() {
+        let mut input = [b'a'; 67];
+        assert_eq!(printable_len(&input), input.len());
+        assert_eq!(printable_len(&[]), 0);
+
+        for control in [0x00, 0x07, 0x18, 0x1b, 0x1f] {
+            for index in 0..input.len() {
+                input = [b'a'; 67];
+                input[index] = control;
+                assert_eq!(printable_len(&input), index);
+            }
+        }
+
+        // DEL and anything above are not C0 controls.
+        let input = [
+            0x7f, 0x80, 0x9b, 0xc2, 0xff, 0x20, 0x7f, 0x80, 0x9b, 0xc2, 0xff, 0x20, 0x7f,
+            0x80, 0x9b, 0xc2, 0xff, 0x20, 0x1b,
+        ];
+        assert_eq!(printable_len(&input), input.len() - 1);
+    }
+}
@@ -32,6 +32,18 @@ pub fn tabs_active() -> ColorArray {
         .to_arr()
 }
 
+pub fn scrollbar() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#8E8A8C"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+
+pub fn scrollbar_marker() -> ColorArray {
+    ColorBuilder::from_hex(String::from("#f712ff"), Format::SRGB0_1)
+        .unwrap()
+        .to_arr()
+}
+
 pub fn foreground() -> ColorArray {
     [1., 1., 1., 1.]
 }
@@ -21,9 +21,10 @@ pub fn default_line_height() -> f32 {
 pub fn default_shell() -> crate::Shell {
     #[cfg(not(target_os = "windows"))]
     {
+        // Runs the shell of the user as a login shell.
         crate::Shell {
             program: String::from(""),
-            args: vec![String::from("--login")],
+            args: vec![],
         }
     }
 
@@ -60,6 +61,31 @@ pub fn default_option_as_alt() -> String {
     String::from("None")
 }
 
+pub fn default_scroll_animation_duration() -> u64 {
+    150
+}
+
+pub fn default_scrollbar_width() -> f32 {
+    6.
+}
+
+pub fn default_scrollbar_markers() -> bool {
+    true
+}
+
+pub fn default_confirm_close_enabled() -> bool {
+    true
+}
+
+pub fn default_confirm_close_allowlist() -> Vec<String> {
+    [
+        "sh", "bash", "zsh", "fish", "dash", "ksh", "tcsh", "csh", "nu", "elvish",
+        "xonsh", "login", "tmux", "screen", "zellij",
+    ]
+    .map(String::from)
+    .to_vec()
+}
+
 pub fn default_log_level() -> String {
     String::from("OFF")
 }
@@ -99,7 +125,7 @@ blinking-cursor = false
 #
 # Default is false
 #
-ignore_theme_selection_fg_color = false
+ignore-selection-fg-color = false
 
 # Performance
 #
@@ -146,6 +172,12 @@ performance = "High"
 #     - "Maximized" window is created with maximized
 #     - "Fullscreen" window is created with fullscreen
 #
+# • title - title of the window, `rio --title` takes precedence
+#   Default: None
+#
+# • class - WM_CLASS on X11 and app id on Wayland, `rio --class` takes precedence
+#   Default: "rio"
+#
 # Example
 #   [window]
 #   width = 600
@@ -174,6 +206,108 @@ performance = "High"
 # x = 0.0
 # y = 0.0
 
+# Custom shader
+#
+# WGSL shader the frame goes through before it is shown. Relative
+# paths start from the configuration folder, where shaders reload on save.
+# The shader defines the fragment function "fs_main", which gets
+# "frame" and "frame_sampler" to read the frame and "uniforms" with
+# "resolution", "time" (seconds, updated on each drawn frame), "scale",
+# "cursor" (x, y, width and height in pixels) and "background".
+#
+# Example
+#   custom-shader = "shaders/crt.wgsl"
+#
+#   // ~/.config/rio/shaders/crt.wgsl
+#   @fragment
+#   fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+#       let color = textureSample(frame, frame_sampler, input.uv);
+#       let scanline = 0.9 + 0.1 * sin(input.position.y * 3.14);
+#       return vec4<f32>(color.rgb * scanline, color.a);
+#   }
+
+# Screenshot directory
+#
+# Directory where the "Screenshot" action and "rio msg screenshot"
+# save PNGs of the window. Default is the pictures folder.
+#
+# Example
+#   screenshot-directory = "/Users/raphael/Pictures/rio"
+
+# Scroll
+#
+# With smooth enabled the viewport follows trackpads pixel by pixel
+# instead of jumping from line to line, and page up and page down
+# are animated for animation-duration milliseconds.
+#   Default: smooth = false, animation-duration = 150
+#
+# Example
+#   [scroll]
+#   smooth = true
+#   animation-duration = 150
+
+# Scrollbar
+#
+# Overlay scrollbar on the right side of the terminal, it can be
+# dragged or clicked to jump through the history.
+# visibility is "Auto" (shown while scrolling or hovering it),
+# "Always" or "Never". markers show where prompts start, for shells
+# emitting OSC 133.
+#   Default: visibility = "Auto", width = 6, markers = true
+#
+# Example
+#   [scrollbar]
+#   visibility = "Auto"
+#   width = 6
+#   color = '#8E8A8C'
+#   markers = true
+#   marker-color = '#f712ff'
+
+# Profiles
+#
+# Named sets of shell, working-dir, env-vars, theme and fonts
+# overriding the options above. A window uses default-profile, or the
+# one given with `rio --profile <name>`, and the CreateTab(<name>)
+# binding action opens a tab with the shell, environment and theme of
+# a profile. Fonts are shared by the tabs of a window, so they only
+# apply to windows opened with the profile.
+#   Default: no profiles
+#
+# Example
+#   default-profile = "dev"
+#
+#   [profiles.prod]
+#   shell = { program = "ssh", args = ["prod.example.com"] }
+#   theme = "red"
+#
+#   [profiles.dev]
+#   working-dir = "/home/me/dev"
+#   env-vars = ["APP_ENV=development"]
+
+# Import
+#
+# Configuration files merged below this one, so it overrides their
+# options. Tables are merged key by key, anything else replaces the
+# imported value. Paths starting with ~ are in the home directory and
+# relative ones start from the directory of the file importing them.
+# Imported files are watched for changes as well.
+#   Default: import = []
+#
+# Example
+#   import = ["~/.config/rio/team.toml"]
+
+# Platform
+#
+# Options only used on one platform, they override the rest of the
+# file. Available tables are linux, macos and windows.
+#
+# Example
+#   [platform.macos]
+#   option-as-alt = "both"
+#
+#   [platform.linux.fonts]
+#   size = 16
+
 # Window Height
 #
 # window-height changes the inital window height.
@@ -216,6 +350,30 @@ performance = "High"
 #   family = "cascadiamono"
 #   style = "italic"
 #   weight = 800
+#
+# Every font also takes OpenType features and, for variable fonts,
+# values for their axes. A leading "-" turns a feature off.
+#
+# Example
+#   [fonts.regular]
+#   family = "JetBrains Mono"
+#   features = ["ss01", "zero", "-calt"]
+#   variations = { wght = 450 }
+#
+# Chars missing in the fonts above are looked up in "extras", in order,
+# then in the fonts installed in the system.
+#
+# Example
+#   [[fonts.extras]]
+#   family = "Noto Sans CJK JP"
+#
+# Box drawing, block elements, braille and Powerline separators are
+# drawn by Rio to fit the cells exactly, set "builtin-box-drawing" to
+# false to take them from the fonts instead.
+#
+# Example
+#   [fonts]
+#   builtin-box-drawing = false
 
 # Navigation
 #
@@ -258,8 +416,6 @@ performance = "High"
 # Directory the shell is started in. If this is unset the working
 # directory of the parent process will be used.
 #
-# This configuration only has effect if use-fork is disabled
-#
 # Example
 #  working-dir = "/Users/raphael/Documents/"
 
@@ -278,6 +434,28 @@ performance = "High"
 # Example
 #   disable-renderer-when-unfocused = false
 
+# Hold on exit
+#
+# Keep tabs open when their shell or command exits, with a banner of its
+# exit status. Enter, Escape or q closes the tab and r runs it again.
+# `rio --hold` enables it for a window.
+#
+# Example
+#   hold-on-exit = true
+
+# Confirm close
+#
+# Closing a tab or a window, or quitting, asks for a confirmation while a
+# program other than the shell of a tab runs in its foreground, like vim or
+# a build. Programs in `allowlist` never ask either, it defaults to the
+# common shells, for the ones started from another, and multiplexers.
+# Not available on Windows.
+#
+# Example
+#   [confirm-close]
+#   enabled = true
+#   allowlist = ["bash", "zsh", "fish", "tmux"]
+
 # Use fork
 #
 # Defaults for POSIX-based systems (Windows is not configurable):
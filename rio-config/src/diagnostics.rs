@@ -0,0 +1,532 @@
+use serde::de::{
+    self, DeserializeSeed, Deserializer, IntoDeserializer, MapAccess, SeqAccess, Visitor,
+};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml::{Spanned, Value};
+
+/// Issue found in a configuration file, `Config::try_load` fails with the
+/// errors while unknown keys are only warnings.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DiagnosticLevel {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for DiagnosticLevel {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DiagnosticLevel::Error => write!(f, "error"),
+            DiagnosticLevel::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Diagnostic {
+    pub level: DiagnosticLevel,
+    pub file: PathBuf,
+    /// Line and column, starting from 1, when the position is known.
+    pub position: Option<(usize, usize)>,
+    /// Path of the key, like `fonts.size`.
+    pub key: Option<String>,
+    pub message: String,
+    /// Known key close to an unknown one.
+    pub suggestion: Option<String>,
+}
+
+impl Diagnostic {
+    pub fn new(file: &Path, message: impl Into<String>) -> Diagnostic {
+        Diagnostic {
+            level: DiagnosticLevel::Error,
+            file: file.to_path_buf(),
+            position: None,
+            key: None,
+            message: message.into(),
+            suggestion: None,
+        }
+    }
+
+    /// Diagnostic of a TOML syntax error of `content`.
+    pub fn from_toml_error(file: &Path, content: &str, error: &toml::de::Error) -> Self {
+        let mut diagnostic = Diagnostic::new(file, error.message().trim());
+        diagnostic.position = error.span().map(|span| position(content, span.start));
+        diagnostic
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.file.display())?;
+        if let Some((line, column)) = self.position {
+            write!(f, ":{line}:{column}")?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, ": `{key}`")?;
+        }
+        write!(f, ": {}", self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, ", did you mean `{suggestion}`?")?;
+        }
+        Ok(())
+    }
+}
+
+/// Line and column, starting from 1, of the byte `offset` of `content`.
+fn position(content: &str, offset: usize) -> (usize, usize) {
+    let before = &content[..offset.min(content.len())];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+    (line, before[line_start..].chars().count() + 1)
+}
+
+/// What came up while deserializing a configuration with [`deserialize`].
+#[derive(Default, Debug)]
+pub struct Report {
+    /// Keys nothing used, with the suggestion for them.
+    pub unknown_keys: Vec<(String, Option<String>)>,
+    /// Key of the first value that failed to deserialize.
+    pub error_key: Option<String>,
+}
+
+/// Deserialize `value` keeping track of the keys serde ignores and of the
+/// key an error comes from.
+pub fn deserialize<T: de::DeserializeOwned>(
+    value: Value,
+) -> (Result<T, toml::de::Error>, Report) {
+    let report = RefCell::new(Report::default());
+    let result = T::deserialize(Tracked {
+        value,
+        path: String::new(),
+        fields: None,
+        report: &report,
+    });
+    (result, report.into_inner())
+}
+
+fn join(path: &str, key: &str) -> String {
+    if path.is_empty() {
+        key.to_string()
+    } else {
+        format!("{path}.{key}")
+    }
+}
+
+/// Field of `fields` closest to the unknown `key`, if any is close enough
+/// to be a typo of it.
+fn suggest(key: &str, fields: &[&str]) -> Option<String> {
+    fields
+        .iter()
+        .map(|field| (strsim::levenshtein(key, field), field))
+        .filter(|(distance, _)| *distance <= (key.len() / 3).max(2))
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, field)| field.to_string())
+}
+
+struct Tracked<'a> {
+    value: Value,
+    path: String,
+    /// Fields of the struct the value belongs to.
+    fields: Option<&'static [&'static str]>,
+    report: &'a RefCell<Report>,
+}
+
+impl<'de, 'a> Deserializer<'de> for Tracked<'a> {
+    type Error = toml::de::Error;
+
+    fn deserialize_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_fields(None, visitor)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.deserialize_fields(Some(fields), visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let key = self.path.rsplit('.').next().unwrap_or_default();
+        let suggestion = self.fields.and_then(|fields| suggest(key, fields));
+        self.report
+            .borrow_mut()
+            .unknown_keys
+            .push((self.path, suggestion));
+        visitor.visit_unit()
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(
+        self,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool u8 u16 u32 u64 i8 i16 i32 i64 f32 f64 char str string unit seq
+        bytes byte_buf map unit_struct tuple_struct tuple identifier
+    }
+}
+
+impl<'a> Tracked<'a> {
+    fn deserialize_fields<'de, V: Visitor<'de>>(
+        self,
+        fields: Option<&'static [&'static str]>,
+        visitor: V,
+    ) -> Result<V::Value, toml::de::Error> {
+        match self.value {
+            Value::Table(table) => visitor.visit_map(TrackedMap {
+                iter: table.into_iter(),
+                value: None,
+                path: self.path,
+                fields,
+                report: self.report,
+            }),
+            Value::Array(array) => visitor.visit_seq(TrackedSeq {
+                iter: array.into_iter().enumerate(),
+                path: self.path,
+                report: self.report,
+            }),
+            value => value.deserialize_any(visitor),
+        }
+    }
+}
+
+struct TrackedMap<'a> {
+    iter: toml::map::IntoIter,
+    value: Option<(String, Value)>,
+    path: String,
+    fields: Option<&'static [&'static str]>,
+    report: &'a RefCell<Report>,
+}
+
+impl<'de, 'a> MapAccess<'de> for TrackedMap<'a> {
+    type Error = toml::de::Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Self::Error> {
+        let Some((key, value)) = self.iter.next() else {
+            return Ok(None);
+        };
+
+        let key_value = seed.deserialize(key.clone().into_deserializer())?;
+        self.value = Some((key, value));
+        Ok(Some(key_value))
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, Self::Error> {
+        let (key, value) = self
+            .value
+            .take()
+            .ok_or_else(|| de::Error::custom("value without a key"))?;
+        let path = join(&self.path, &key);
+        let result = seed.deserialize(Tracked {
+            value,
+            path: path.clone(),
+            fields: self.fields,
+            report: self.report,
+        });
+
+        if result.is_err() {
+            self.report.borrow_mut().error_key.get_or_insert(path);
+        }
+        result
+    }
+}
+
+struct TrackedSeq<'a> {
+    iter: std::iter::Enumerate<std::vec::IntoIter<Value>>,
+    path: String,
+    report: &'a RefCell<Report>,
+}
+
+impl<'de, 'a> SeqAccess<'de> for TrackedSeq<'a> {
+    type Error = toml::de::Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Self::Error> {
+        let Some((index, value)) = self.iter.next() else {
+            return Ok(None);
+        };
+
+        seed.deserialize(Tracked {
+            value,
+            path: format!("{}[{index}]", self.path),
+            fields: None,
+            report: self.report,
+        })
+        .map(Some)
+    }
+}
+
+/// Position of every key of a TOML document, by key path.
+#[derive(Default)]
+pub struct KeyPositions(HashMap<String, (usize, usize)>);
+
+impl KeyPositions {
+    /// Positions of the keys of `content`, none if it isn't valid TOML.
+    pub fn new(content: &str) -> KeyPositions {
+        let spans = RefCell::new(HashMap::new());
+        let collector = SpanCollector {
+            path: String::new(),
+            spans: &spans,
+        };
+
+        if collector
+            .deserialize(toml::Deserializer::new(content))
+            .is_err()
+        {
+            return KeyPositions::default();
+        }
+
+        KeyPositions(
+            spans
+                .into_inner()
+                .into_iter()
+                .map(|(key, span)| (key, position(content, span.start)))
+                .collect(),
+        )
+    }
+
+    /// Position of `key`, or of the closest parent of it in the document.
+    fn find(&self, key: &str) -> Option<(usize, usize)> {
+        let mut key = key;
+        loop {
+            if let Some(position) = self.0.get(key) {
+                return Some(*position);
+            }
+            key = &key[..key.rfind(['.', '['])?];
+        }
+    }
+}
+
+/// Places diagnostics of key paths of the merged configuration in the
+/// files they come from.
+pub struct Locator {
+    files: Vec<(PathBuf, KeyPositions)>,
+    overrides: Vec<String>,
+}
+
+impl Locator {
+    pub fn new(files: &[PathBuf], overrides: &[String]) -> Locator {
+        Locator {
+            overrides: overrides
+                .iter()
+                .filter_map(|option| option.split_once('='))
+                .map(|(key, _)| key.trim().to_string())
+                .collect(),
+            files: files
+                .iter()
+                .map(|file| {
+                    let content = std::fs::read_to_string(file).unwrap_or_default();
+                    (file.to_owned(), KeyPositions::new(&content))
+                })
+                .collect(),
+        }
+    }
+
+    /// Diagnostic of `key`, in the command line when an option sets it or
+    /// else in the first file setting it or its closest parent.
+    pub fn diagnostic(&self, key: &str, message: impl Into<String>) -> Diagnostic {
+        let overridden = self.overrides.iter().any(|option| {
+            option == key
+                || key.starts_with(&format!("{option}."))
+                || option.starts_with(&format!("{key}."))
+        });
+        if overridden {
+            let mut diagnostic =
+                Diagnostic::new(Path::new(crate::import::OVERRIDES), message);
+            diagnostic.key = Some(key.to_string());
+            return diagnostic;
+        }
+
+        let platform_key = format!("platform.{}.{key}", crate::import::PLATFORM);
+        let position = |positions: &KeyPositions, exact: bool| {
+            [key, &platform_key].into_iter().find_map(|key| {
+                if exact {
+                    positions.0.get(key).copied()
+                } else {
+                    positions.find(key)
+                }
+            })
+        };
+
+        let located = [true, false].into_iter().find_map(|exact| {
+            self.files.iter().find_map(|(file, positions)| {
+                position(positions, exact).map(|position| (file, position))
+            })
+        });
+
+        let mut diagnostic = match (located, self.files.first()) {
+            (Some((file, position)), _) => {
+                let mut diagnostic = Diagnostic::new(file, message);
+                diagnostic.position = Some(position);
+                diagnostic
+            }
+            (None, Some((file, _))) => Diagnostic::new(file, message),
+            (None, None) => Diagnostic::new(Path::new(""), message),
+        };
+        diagnostic.key = Some(key.to_string());
+        diagnostic
+    }
+}
+
+struct SpanCollector<'a> {
+    path: String,
+    spans: &'a RefCell<HashMap<String, Range<usize>>>,
+}
+
+impl<'de, 'a> DeserializeSeed<'de> for SpanCollector<'a> {
+    type Value = ();
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<(), D::Error> {
+        deserializer.deserialize_any(self)
+    }
+}
+
+impl<'de, 'a> Visitor<'de> for SpanCollector<'a> {
+    type Value = ();
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a TOML value")
+    }
+
+    fn visit_bool<E>(self, _value: bool) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_i64<E>(self, _value: i64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_u64<E>(self, _value: u64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_f64<E>(self, _value: f64) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_str<E>(self, _value: &str) -> Result<(), E> {
+        Ok(())
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<(), A::Error> {
+        let mut index = 0;
+        while seq
+            .next_element_seed(SpanCollector {
+                path: format!("{}[{index}]", self.path),
+                spans: self.spans,
+            })?
+            .is_some()
+        {
+            index += 1;
+        }
+        Ok(())
+    }
+
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<(), A::Error> {
+        while let Some(key) = map.next_key::<Spanned<String>>()? {
+            let path = join(&self.path, key.get_ref());
+            self.spans
+                .borrow_mut()
+                .entry(path.clone())
+                .or_insert(key.span());
+            map.next_value_seed(SpanCollector {
+                path,
+                spans: self.spans,
+            })?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde::Deserialize;
+
+    #[derive(Deserialize)]
+    struct Fonts {
+        #[allow(dead_code)]
+        size: f32,
+    }
+
+    #[derive(Deserialize)]
+    struct Config {
+        #[allow(dead_code)]
+        #[serde(rename = "blinking-cursor", default)]
+        blinking_cursor: bool,
+        #[allow(dead_code)]
+        fonts: Fonts,
+    }
+
+    #[test]
+    fn unknown_keys_come_with_suggestions() {
+        let content = "blinking_cursor = true\n[fonts]\nsize = 14\nfamiyl = 'x'\n";
+        let value = Value::Table(content.parse().unwrap());
+        let (result, report) = deserialize::<Config>(value);
+
+        assert!(result.is_ok());
+        assert_eq!(
+            report.unknown_keys,
+            vec![
+                (
+                    String::from("blinking_cursor"),
+                    Some(String::from("blinking-cursor"))
+                ),
+                (String::from("fonts.famiyl"), None),
+            ]
+        );
+
+        let positions = KeyPositions::new(content);
+        assert_eq!(positions.find("blinking_cursor"), Some((1, 1)));
+        assert_eq!(positions.find("fonts.famiyl"), Some((4, 1)));
+        assert_eq!(positions.find("fonts.size.missing"), Some((3, 1)));
+        assert_eq!(positions.find("window"), None);
+    }
+
+    #[test]
+    fn errors_know_their_key() {
+        let value = Value::Table("[fonts]\nsize = 'big'\n".parse().unwrap());
+        let (result, report) = deserialize::<Config>(value);
+
+        assert!(result.is_err());
+        assert_eq!(report.error_key, Some(String::from("fonts.size")));
+    }
+}
@@ -0,0 +1,194 @@
+use crate::diagnostics::Diagnostic;
+use std::path::{Path, PathBuf};
+use toml::{Table, Value};
+
+#[cfg(target_os = "linux")]
+pub(crate) const PLATFORM: &str = "linux";
+#[cfg(target_os = "macos")]
+pub(crate) const PLATFORM: &str = "macos";
+#[cfg(target_os = "windows")]
+pub(crate) const PLATFORM: &str = "windows";
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+pub(crate) const PLATFORM: &str = "";
+
+/// Origin of the diagnostics of options set in the command line.
+pub const OVERRIDES: &str = "--option";
+
+/// Read the configuration at `path` as a table, with the files listed in
+/// its `import` merged below it and its `[platform.<os>]` table above it.
+/// Every file read ends up in `files`.
+pub fn load(path: &Path, files: &mut Vec<PathBuf>) -> Result<Table, Vec<Diagnostic>> {
+    load_with_stack(path, files, &mut vec![])
+}
+
+fn load_with_stack(
+    path: &Path,
+    files: &mut Vec<PathBuf>,
+    stack: &mut Vec<PathBuf>,
+) -> Result<Table, Vec<Diagnostic>> {
+    if stack.iter().any(|file| file == path) {
+        return Err(vec![Diagnostic::new(path, "the file imports itself")]);
+    }
+
+    let content = std::fs::read_to_string(path).map_err(|err| {
+        vec![Diagnostic::new(path, format!("unable to read it: {err}"))]
+    })?;
+    let mut table = content
+        .parse::<Table>()
+        .map_err(|err| vec![Diagnostic::from_toml_error(path, &content, &err)])?;
+
+    files.push(path.to_path_buf());
+    stack.push(path.to_path_buf());
+
+    let mut merged = Table::new();
+    if let Some(imports) = table.remove("import") {
+        let Value::Array(imports) = imports else {
+            return Err(vec![import_diagnostic(
+                path,
+                "import has to be a list of paths",
+            )]);
+        };
+
+        for import in imports {
+            let Value::String(import) = import else {
+                return Err(vec![import_diagnostic(
+                    path,
+                    "import has to be a list of paths",
+                )]);
+            };
+
+            let import_path = resolve(&import, path);
+            if !import_path.exists() {
+                log::warn!("imported file {} does not exist", import_path.display());
+                continue;
+            }
+
+            merge(&mut merged, load_with_stack(&import_path, files, stack)?);
+        }
+    }
+
+    if let Some(Value::Table(mut platforms)) = table.remove("platform") {
+        if let Some(Value::Table(platform)) = platforms.remove(PLATFORM) {
+            merge(&mut table, platform);
+        }
+    }
+
+    merge(&mut merged, table);
+    stack.pop();
+    Ok(merged)
+}
+
+/// Set the `key.path=value` option of the command line in `table`, the
+/// value is read as TOML or else as a string.
+pub fn apply_override(table: &mut Table, option: &str) -> Result<(), Vec<Diagnostic>> {
+    let invalid = || {
+        vec![Diagnostic::new(
+            Path::new(OVERRIDES),
+            format!("{option} is not key.path=value"),
+        )]
+    };
+    let (key, value) = option.split_once('=').ok_or_else(invalid)?;
+    let key = key.trim();
+    if key.is_empty() || key.split('.').any(str::is_empty) {
+        return Err(invalid());
+    }
+
+    let mut value = match format!("value = {value}").parse::<Table>() {
+        Ok(mut parsed) => parsed
+            .remove("value")
+            .unwrap_or(Value::String(value.into())),
+        Err(_) => Value::String(value.to_string()),
+    };
+    for part in key.rsplit('.') {
+        value = Value::Table(Table::from_iter([(part.to_string(), value)]));
+    }
+
+    if let Value::Table(option_table) = value {
+        merge(table, option_table);
+    }
+    Ok(())
+}
+
+fn import_diagnostic(path: &Path, message: &str) -> Diagnostic {
+    let mut diagnostic = Diagnostic::new(path, message);
+    diagnostic.key = Some(String::from("import"));
+    diagnostic
+}
+
+/// Path of an import, `~` is the home directory and relative paths start
+/// from the directory of the file importing it.
+fn resolve(import: &str, importer: &Path) -> PathBuf {
+    if let Some(rest) = import.strip_prefix("~/") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(rest);
+        }
+    }
+
+    let import = Path::new(import);
+    match importer.parent() {
+        Some(directory) if import.is_relative() => directory.join(import),
+        _ => import.to_path_buf(),
+    }
+}
+
+/// Deep merge of `other` into `base`, tables are merged key by key and any
+/// other value of `other` replaces the one in `base`.
+pub fn merge(base: &mut Table, other: Table) {
+    for (key, value) in other {
+        match (base.get_mut(&key), value) {
+            (Some(Value::Table(base_table)), Value::Table(table)) => {
+                merge(base_table, table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn merge_is_deep_and_replaces_arrays() {
+        let mut base: Table = r#"
+            theme = "dracula"
+            env-vars = ["A=1"]
+            [fonts]
+            size = 14
+            family = "Fira Code"
+        "#
+        .parse()
+        .unwrap();
+        let other: Table = r#"
+            env-vars = ["B=2"]
+            [fonts]
+            size = 18
+        "#
+        .parse()
+        .unwrap();
+
+        merge(&mut base, other);
+        assert_eq!(base["theme"].as_str(), Some("dracula"));
+        assert_eq!(base["env-vars"].as_array().unwrap().len(), 1);
+        assert_eq!(base["fonts"]["size"].as_integer(), Some(18));
+        assert_eq!(base["fonts"]["family"].as_str(), Some("Fira Code"));
+    }
+
+    #[test]
+    fn overrides_are_toml_or_strings() {
+        let mut table: Table = "[fonts]\nsize = 14\nfamily = 'Hack'\n".parse().unwrap();
+
+        apply_override(&mut table, "fonts.size=18").unwrap();
+        apply_override(&mut table, "theme=dracula").unwrap();
+        apply_override(&mut table, "shell.args=['-l']").unwrap();
+        assert_eq!(table["fonts"]["size"].as_integer(), Some(18));
+        assert_eq!(table["fonts"]["family"].as_str(), Some("Hack"));
+        assert_eq!(table["theme"].as_str(), Some("dracula"));
+        assert_eq!(table["shell"]["args"][0].as_str(), Some("-l"));
+
+        assert!(apply_override(&mut table, "fonts.size").is_err());
+        assert!(apply_override(&mut table, "fonts..size=1").is_err());
+    }
+}
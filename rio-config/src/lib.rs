@@ -1,25 +1,35 @@
 pub mod bindings;
 pub mod colors;
 pub mod defaults;
+pub mod diagnostics;
+pub mod import;
 pub mod navigation;
+pub mod profile;
+pub mod scrollbar;
 pub mod theme;
 pub mod window;
 
 use crate::bindings::Bindings;
 use crate::defaults::*;
+use crate::diagnostics::{Diagnostic, Locator};
 use crate::navigation::Navigation;
+use crate::profile::Profile;
+use crate::scrollbar::Scrollbar;
 use crate::window::{Background, Window};
 use colors::Colors;
 use log::warn;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::default::Default;
+use std::path::{Path, PathBuf};
 use sugarloaf::font::fonts::SugarloafFonts;
 use theme::{AdaptiveColors, AdaptiveTheme, Theme};
 
 #[derive(Clone, Debug)]
 pub enum ConfigError {
-    ErrLoadingConfig(String),
+    ErrLoadingConfig(Vec<Diagnostic>),
     ErrLoadingTheme(String),
+    ProfileNotFound(String),
     PathNotFound,
 }
 
@@ -66,6 +76,46 @@ impl Default for Developer {
     }
 }
 
+/// Confirmation asked before closing tabs with programs running in them.
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct ConfirmClose {
+    #[serde(default = "default_confirm_close_enabled")]
+    pub enabled: bool,
+    /// Programs that can be closed without asking, like shells.
+    #[serde(default = "default_confirm_close_allowlist")]
+    pub allowlist: Vec<String>,
+}
+
+impl Default for ConfirmClose {
+    fn default() -> ConfirmClose {
+        ConfirmClose {
+            enabled: default_confirm_close_enabled(),
+            allowlist: default_confirm_close_allowlist(),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Scroll {
+    #[serde(default = "bool::default")]
+    pub smooth: bool,
+    /// Duration in milliseconds of the page up and page down animation.
+    #[serde(
+        default = "default_scroll_animation_duration",
+        rename = "animation-duration"
+    )]
+    pub animation_duration: u64,
+}
+
+impl Default for Scroll {
+    fn default() -> Scroll {
+        Scroll {
+            smooth: false,
+            animation_duration: default_scroll_animation_duration(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
 pub struct Config {
     #[serde(default = "bool::default", rename = "blinking-cursor")]
@@ -86,6 +136,10 @@ pub struct Config {
     pub use_fork: bool,
     #[serde(default = "default_working_dir", rename = "working-dir")]
     pub working_dir: Option<String>,
+    #[serde(default = "bool::default", rename = "hold-on-exit")]
+    pub hold_on_exit: bool,
+    #[serde(default = "ConfirmClose::default", rename = "confirm-close")]
+    pub confirm_close: ConfirmClose,
     #[serde(rename = "line-height", default = "default_line_height")]
     pub line_height: f32,
     #[serde(default = "default_theme")]
@@ -112,10 +166,29 @@ pub struct Config {
     pub adaptive_colors: Option<AdaptiveColors>,
     #[serde(default = "Developer::default")]
     pub developer: Developer,
+    #[serde(default = "Scroll::default")]
+    pub scroll: Scroll,
+    #[serde(default = "Scrollbar::default")]
+    pub scrollbar: Scrollbar,
     #[serde(default = "Bindings::default")]
     pub bindings: bindings::Bindings,
     #[serde(default = "bool::default", rename = "ignore-selection-fg-color")]
     pub ignore_selection_fg_color: bool,
+    #[serde(default = "Option::default", rename = "custom-shader")]
+    pub custom_shader: Option<String>,
+    #[serde(default = "Option::default", rename = "screenshot-directory")]
+    pub screenshot_directory: Option<String>,
+    #[serde(default = "HashMap::default")]
+    pub profiles: HashMap<String, Profile>,
+    #[serde(default = "Option::default", rename = "default-profile")]
+    pub default_profile: Option<String>,
+    /// Files the configuration was read from, `config.toml` and the ones
+    /// it imports.
+    #[serde(skip)]
+    pub loaded_files: Vec<PathBuf>,
+    /// Warnings about the files, like unknown keys.
+    #[serde(skip)]
+    pub diagnostics: Vec<Diagnostic>,
 }
 
 #[cfg(not(target_os = "windows"))]
@@ -160,8 +233,7 @@ impl Config {
     #[cfg(test)]
     fn load_from_path_without_fallback(path: &str) -> Result<Self, String> {
         if std::path::Path::new(path).exists() {
-            let content = std::fs::read_to_string(path).unwrap();
-            match toml::from_str::<Config>(&content) {
+            match Config::from_file(Some(Path::new(path)), &[], &mut vec![]) {
                 Ok(mut decoded) => {
                     let theme = &decoded.theme;
                     if theme.is_empty() {
@@ -219,6 +291,60 @@ impl Config {
         }
     }
 
+    /// Decode the configuration at `path` with its imports and platform
+    /// overrides, the files read are added to `files`. Unknown keys are
+    /// warnings in `diagnostics` unless something else fails.
+    fn from_file(
+        path: Option<&Path>,
+        overrides: &[String],
+        files: &mut Vec<PathBuf>,
+    ) -> Result<Config, Vec<Diagnostic>> {
+        let mut table = match path {
+            Some(path) => import::load(path, files)?,
+            None => toml::Table::new(),
+        };
+        for option in overrides {
+            import::apply_override(&mut table, option)?;
+        }
+
+        let (result, report) =
+            diagnostics::deserialize::<Config>(toml::Value::Table(table));
+        if result.is_ok() && report.unknown_keys.is_empty() {
+            return result.map_err(|_| vec![]);
+        }
+
+        let locator = Locator::new(files, overrides);
+        let mut diagnostics: Vec<Diagnostic> = report
+            .unknown_keys
+            .into_iter()
+            .map(|(key, suggestion)| {
+                let mut diagnostic = locator.diagnostic(&key, "unknown key");
+                diagnostic.level = diagnostics::DiagnosticLevel::Warning;
+                diagnostic.suggestion = suggestion;
+                diagnostic
+            })
+            .collect();
+
+        match result {
+            Ok(mut config) => {
+                config.diagnostics = diagnostics;
+                Ok(config)
+            }
+            Err(err_message) => {
+                let message = err_message.message().trim();
+                let diagnostic = match report.error_key {
+                    Some(key) => locator.diagnostic(&key, message),
+                    None => Diagnostic::new(
+                        path.unwrap_or(Path::new(import::OVERRIDES)),
+                        message,
+                    ),
+                };
+                diagnostics.insert(0, diagnostic);
+                Err(diagnostics)
+            }
+        }
+    }
+
     fn load_theme(path: &str) -> Result<Theme, String> {
         if std::path::Path::new(&path).exists() {
             let content = std::fs::read_to_string(path).unwrap();
@@ -231,6 +357,59 @@ impl Config {
         }
     }
 
+    /// Path of the custom shader, relative paths start from the
+    /// configuration folder.
+    pub fn custom_shader_path(&self) -> Option<std::path::PathBuf> {
+        self.custom_shader
+            .as_ref()
+            .map(|path| std::path::Path::new(&config_dir_path()).join(path))
+    }
+
+    /// Directory screenshots are saved to, the pictures folder of the
+    /// user unless it's configured.
+    pub fn screenshot_directory(&self) -> std::path::PathBuf {
+        match &self.screenshot_directory {
+            Some(directory) => std::path::PathBuf::from(directory),
+            None => dirs::picture_dir()
+                .or_else(dirs::home_dir)
+                .unwrap_or_default(),
+        }
+    }
+
+    /// Override the top level options with the profile `name`, or with
+    /// `default-profile` if there isn't one.
+    pub fn apply_profile(&mut self, name: Option<&str>) -> Result<(), ConfigError> {
+        let Some(name) = name.or(self.default_profile.as_deref()) else {
+            return Ok(());
+        };
+
+        let profile = self
+            .profiles
+            .get(name)
+            .cloned()
+            .ok_or_else(|| ConfigError::ProfileNotFound(name.to_string()))?;
+
+        if let Some(shell) = profile.shell {
+            self.shell = shell;
+        }
+        if profile.working_dir.is_some() {
+            self.working_dir = profile.working_dir;
+        }
+        self.env_vars.extend(profile.env_vars);
+        if let Some(theme) = profile.theme {
+            self.theme = theme;
+        }
+        if let Some(colors) = profile.colors {
+            self.colors = colors;
+            self.adaptive_colors = None;
+        }
+        if let Some(fonts) = profile.fonts {
+            self.fonts = fonts;
+        }
+
+        Ok(())
+    }
+
     pub fn to_string(&self) -> Result<String, toml::ser::Error> {
         toml::to_string(self)
     }
@@ -267,71 +446,105 @@ impl Config {
     }
 
     pub fn try_load() -> Result<Self, ConfigError> {
-        let config_path_str = config_dir_path();
-        let path = format!("{config_path_str}/config.toml");
-        if std::path::Path::new(&path).exists() {
-            let content = std::fs::read_to_string(path).unwrap();
-            match toml::from_str::<Config>(&content) {
-                Ok(mut decoded) => {
-                    let theme = &decoded.theme;
-                    let theme_path = format!("{config_path_str}/themes");
-                    if !theme.is_empty() {
-                        let path = format!("{theme_path}/{theme}.toml");
-                        match Config::load_theme(&path) {
-                            Ok(loaded_theme) => {
-                                decoded.colors = loaded_theme.colors;
-                            }
-                            Err(err_message) => {
-                                return Err(ConfigError::ErrLoadingTheme(err_message));
-                            }
+        Config::try_load_from_path(Path::new(&config_file_path()), &[])
+    }
+
+    /// Load the configuration at `path` with the `key.path=value`
+    /// `overrides` of the command line on top of it.
+    pub fn try_load_from_path(
+        path: &Path,
+        overrides: &[String],
+    ) -> Result<Self, ConfigError> {
+        if !path.exists() {
+            return Err(ConfigError::PathNotFound);
+        }
+
+        Config::decode(Some(path), overrides)
+    }
+
+    /// Default configuration with the `overrides` of the command line.
+    pub fn try_load_overrides(overrides: &[String]) -> Result<Self, ConfigError> {
+        Config::decode(None, overrides)
+    }
+
+    fn decode(path: Option<&Path>, overrides: &[String]) -> Result<Self, ConfigError> {
+        // Themes are next to the configuration file.
+        let config_path_str = path
+            .and_then(Path::parent)
+            .map(|directory| directory.to_string_lossy().to_string())
+            .unwrap_or_else(config_dir_path);
+
+        let mut loaded_files = vec![];
+        match Config::from_file(path, overrides, &mut loaded_files) {
+            Ok(mut decoded) => {
+                decoded.loaded_files = loaded_files;
+                let theme = &decoded.theme;
+                let theme_path = format!("{config_path_str}/themes");
+                if !theme.is_empty() {
+                    let path = format!("{theme_path}/{theme}.toml");
+                    match Config::load_theme(&path) {
+                        Ok(loaded_theme) => {
+                            decoded.colors = loaded_theme.colors;
+                        }
+                        Err(err_message) => {
+                            return Err(ConfigError::ErrLoadingTheme(err_message));
                         }
                     }
+                }
 
-                    if let Some(adaptive_theme) = &decoded.adaptive_theme {
-                        let mut adaptive_colors = AdaptiveColors {
-                            dark: None,
-                            light: None,
-                        };
+                if let Some(adaptive_theme) = &decoded.adaptive_theme {
+                    let mut adaptive_colors = AdaptiveColors {
+                        dark: None,
+                        light: None,
+                    };
+
+                    let light_theme = &adaptive_theme.light;
+                    let path = format!("{theme_path}/{light_theme}.toml");
+                    match Config::load_theme(&path) {
+                        Ok(light_loaded_theme) => {
+                            adaptive_colors.light = Some(light_loaded_theme.colors)
+                        }
+                        Err(err_message) => {
+                            warn!("failed to load light theme: {}", light_theme);
+                            return Err(ConfigError::ErrLoadingTheme(err_message));
+                        }
+                    }
 
-                        let light_theme = &adaptive_theme.light;
-                        let path = format!("{theme_path}/{light_theme}.toml");
-                        match Config::load_theme(&path) {
-                            Ok(light_loaded_theme) => {
-                                adaptive_colors.light = Some(light_loaded_theme.colors)
-                            }
-                            Err(err_message) => {
-                                warn!("failed to load light theme: {}", light_theme);
-                                return Err(ConfigError::ErrLoadingTheme(err_message));
-                            }
+                    let dark_theme = &adaptive_theme.dark;
+                    let path = format!("{theme_path}/{dark_theme}.toml");
+                    match Config::load_theme(&path) {
+                        Ok(dark_loaded_theme) => {
+                            adaptive_colors.dark = Some(dark_loaded_theme.colors)
+                        }
+                        Err(err_message) => {
+                            warn!("failed to load dark theme: {}", dark_theme);
+                            return Err(ConfigError::ErrLoadingTheme(err_message));
                         }
+                    }
 
-                        let dark_theme = &adaptive_theme.dark;
-                        let path = format!("{theme_path}/{dark_theme}.toml");
+                    if adaptive_colors.light.is_some() && adaptive_colors.dark.is_some() {
+                        decoded.adaptive_colors = Some(adaptive_colors);
+                    }
+                }
+
+                for profile in decoded.profiles.values_mut() {
+                    if let Some(theme) = &profile.theme {
+                        let path = format!("{theme_path}/{theme}.toml");
                         match Config::load_theme(&path) {
-                            Ok(dark_loaded_theme) => {
-                                adaptive_colors.dark = Some(dark_loaded_theme.colors)
+                            Ok(loaded_theme) => {
+                                profile.colors = Some(loaded_theme.colors);
                             }
                             Err(err_message) => {
-                                warn!("failed to load dark theme: {}", dark_theme);
+                                warn!("failed to load profile theme: {}", theme);
                                 return Err(ConfigError::ErrLoadingTheme(err_message));
                             }
                         }
-
-                        if adaptive_colors.light.is_some()
-                            && adaptive_colors.dark.is_some()
-                        {
-                            decoded.adaptive_colors = Some(adaptive_colors);
-                        }
                     }
-
-                    Ok(decoded)
-                }
-                Err(err_message) => {
-                    Err(ConfigError::ErrLoadingConfig(err_message.to_string()))
                 }
+
+                Ok(decoded)
             }
-        } else {
-            Err(ConfigError::PathNotFound)
+            Err(err_message) => Err(ConfigError::ErrLoadingConfig(err_message)),
         }
     }
 }
@@ -347,6 +560,8 @@ impl Default for Config {
             colors: Colors::default(),
             cursor: default_cursor(),
             developer: Developer::default(),
+            scroll: Scroll::default(),
+            scrollbar: Scrollbar::default(),
             disable_unfocused_render: false,
             env_vars: default_env_vars(),
             fonts: SugarloafFonts::default(),
@@ -360,7 +575,15 @@ impl Default for Config {
             use_fork: default_use_fork(),
             window: Window::default(),
             working_dir: default_working_dir(),
+            hold_on_exit: false,
+            confirm_close: ConfirmClose::default(),
             ignore_selection_fg_color: false,
+            custom_shader: None,
+            screenshot_directory: None,
+            profiles: HashMap::default(),
+            default_profile: None,
+            loaded_files: vec![],
+            diagnostics: vec![],
         }
     }
 }
@@ -458,6 +681,11 @@ mod tests {
         assert_eq!(result.colors, Colors::default());
         // Developer
         assert_eq!(result.developer, Developer::default());
+        assert_eq!(result.scroll, Scroll::default());
+        assert_eq!(result.scrollbar, Scrollbar::default());
+        assert_eq!(result.confirm_close, ConfirmClose::default());
+        assert!(result.profiles.is_empty());
+        assert_eq!(result.default_profile, None);
         assert_eq!(result.bindings, Bindings::default());
     }
 
@@ -656,6 +884,92 @@ mod tests {
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
 
+    #[test]
+    fn test_font_features_and_variations() {
+        let result = create_temporary_config(
+            "font-features",
+            r#"
+            [fonts.regular]
+            family = "JetBrains Mono"
+            features = ["ss01", "zero", "-calt"]
+            variations = { wght = 450 }
+        "#,
+        );
+
+        assert_eq!(result.fonts.regular.family, "JetBrains Mono");
+        assert_eq!(result.fonts.regular.features, vec!["ss01", "zero", "-calt"]);
+        assert_eq!(result.fonts.regular.variations.get("wght"), Some(&450.0));
+        assert!(result.fonts.bold.features.is_empty());
+        assert!(result.fonts.bold.variations.is_empty());
+    }
+
+    #[test]
+    fn test_font_extras() {
+        let result = create_temporary_config(
+            "font-extras",
+            r#"
+            [[fonts.extras]]
+            family = "Noto Sans CJK JP"
+
+            [[fonts.extras]]
+            family = "Noto Sans Devanagari"
+        "#,
+        );
+
+        assert_eq!(result.fonts.extras.len(), 2);
+        assert_eq!(result.fonts.extras[0].family, "Noto Sans CJK JP");
+        assert_eq!(result.fonts.extras[1].family, "Noto Sans Devanagari");
+        assert_eq!(result.fonts.regular, SugarloafFonts::default().regular);
+        assert!(SugarloafFonts::default().extras.is_empty());
+    }
+
+    #[test]
+    fn test_font_builtin_box_drawing() {
+        let result = create_temporary_config(
+            "font-builtin-box-drawing",
+            r#"
+            [fonts]
+            builtin-box-drawing = false
+        "#,
+        );
+
+        assert!(!result.fonts.builtin_box_drawing);
+        assert!(SugarloafFonts::default().builtin_box_drawing);
+    }
+
+    #[test]
+    fn test_custom_shader() {
+        let result = create_temporary_config(
+            "change-custom-shader",
+            r#"
+            custom-shader = "shaders/crt.wgsl"
+        "#,
+        );
+
+        assert_eq!(result.custom_shader, Some(String::from("shaders/crt.wgsl")));
+        let path = result.custom_shader_path().unwrap();
+        assert!(path.starts_with(config_dir_path()));
+        assert!(path.ends_with("shaders/crt.wgsl"));
+    }
+
+    #[test]
+    fn test_screenshot_directory() {
+        let result = create_temporary_config("default-screenshot-directory", "");
+        assert_eq!(result.screenshot_directory, None);
+        assert!(result.screenshot_directory().is_absolute());
+
+        let result = create_temporary_config(
+            "change-screenshot-directory",
+            r#"
+            screenshot-directory = "/tmp/rio-screenshots"
+        "#,
+        );
+        assert_eq!(
+            result.screenshot_directory(),
+            std::path::PathBuf::from("/tmp/rio-screenshots")
+        );
+    }
+
     #[test]
     fn test_change_theme() {
         let result = create_temporary_config(
@@ -871,4 +1185,219 @@ mod tests {
         assert_eq!(result.colors.tabs_active, colors::defaults::tabs_active());
         assert_eq!(result.colors.cursor, colors::defaults::cursor());
     }
+
+    #[test]
+    fn test_change_scroll() {
+        let result = create_temporary_config(
+            "change-scroll",
+            r#"
+            [scroll]
+            smooth = true
+            animation-duration = 250
+        "#,
+        );
+
+        assert!(result.scroll.smooth);
+        assert_eq!(result.scroll.animation_duration, 250);
+    }
+
+    #[test]
+    fn test_change_scrollbar() {
+        let result = create_temporary_config(
+            "change-scrollbar",
+            r#"
+            [scrollbar]
+            visibility = "Always"
+            width = 10
+            color = '#FFFFFF'
+            markers = false
+        "#,
+        );
+
+        assert_eq!(
+            result.scrollbar.visibility,
+            scrollbar::ScrollbarVisibility::Always
+        );
+        assert_eq!(result.scrollbar.width, 10.);
+        assert_eq!(result.scrollbar.color, [1., 1., 1., 1.]);
+        assert!(!result.scrollbar.markers);
+        assert_eq!(
+            result.scrollbar.marker_color,
+            colors::defaults::scrollbar_marker()
+        );
+    }
+
+    #[test]
+    fn test_change_confirm_close() {
+        let result = create_temporary_config(
+            "change-confirm-close",
+            r#"
+            [confirm-close]
+            allowlist = ["zsh", "htop"]
+        "#,
+        );
+
+        assert!(result.confirm_close.enabled);
+        assert_eq!(result.confirm_close.allowlist, ["zsh", "htop"]);
+    }
+
+    #[test]
+    fn test_profiles() {
+        let mut result = create_temporary_config(
+            "change-profiles",
+            r#"
+            default-profile = "dev"
+            env-vars = ["TERM_PROGRAM=rio"]
+
+            [profiles.prod]
+            shell = { program = "ssh", args = ["prod"] }
+
+            [profiles.dev]
+            working-dir = "/tmp/dev"
+            env-vars = ["APP_ENV=dev"]
+        "#,
+        );
+
+        assert_eq!(result.profiles.len(), 2);
+        assert_eq!(
+            result.profiles["prod"].shell.as_ref().unwrap().program,
+            "ssh"
+        );
+        assert_eq!(result.profiles["prod"].working_dir, None);
+        assert_eq!(result.default_profile, Some(String::from("dev")));
+
+        let mut prod = result.clone();
+        assert!(prod.apply_profile(Some("prod")).is_ok());
+        assert_eq!(prod.shell.program, "ssh");
+        assert_eq!(prod.shell.args, vec!["prod"]);
+        assert_eq!(prod.use_fork, result.use_fork);
+
+        assert!(result.apply_profile(None).is_ok());
+        assert_eq!(result.shell, default_shell());
+        assert_eq!(result.working_dir, Some(String::from("/tmp/dev")));
+        assert_eq!(result.env_vars, vec!["TERM_PROGRAM=rio", "APP_ENV=dev"]);
+
+        assert!(matches!(
+            result.apply_profile(Some("staging")),
+            Err(ConfigError::ProfileNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_import_and_platform() {
+        let tmp = tmp_dir();
+        let file_name = format!("{tmp}/test-rio-team-config.toml");
+        let mut file = std::fs::File::create(file_name).unwrap();
+        writeln!(
+            file,
+            r#"
+            padding-x = 20
+            [fonts]
+            size = 20
+            [platform.linux]
+            cursor = 'l'
+            [platform.macos]
+            cursor = 'm'
+            [platform.windows]
+            cursor = 'w'
+        "#
+        )
+        .unwrap();
+
+        let result = create_temporary_config(
+            "import",
+            r#"
+            import = ["test-rio-team-config.toml", "test-rio-missing-config.toml"]
+            line-height = 2.0
+            [fonts]
+            family = "Fira Code"
+            [platform.linux]
+            line-height = 1.25
+            [platform.macos]
+            line-height = 1.5
+            [platform.windows]
+            line-height = 1.75
+        "#,
+        );
+
+        assert_eq!(result.padding_x, 20.);
+        assert_eq!(result.fonts.size, 20.);
+        assert_eq!(result.fonts.family, Some(String::from("Fira Code")));
+
+        // Only the table of the current platform is merged.
+        let (cursor, line_height) = match crate::import::PLATFORM {
+            "linux" => ('l', 1.25),
+            "macos" => ('m', 1.5),
+            "windows" => ('w', 1.75),
+            _ => (default_cursor(), 2.0),
+        };
+        assert_eq!(result.cursor, cursor);
+        assert_eq!(result.line_height, line_height);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let result = create_temporary_config(
+            "diagnostics",
+            r#"
+            blinking_cursor = true
+            [fonts]
+            size = 18
+        "#,
+        );
+
+        assert_eq!(result.fonts.size, 18.);
+        assert_eq!(result.diagnostics.len(), 1);
+        let diagnostic = &result.diagnostics[0];
+        assert_eq!(diagnostic.level, diagnostics::DiagnosticLevel::Warning);
+        assert_eq!(diagnostic.key, Some(String::from("blinking_cursor")));
+        assert_eq!(diagnostic.position, Some((2, 13)));
+        assert_eq!(diagnostic.suggestion, Some(String::from("blinking-cursor")));
+
+        let file_name = format!("{}/test-rio-invalid-config.toml", tmp_dir());
+        let mut file = std::fs::File::create(&file_name).unwrap();
+        writeln!(file, "[fonts]\nsize = 'big'").unwrap();
+
+        let diagnostics =
+            Config::from_file(Some(Path::new(&file_name)), &[], &mut vec![])
+                .expect_err("size is not a number");
+        assert_eq!(diagnostics[0].level, diagnostics::DiagnosticLevel::Error);
+        assert_eq!(diagnostics[0].key, Some(String::from("fonts.size")));
+        assert_eq!(diagnostics[0].position, Some((2, 1)));
+    }
+
+    #[test]
+    fn test_default_config_has_no_diagnostics() {
+        let file_name = format!("{}/test-rio-default-config.toml", tmp_dir());
+        let mut file = std::fs::File::create(&file_name).unwrap();
+        writeln!(file, "{}", config_file_content()).unwrap();
+
+        let config =
+            Config::from_file(Some(Path::new(&file_name)), &[], &mut vec![]).unwrap();
+        assert_eq!(config.diagnostics, vec![]);
+    }
+
+    #[test]
+    fn test_overrides() {
+        let config = Config::try_load_overrides(&[
+            String::from("fonts.size=18"),
+            String::from("window.title=Logs"),
+            String::from("env-vars=['A=1', 'B=2']"),
+        ])
+        .unwrap();
+
+        assert_eq!(config.fonts.size, 18.);
+        assert_eq!(config.window.title, Some(String::from("Logs")));
+        assert_eq!(config.env_vars, vec!["A=1", "B=2"]);
+        assert!(config.loaded_files.is_empty());
+
+        let config = Config::try_load_overrides(&[String::from("fonts.siz=2")]).unwrap();
+        assert_eq!(config.diagnostics.len(), 1);
+        assert_eq!(config.diagnostics[0].file, Path::new(import::OVERRIDES));
+
+        assert!(matches!(
+            Config::try_load_overrides(&[String::from("fonts.size=big")]),
+            Err(ConfigError::ErrLoadingConfig(_))
+        ));
+    }
 }
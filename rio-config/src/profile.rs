@@ -0,0 +1,26 @@
+use crate::colors::Colors;
+use crate::Shell;
+use serde::{Deserialize, Serialize};
+use sugarloaf::font::fonts::SugarloafFonts;
+
+/// Named set of options overriding the top level ones, a window uses it
+/// with `--profile` or `default-profile` and a tab with `createtab(<name>)`.
+#[derive(Default, Debug, PartialEq, Clone, Serialize, Deserialize)]
+pub struct Profile {
+    #[serde(default = "Option::default")]
+    pub shell: Option<Shell>,
+    #[serde(default = "Option::default", rename = "working-dir")]
+    pub working_dir: Option<String>,
+    /// Added to the top level `env-vars`.
+    #[serde(default = "Vec::default", rename = "env-vars")]
+    pub env_vars: Vec<String>,
+    #[serde(default = "Option::default")]
+    pub theme: Option<String>,
+    /// Fonts are shared by all the tabs of a window, they're only used by
+    /// windows opened with the profile.
+    #[serde(default = "Option::default")]
+    pub fonts: Option<SugarloafFonts>,
+    /// Colors of `theme`, loaded with the configuration.
+    #[serde(skip)]
+    pub colors: Option<Colors>,
+}
@@ -0,0 +1,48 @@
+use crate::colors::{deserialize_to_arr, ColorArray};
+use crate::defaults::*;
+use serde::{Deserialize, Serialize};
+
+#[derive(Default, Clone, Serialize, Deserialize, Copy, Debug, PartialEq)]
+pub enum ScrollbarVisibility {
+    /// Shown while scrolling or hovering it, hidden a moment after.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(PartialEq, Serialize, Deserialize, Clone, Debug)]
+pub struct Scrollbar {
+    #[serde(default = "ScrollbarVisibility::default")]
+    pub visibility: ScrollbarVisibility,
+    #[serde(default = "default_scrollbar_width")]
+    pub width: f32,
+    #[serde(
+        deserialize_with = "deserialize_to_arr",
+        default = "crate::colors::defaults::scrollbar",
+        skip_serializing
+    )]
+    pub color: ColorArray,
+    /// Show where prompts start (OSC 133) along the scrollbar.
+    #[serde(default = "default_scrollbar_markers")]
+    pub markers: bool,
+    #[serde(
+        deserialize_with = "deserialize_to_arr",
+        default = "crate::colors::defaults::scrollbar_marker",
+        rename = "marker-color",
+        skip_serializing
+    )]
+    pub marker_color: ColorArray,
+}
+
+impl Default for Scrollbar {
+    fn default() -> Scrollbar {
+        Scrollbar {
+            visibility: ScrollbarVisibility::default(),
+            width: default_scrollbar_width(),
+            color: crate::colors::defaults::scrollbar(),
+            markers: default_scrollbar_markers(),
+            marker_color: crate::colors::defaults::scrollbar_marker(),
+        }
+    }
+}
@@ -19,6 +19,12 @@ pub struct Window {
     pub height: i32,
     #[serde(default = "WindowMode::default")]
     pub mode: WindowMode,
+    /// Title of new windows, before programs set one.
+    #[serde(default = "Option::default")]
+    pub title: Option<String>,
+    /// WM_CLASS on X11 and app id on Wayland.
+    #[serde(default = "Option::default")]
+    pub class: Option<String>,
 }
 
 impl Default for Window {
@@ -27,6 +33,8 @@ impl Default for Window {
             width: default_window_width(),
             height: default_window_height(),
             mode: WindowMode::default(),
+            title: None,
+            class: None,
         }
     }
 }
@@ -0,0 +1,114 @@
+//! Wrap random payloads into OSC and DCS strings.
+//!
+//! Unstructured input rarely reaches deep into the string states, so this
+//! target builds the introducers and terminators itself and leaves only the
+//! payload to the fuzzer.
+#![no_main]
+
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+use rioterm_fuzz::{Op, Term};
+
+#[derive(Arbitrary, Debug)]
+enum Terminator {
+    Bell,
+    St,
+    C1St,
+    Cancel,
+    None,
+}
+
+impl Terminator {
+    fn bytes(&self) -> &'static [u8] {
+        match self {
+            Terminator::Bell => b"\x07",
+            Terminator::St => b"\x1b\\",
+            Terminator::C1St => b"\x9c",
+            Terminator::Cancel => b"\x18",
+            Terminator::None => b"",
+        }
+    }
+}
+
+#[derive(Arbitrary, Debug)]
+enum Chunk {
+    /// `ESC ] <command> ; <params joined by ;> <terminator>`.
+    Osc {
+        command: u8,
+        params: Vec<Vec<u8>>,
+        terminator: Terminator,
+    },
+    /// `ESC P <params> <intermediates> <final> <payload> <terminator>`.
+    Dcs {
+        params: Vec<u16>,
+        intermediates: Vec<u8>,
+        action: u8,
+        payload: Vec<u8>,
+        terminator: Terminator,
+    },
+    Op(Op),
+}
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    columns: u8,
+    lines: u8,
+    chunks: Vec<Chunk>,
+}
+
+fn osc(command: u8, params: &[Vec<u8>], terminator: &Terminator) -> Vec<u8> {
+    let mut bytes = b"\x1b]".to_vec();
+    bytes.extend_from_slice(command.to_string().as_bytes());
+    for param in params {
+        bytes.push(b';');
+        bytes.extend_from_slice(param);
+    }
+    bytes.extend_from_slice(terminator.bytes());
+    bytes
+}
+
+fn dcs(
+    params: &[u16],
+    intermediates: &[u8],
+    action: u8,
+    payload: &[u8],
+    terminator: &Terminator,
+) -> Vec<u8> {
+    let mut bytes = b"\x1bP".to_vec();
+    let params = params
+        .iter()
+        .map(|param| param.to_string())
+        .collect::<Vec<_>>()
+        .join(";");
+    bytes.extend_from_slice(params.as_bytes());
+    // Intermediates live in 0x20..=0x2f and the final byte in 0x40..=0x7e.
+    bytes.extend(intermediates.iter().map(|byte| 0x20 + byte % 0x10));
+    bytes.push(0x40 + action % 0x3f);
+    bytes.extend_from_slice(payload);
+    bytes.extend_from_slice(terminator.bytes());
+    bytes
+}
+
+fuzz_target!(|input: Input| {
+    let mut term = Term::new(input.columns, input.lines);
+
+    for chunk in &input.chunks {
+        match chunk {
+            Chunk::Osc {
+                command,
+                params,
+                terminator,
+            } => term.advance(&osc(*command, params, terminator)),
+            Chunk::Dcs {
+                params,
+                intermediates,
+                action,
+                payload,
+                terminator,
+            } => term.advance(&dcs(params, intermediates, *action, payload, terminator)),
+            Chunk::Op(op) => term.apply(op),
+        }
+    }
+
+    term.render();
+});
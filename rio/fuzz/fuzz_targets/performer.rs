@@ -0,0 +1,23 @@
+//! Random PTY output interleaved with resizes and viewport scrolls.
+#![no_main]
+
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use libfuzzer_sys::fuzz_target;
+use rioterm_fuzz::{Op, Term};
+
+#[derive(Arbitrary, Debug)]
+struct Input {
+    columns: u8,
+    lines: u8,
+    ops: Vec<Op>,
+}
+
+fuzz_target!(|input: Input| {
+    let mut term = Term::new(input.columns, input.lines);
+
+    for op in &input.ops {
+        term.apply(op);
+    }
+
+    term.render();
+});
@@ -0,0 +1,72 @@
+//! Shared harness for the terminal fuzz targets.
+//!
+//! Every target drives `copa` through the `Performer` into a `Crosswords`,
+//! interleaving the PTY bytes with resizes like a real window would.
+
+use libfuzzer_sys::arbitrary::{self, Arbitrary};
+use rioterm::crosswords::grid::{Grid, Scroll};
+use rioterm::crosswords::square::Square;
+use rioterm::crosswords::{Crosswords, MIN_COLUMNS, MIN_LINES};
+use rioterm::event::VoidListener;
+use rioterm::performer::handler::ParserProcessor;
+use winit::window::WindowId;
+
+/// Upper bound for the grid dimensions, keeps iterations fast.
+const MAX_COLUMNS: usize = 256;
+const MAX_LINES: usize = 128;
+
+#[derive(Arbitrary, Debug)]
+pub enum Op {
+    /// Bytes read from the PTY.
+    Pty(Vec<u8>),
+    /// Window resize.
+    Resize { columns: u8, lines: u8 },
+    /// Scroll the viewport through the scrollback.
+    Scroll(i8),
+}
+
+pub struct Term {
+    pub terminal: Crosswords<VoidListener>,
+    pub parser: ParserProcessor,
+}
+
+impl Term {
+    pub fn new(columns: u8, lines: u8) -> Term {
+        let (columns, lines) = dimensions(columns, lines);
+        Term {
+            terminal: Crosswords::new(columns, lines, VoidListener, WindowId::from(0)),
+            parser: ParserProcessor::new(),
+        }
+    }
+
+    pub fn advance(&mut self, bytes: &[u8]) {
+        self.parser.advance_bytes(&mut self.terminal, bytes);
+    }
+
+    pub fn apply(&mut self, op: &Op) {
+        match op {
+            Op::Pty(bytes) => self.advance(bytes),
+            Op::Resize { columns, lines } => {
+                let (columns, lines) = dimensions(*columns, *lines);
+                self.terminal.resize::<Grid<Square>>(columns, lines);
+            }
+            Op::Scroll(delta) => {
+                self.terminal.scroll_display(Scroll::Delta(*delta as i32));
+            }
+        }
+    }
+
+    /// Exercise the read paths the renderer and the selection use.
+    pub fn render(&mut self) {
+        let _ = self.terminal.visible_rows();
+        let _ = self.terminal.cursor();
+        let _ = self.terminal.selection_to_string();
+    }
+}
+
+fn dimensions(columns: u8, lines: u8) -> (usize, usize) {
+    (
+        (columns as usize).clamp(MIN_COLUMNS, MAX_COLUMNS),
+        (lines as usize).clamp(MIN_LINES, MAX_LINES),
+    )
+}
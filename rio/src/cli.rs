@@ -1,16 +1,60 @@
 // cli.rs was retired originally from https://github.com/alacritty/alacritty/blob/e35e5ad14fce8456afdd89f2b392b9924bb27471/alacritty/src/cli.rs
 // which is licensed under Apache 2.0 license.
 
-use clap::{Args, Parser};
-use rio_config::Shell;
+use clap::{Args, Parser, Subcommand};
+use rio_config::{Config, ConfigError, Shell};
 use serde::{Deserialize, Serialize};
+use std::path::PathBuf;
 
 #[derive(Parser, Default, Debug)]
 #[clap(author, about, version)]
 pub struct Options {
+    /// Where the configuration is read from and what overrides it.
+    #[clap(flatten)]
+    pub config_options: ConfigOptions,
+
     /// Options which can be passed via IPC.
     #[clap(flatten)]
     pub window_options: WindowOptions,
+
+    /// Check the configuration file and print the issues found in it.
+    #[clap(long)]
+    pub check_config: bool,
+
+    #[cfg(unix)]
+    #[clap(subcommand)]
+    pub subcommands: Option<Subcommands>,
+}
+
+#[cfg(unix)]
+#[derive(Subcommand, Debug)]
+pub enum Subcommands {
+    Msg(MessageOptions),
+}
+
+/// Send a message to a running Rio.
+#[cfg(unix)]
+#[derive(Args, Debug)]
+pub struct MessageOptions {
+    /// Socket of the instance, $RIO_SOCKET by default.
+    #[clap(short, long)]
+    pub socket: Option<PathBuf>,
+
+    #[clap(subcommand)]
+    pub message: SocketMessage,
+}
+
+#[derive(Subcommand, Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SocketMessage {
+    /// Save the focused window as a PNG and print its path.
+    Screenshot(ScreenshotOptions),
+}
+
+#[derive(Args, Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+pub struct ScreenshotOptions {
+    /// File or directory to save to, `screenshot-directory` by default.
+    #[clap(short, long)]
+    pub output: Option<PathBuf>,
 }
 
 impl Options {
@@ -19,11 +63,97 @@ impl Options {
     }
 }
 
+#[derive(Args, Default, Debug, Clone, PartialEq, Eq)]
+pub struct ConfigOptions {
+    /// Configuration file to use instead of the default one.
+    #[clap(long, value_name = "PATH")]
+    pub config_file: Option<PathBuf>,
+
+    /// Override a configuration option, like `-o fonts.size=18`.
+    #[clap(short = 'o', long = "option", value_name = "KEY=VALUE")]
+    pub options: Vec<String>,
+}
+
+impl ConfigOptions {
+    #[inline]
+    pub fn path(&self) -> PathBuf {
+        self.config_file
+            .clone()
+            .unwrap_or_else(|| PathBuf::from(rio_config::config_file_path()))
+    }
+
+    /// Load the configuration with the command line options on top of it,
+    /// the default one is used if it fails to load.
+    pub fn load(&self, window_options: &WindowOptions) -> (Config, Option<ConfigError>) {
+        let mut config_error = None;
+        let mut config = match Config::try_load_from_path(&self.path(), &self.options) {
+            Ok(config) => config,
+            // Without a file the options still go on top of the default one,
+            // their errors are reported instead.
+            Err(ConfigError::PathNotFound) => {
+                match Config::try_load_overrides(&self.options) {
+                    Ok(config) => {
+                        config_error = Some(ConfigError::PathNotFound);
+                        config
+                    }
+                    Err(error) => {
+                        config_error = Some(error);
+                        Config::default()
+                    }
+                }
+            }
+            Err(error) => {
+                config_error = Some(error);
+                Config::default()
+            }
+        };
+
+        let terminal_options = &window_options.terminal_options;
+        if config_error.is_none() {
+            if let Err(error) = config.apply_profile(terminal_options.profile.as_deref())
+            {
+                config_error = Some(error);
+            }
+        }
+
+        window_options.override_config(&mut config);
+        (config, config_error)
+    }
+}
+
 #[derive(Serialize, Deserialize, Args, Default, Clone, Debug, PartialEq, Eq)]
 pub struct WindowOptions {
     /// Terminal options which can be passed via IPC.
     #[clap(flatten)]
     pub terminal_options: TerminalOptions,
+
+    /// Title of the window.
+    #[clap(short = 'T', long)]
+    pub title: Option<String>,
+
+    /// WM_CLASS on X11 and app id on Wayland.
+    #[clap(long)]
+    pub class: Option<String>,
+}
+
+impl WindowOptions {
+    pub fn override_config(&self, config: &mut Config) {
+        if let Some(title) = &self.title {
+            config.window.title = Some(title.to_owned());
+        }
+        if let Some(class) = &self.class {
+            config.window.class = Some(class.to_owned());
+        }
+
+        let terminal_options = &self.terminal_options;
+        if let Some(command) = terminal_options.command() {
+            config.shell = command;
+        }
+        if let Some(working_dir) = &terminal_options.working_dir {
+            config.working_dir = Some(working_dir.to_string_lossy().to_string());
+        }
+        config.hold_on_exit |= terminal_options.hold;
+    }
 }
 
 #[derive(Serialize, Deserialize, Args, Default, Debug, Clone, PartialEq, Eq)]
@@ -31,6 +161,18 @@ pub struct TerminalOptions {
     /// Command and args to execute (must be last argument).
     #[clap(short = 'e', long, allow_hyphen_values = true, num_args = 1..)]
     pub command: Vec<String>,
+
+    /// Profile of the configuration to use, `default-profile` otherwise.
+    #[clap(long)]
+    pub profile: Option<String>,
+
+    /// Start the shell in this directory.
+    #[clap(long, value_name = "PATH")]
+    pub working_dir: Option<PathBuf>,
+
+    /// Keep tabs open after their command exits.
+    #[clap(long)]
+    pub hold: bool,
 }
 
 impl TerminalOptions {
@@ -46,20 +188,25 @@ impl TerminalOptions {
             args: args.to_vec(),
         })
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-    // pub fn override_pty_config(&self, pty_config: &mut PtyConfig) {
-    //     if let Some(working_directory) = &self.working_directory {
-    //         if working_directory.is_dir() {
-    //             pty_config.working_directory = Some(working_directory.to_owned());
-    //         } else {
-    //             error!("Invalid working directory: {:?}", working_directory);
-    //         }
-    //     }
-
-    //     if let Some(command) = self.command() {
-    //         pty_config.shell = Some(command);
-    //     }
-
-    //     pty_config.hold |= self.hold;
-    // }
+    #[test]
+    fn options_are_checked_without_a_config_file() {
+        let mut config_options = ConfigOptions {
+            config_file: Some(PathBuf::from("/nonexistent/rio/config.toml")),
+            options: vec![String::from("fonts.size=18")],
+        };
+        let (config, error) = config_options.load(&WindowOptions::default());
+        assert!(matches!(error, Some(ConfigError::PathNotFound)));
+        assert_eq!(config.fonts.size, 18.);
+
+        config_options.options = vec![String::from("fonts.size=big")];
+        let (config, error) = config_options.load(&WindowOptions::default());
+        assert!(matches!(error, Some(ConfigError::ErrLoadingConfig(_))));
+        assert_eq!(config.fonts.size, Config::default().fonts.size);
+    }
 }
@@ -27,6 +27,9 @@ pub struct Clipboard {
 }
 
 impl Clipboard {
+    /// # Safety
+    ///
+    /// `display` must stay valid for as long as the clipboard is used.
     pub unsafe fn new(display: RawDisplayHandle) -> Self {
         match display {
             #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
@@ -20,6 +20,9 @@ pub struct Row<T> {
     /// This is the upper bound on the number of elements in the row, which have been modified
     /// since the last reset. All cells after this point are guaranteed to be equal.
     pub(crate) occ: usize,
+
+    /// A prompt starts on this row (OSC 133;A).
+    pub prompt: bool,
 }
 
 impl<T: PartialEq> PartialEq for Row<T> {
@@ -50,7 +53,11 @@ impl<T: Clone + Default> Row<T> {
             inner.set_len(columns);
         }
 
-        Row { inner, occ: 0 }
+        Row {
+            inner,
+            occ: 0,
+            prompt: false,
+        }
     }
 
     /// Increase the number of columns in the row.
@@ -109,6 +116,7 @@ impl<T: Clone + Default> Row<T> {
         }
 
         self.occ = 0;
+        self.prompt = false;
     }
 }
 
@@ -116,7 +124,11 @@ impl<T: Clone + Default> Row<T> {
 impl<T> Row<T> {
     #[inline]
     pub fn from_vec(vec: Vec<T>, occ: usize) -> Row<T> {
-        Row { inner: vec, occ }
+        Row {
+            inner: vec,
+            occ,
+            prompt: false,
+        }
     }
 
     #[inline]
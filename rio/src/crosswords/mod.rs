@@ -44,7 +44,7 @@ use rio_config::colors::{
 };
 use square::{Hyperlink, LineLength, Square};
 use std::mem;
-use std::ops::{Index, IndexMut, Range};
+use std::ops::{Index, IndexMut, Range, RangeInclusive};
 use std::option::Option;
 use std::ptr;
 use std::sync::Arc;
@@ -151,7 +151,6 @@ impl LineDamageBounds {
     }
 
     #[inline]
-    #[allow(dead_code)]
     pub fn reset(&mut self, num_cols: usize) {
         *self = Self::undamaged(num_cols, self.line);
     }
@@ -163,12 +162,38 @@ impl LineDamageBounds {
     }
 
     #[inline]
-    #[allow(dead_code)]
     pub fn is_damaged(&self) -> bool {
         self.left <= self.right
     }
 }
 
+/// Damage collected since the last call to [`Crosswords::reset_damage`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TermDamage {
+    /// The entire viewport has to be redrawn.
+    Full,
+
+    /// Only the listed viewport lines changed.
+    Partial(Vec<LineDamageBounds>),
+}
+
+/// Viewport state handed to the renderer, taken while holding the lock so the
+/// frame can be prepared without it.
+#[derive(Debug, Clone)]
+pub struct TermSnapshot {
+    /// Viewport rows, `None` for the ones without damage since the previous
+    /// snapshot.
+    pub rows: Vec<Option<Row<Square>>>,
+    /// Row right above the viewport, partly shown while scrolling pixel by
+    /// pixel. `None` at the top of the history.
+    pub above: Option<Row<Square>>,
+    pub cursor: CursorState,
+    pub display_offset: usize,
+    pub history_size: usize,
+    pub blinking_cursor: bool,
+    pub selection: Option<SelectionRange>,
+}
+
 #[derive(Debug, Clone)]
 struct TermDamageState {
     /// Hint whether terminal should be damaged entirely regardless of the actual damage changes.
@@ -228,28 +253,25 @@ impl TermDamageState {
         self.lines[line].expand(left, right);
     }
 
-    #[allow(dead_code)]
-    fn damage_selection(
-        &mut self,
+    /// Viewport lines covered by `selection`, if any of it is visible.
+    fn selection_lines(
+        &self,
         selection: SelectionRange,
         display_offset: usize,
-        num_cols: usize,
-    ) {
+    ) -> Option<RangeInclusive<usize>> {
         let display_offset = display_offset as i32;
         let last_visible_line = self.lines.len() as i32 - 1;
 
         // Don't damage invisible selection.
         if selection.end.row.0 + display_offset < 0
-            || selection.start.row.0.abs() < display_offset - last_visible_line
+            || selection.start.row.0 + display_offset > last_visible_line
         {
-            return;
+            return None;
         };
 
         let start = std::cmp::max(selection.start.row.0 + display_offset, 0);
         let end = (selection.end.row.0 + display_offset).clamp(0, last_visible_line);
-        for line in start as usize..=end as usize {
-            self.damage_line(line, 0, num_cols - 1);
-        }
+        Some(start as usize..=end as usize)
     }
 
     /// Reset information about terminal damage.
@@ -357,6 +379,16 @@ where
     damage: TermDamageState,
     pub cursor_shape: CursorShape,
     pub blinking_cursor: bool,
+    /// Keep the terminal open when its child exits.
+    pub hold: bool,
+    /// Set once the child exited while holding, nothing is written to the
+    /// PTY anymore.
+    pub held: bool,
+    /// Process in the foreground of the PTY, updated by the `Machine`.
+    pub foreground_process: Option<teletypewriter::ForegroundProcess>,
+    /// Lines of the prompts of `grid`, moved along as it scrolls. Set to
+    /// `None` by the changes they can't follow, the rows are scanned again.
+    prompt_lines: Option<Vec<Line>>,
     window_id: WindowId,
     title_stack: Vec<String>,
 
@@ -401,6 +433,10 @@ impl<U: EventListener> Crosswords<U> {
             damage: TermDamageState::new(cols, rows),
             cursor_shape: CursorShape::Block,
             blinking_cursor: false,
+            hold: false,
+            held: false,
+            foreground_process: None,
+            prompt_lines: Some(Vec::new()),
             window_id,
             title_stack: Default::default(),
             keyboard_mode_stack: Default::default(),
@@ -412,15 +448,142 @@ impl<U: EventListener> Crosswords<U> {
         self.damage.is_fully_damaged = true;
     }
 
-    #[allow(dead_code)]
     pub fn reset_damage(&mut self) {
         self.damage.reset(self.grid.columns());
     }
 
+    /// Collect the damage since the last reset, in viewport lines.
+    ///
+    /// The cursor and `selection` are compared against the ones from the
+    /// previous call, so both old and new positions are reported.
+    pub fn damage(&mut self, selection: Option<SelectionRange>) -> TermDamage {
+        let previous_cursor =
+            mem::replace(&mut self.damage.last_cursor, self.grid.cursor.pos);
+        let previous_selection = mem::replace(&mut self.damage.last_selection, selection);
+
+        if self.damage.is_fully_damaged {
+            return TermDamage::Full;
+        }
+
+        if previous_cursor != self.grid.cursor.pos {
+            self.damage.damage_point(previous_cursor);
+        }
+        self.damage_cursor();
+
+        // Damage is tracked for grid lines, which are pushed down in the
+        // viewport by the display offset. Anything changed while scrolled
+        // back is covered by full damage from `scroll_display`.
+        let display_offset = self.grid.display_offset();
+        let num_cols = self.grid.columns();
+        let mut lines: Vec<LineDamageBounds> = (0..self.damage.lines.len())
+            .map(|line| LineDamageBounds::undamaged(num_cols, line))
+            .collect();
+        for damage in self.damage.lines.iter().filter(|line| line.is_damaged()) {
+            if let Some(line) = lines.get_mut(damage.line + display_offset) {
+                line.expand(damage.left, damage.right);
+            }
+        }
+
+        if previous_selection != selection {
+            let selections = [previous_selection, selection].into_iter().flatten();
+            for selection in selections {
+                if let Some(range) =
+                    self.damage.selection_lines(selection, display_offset)
+                {
+                    for line in &mut lines[range] {
+                        line.expand(0, num_cols - 1);
+                    }
+                }
+            }
+        }
+
+        lines.retain(|line| line.is_damaged());
+        TermDamage::Partial(lines)
+    }
+
+    /// Take a [`TermSnapshot`] and reset the damage.
+    ///
+    /// Only damaged rows are copied, the renderer keeps the rest around from
+    /// earlier snapshots.
+    pub fn snapshot(&mut self) -> TermSnapshot {
+        let selection = self
+            .selection
+            .as_ref()
+            .and_then(|selection| selection.to_range(self));
+
+        let rows = match self.damage(selection) {
+            TermDamage::Full => self.visible_rows().into_iter().map(Some).collect(),
+            TermDamage::Partial(lines) => {
+                let display_offset = self.grid.display_offset() as i32;
+                let mut rows = vec![None; self.grid.screen_lines()];
+                for line in lines {
+                    let row = Line(line.line as i32 - display_offset);
+                    rows[line.line] = Some(self.grid[row].clone());
+                }
+                rows
+            }
+        };
+        self.reset_damage();
+
+        let display_offset = self.grid.display_offset();
+        let above = (display_offset < self.grid.history_size())
+            .then(|| self.grid[Line(-(display_offset as i32) - 1)].clone());
+
+        TermSnapshot {
+            rows,
+            above,
+            cursor: self.cursor(),
+            display_offset: self.grid.display_offset(),
+            history_size: self.grid.history_size(),
+            blinking_cursor: self.blinking_cursor,
+            selection,
+        }
+    }
+
     pub fn display_offset(&mut self) -> usize {
         self.grid.display_offset()
     }
 
+    /// Lines where prompts start, from the top of the history.
+    pub fn prompt_lines(&mut self) -> Vec<Line> {
+        let grid = &self.grid;
+        let lines = self.prompt_lines.get_or_insert_with(|| {
+            (grid.topmost_line().0..=grid.bottommost_line().0)
+                .map(Line)
+                .filter(|line| grid[*line].prompt)
+                .collect()
+        });
+
+        // Rows cleared since are still in there.
+        lines.retain(|line| {
+            *line >= grid.topmost_line()
+                && *line <= grid.bottommost_line()
+                && grid[*line].prompt
+        });
+        lines.clone()
+    }
+
+    /// Move the prompts after `region` scrolled by `delta` lines. Only the
+    /// whole screen scrolling up is followed, like output going into the
+    /// history, the rows are scanned again after the others.
+    #[inline]
+    fn scroll_prompt_lines(&mut self, region: &Range<Line>, delta: i32) {
+        let Some(lines) = &mut self.prompt_lines else {
+            return;
+        };
+
+        let screen_lines = self.grid.screen_lines() as i32;
+        if delta < 0 && region.start == 0 && region.end == screen_lines {
+            let topmost = self.grid.topmost_line();
+            lines.retain_mut(|line| {
+                *line += delta;
+                *line >= topmost
+            });
+        } else if !lines.is_empty() {
+            self.prompt_lines = None;
+        }
+    }
+
     pub fn clear_saved_history(&mut self) {
         self.clear_screen(ClearMode::Saved);
     }
@@ -484,6 +647,7 @@ impl<U: EventListener> Crosswords<U> {
         let is_alt = self.mode.contains(Mode::ALT_SCREEN);
         self.grid.resize(!is_alt, num_lines, num_cols);
         self.inactive_grid.resize(is_alt, num_lines, num_cols);
+        self.prompt_lines = None;
 
         // Invalidate selection and tabs only when necessary.
         if old_cols != num_cols {
@@ -694,7 +858,26 @@ impl<U: EventListener> Crosswords<U> {
 
         // Scroll between origin and bottom
         self.grid.scroll_down(&region, lines);
-        self.mark_fully_damaged();
+        self.scroll_prompt_lines(&region, lines as i32);
+        self.damage_scrolled_region(&region);
+    }
+
+    /// Damage the rows of `region` after scrolling it, or everything when
+    /// the history and the viewport moved along.
+    #[inline]
+    fn damage_scrolled_region(&mut self, region: &Range<Line>) {
+        let screen_lines = self.grid.screen_lines() as i32;
+        if self.grid.display_offset() != 0
+            || (region.start == 0 && region.end == screen_lines)
+        {
+            self.mark_fully_damaged();
+            return;
+        }
+
+        let columns = self.grid.columns();
+        for line in region.start.0.max(0)..region.end.0.min(screen_lines) {
+            self.damage.damage_line(line as usize, 0, columns - 1);
+        }
     }
 
     #[inline]
@@ -715,6 +898,7 @@ impl<U: EventListener> Crosswords<U> {
             .and_then(|s| s.rotate(&self.grid, &region, lines as i32));
 
         self.grid.scroll_up(&region, lines);
+        self.scroll_prompt_lines(&region, -(lines as i32));
 
         // Scroll vi mode cursor.
         let viewport_top = Line(-(self.grid.display_offset() as i32));
@@ -727,7 +911,7 @@ impl<U: EventListener> Crosswords<U> {
         if (top <= *line) && region.end > *line {
             *line = std::cmp::max(*line - lines, top);
         }
-        self.mark_fully_damaged();
+        self.damage_scrolled_region(&region);
     }
 
     #[inline]
@@ -836,6 +1020,7 @@ impl<U: EventListener> Crosswords<U> {
         let bg = self.grid.cursor.template.bg;
         let flags = self.grid.cursor.template.flags;
         let extra = self.grid.cursor.template.extra.clone();
+        self.damage_cursor();
 
         let mut cursor_square = self.grid.cursor_square();
         if cursor_square
@@ -871,23 +1056,70 @@ impl<U: EventListener> Crosswords<U> {
         cursor_square.extra = extra;
     }
 
-    #[inline]
-    pub fn visible_rows(&mut self) -> Vec<Row<Square>> {
-        let mut visible_rows = vec![];
-        let mut start = self.scroll_region.start.0;
-        let mut end = self.scroll_region.end.0;
+    /// Write a run of printable ASCII, filling a row at a time.
+    ///
+    /// Produces the same grid as calling `input` for every byte outside of
+    /// insert mode.
+    fn write_ascii(&mut self, mut bytes: &[u8]) {
+        while !bytes.is_empty() {
+            if self.grid.cursor.should_wrap {
+                self.wrapline();
 
-        let scroll = self.display_offset() as i32;
-        if scroll != 0 {
-            start -= scroll;
-            end -= scroll;
-        }
+                // Without line wrapping every character overwrites the last
+                // column, so only the last one ends up on the screen.
+                if self.grid.cursor.should_wrap {
+                    bytes = &bytes[bytes.len() - 1..];
+                }
+            }
+
+            let columns = self.grid.columns();
+            let Pos { row, col } = self.grid.cursor.pos;
+            let count = bytes.len().min(columns - col.0);
+            let (chunk, remaining) = bytes.split_at(count);
+            bytes = remaining;
+
+            self.damage
+                .damage_line(row.0 as usize, col.0, col.0 + count - 1);
+
+            let charset = self.grid.cursor.charsets[self.active_charset];
+            let template = self.grid.cursor.template.clone();
+            let cells = &mut self.grid[row][col..col + count];
+            if cells.iter().any(|cell| {
+                cell.flags.intersects(
+                    square::Flags::WIDE_CHAR | square::Flags::WIDE_CHAR_SPACER,
+                )
+            }) {
+                // Wide chars have to be cleaned up cell by cell.
+                for (offset, byte) in chunk.iter().enumerate() {
+                    self.grid.cursor.pos.col = col + offset;
+                    self.write_at_cursor(*byte as char);
+                }
+            } else {
+                for (cell, byte) in cells.iter_mut().zip(chunk) {
+                    cell.c = charset.map(*byte as char);
+                    cell.fg = template.fg;
+                    cell.bg = template.bg;
+                    cell.flags = template.flags;
+                    cell.extra = template.extra.clone();
+                }
+            }
 
-        for row in start..end {
-            visible_rows.push(self.grid[Line(row)].to_owned());
+            if col.0 + count < columns {
+                self.grid.cursor.pos.col = col + count;
+            } else {
+                self.grid.cursor.pos.col = col + (count - 1);
+                self.grid.cursor.should_wrap = true;
+            }
         }
+    }
 
-        visible_rows
+    #[inline]
+    pub fn visible_rows(&mut self) -> Vec<Row<Square>> {
+        // The whole viewport, so rows match the damaged lines one to one.
+        let scroll = self.display_offset() as i32;
+        (0..self.grid.screen_lines() as i32)
+            .map(|row| self.grid[Line(row - scroll)].to_owned())
+            .collect()
     }
 
     fn deccolm(&mut self)
@@ -962,6 +1194,7 @@ impl<U: EventListener> Crosswords<U> {
         mem::swap(&mut self.grid, &mut self.inactive_grid);
         self.mode ^= Mode::ALT_SCREEN;
         self.selection = None;
+        self.prompt_lines = None;
         self.mark_fully_damaged();
     }
 
@@ -1529,6 +1762,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
         // self.cursor_style = None;
         self.grid.reset();
         self.inactive_grid.reset();
+        self.prompt_lines = Some(Vec::new());
         self.scroll_region = Line(0)..Line(self.grid.screen_lines() as i32);
         self.tabs = TabStops::new(self.grid.columns());
         self.title_stack = Vec::new();
@@ -1619,7 +1853,11 @@ impl<U: EventListener> Handler for Crosswords<U> {
             Some(title) => title,
             None => String::from(""),
         };
-        self.title = title_str;
+        if self.title != title_str {
+            self.title = title_str;
+            self.event_proxy
+                .send_event(RioEvent::TerminalTitle, self.window_id);
+        }
     }
 
     #[inline]
@@ -1706,6 +1944,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
 
             self.grid[row][column].push_zerowidth(c);
+            self.damage.damage_point(Pos::new(row, column));
             return;
         }
 
@@ -1718,6 +1957,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
         {
             let line = self.grid.cursor.pos.row;
             let col = self.grid.cursor.pos.col;
+            self.damage.damage_line(line.0 as usize, col.0, columns - 1);
             let row = &mut self.grid[line][..];
 
             for col in (col.0..(columns - width)).rev() {
@@ -1784,6 +2024,29 @@ impl<U: EventListener> Handler for Crosswords<U> {
         }
     }
 
+    #[inline]
+    fn input_str(&mut self, s: &str) {
+        let mut rest = s;
+
+        while !rest.is_empty() {
+            // Printable ASCII always takes one cell, anything else needs the
+            // width lookup and wide char handling from `input`.
+            let ascii_len = rest
+                .bytes()
+                .position(|byte| !(0x20..0x7f).contains(&byte))
+                .unwrap_or(rest.len());
+
+            if ascii_len == 0 || self.mode.contains(Mode::INSERT) {
+                let c = rest.chars().next().unwrap();
+                self.input(c);
+                rest = &rest[c.len_utf8()..];
+            } else {
+                self.write_ascii(&rest.as_bytes()[..ascii_len]);
+                rest = &rest[ascii_len..];
+            }
+        }
+    }
+
     #[inline]
     fn identify_terminal(&mut self, intermediate: Option<char>) {
         match intermediate {
@@ -1938,6 +2201,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
                     let old_offset = self.grid.display_offset();
 
                     self.grid.clear_viewport();
+                    self.prompt_lines = None;
 
                     // Compute number of lines scrolled by clearing the viewport.
                     let lines = self.grid.display_offset().saturating_sub(old_offset);
@@ -1950,6 +2214,7 @@ impl<U: EventListener> Handler for Crosswords<U> {
             }
             ClearMode::Saved if self.history_size() > 0 => {
                 self.grid.clear_history();
+                self.prompt_lines = None;
 
                 self.vi_mode_cursor.pos.row = self
                     .vi_mode_cursor
@@ -2003,6 +2268,17 @@ impl<U: EventListener> Handler for Crosswords<U> {
         self.grid.cursor.template.set_hyperlink(hyperlink);
     }
 
+    #[inline]
+    fn mark_prompt(&mut self) {
+        let row = self.grid.cursor.pos.row;
+        self.grid[row].prompt = true;
+        if let Some(lines) = &mut self.prompt_lines {
+            if let Err(index) = lines.binary_search(&row) {
+                lines.insert(index, row);
+            }
+        }
+    }
+
     /// Set the indexed color value.
     #[inline]
     fn set_color(&mut self, _index: usize, _color: ColorRgb) {
@@ -2224,7 +2500,7 @@ pub mod test {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::crosswords::pos::{Column, Line, Pos, Side};
+    use crate::crosswords::pos::{Column, Line, Pos, Side, StandardCharset};
     use crate::crosswords::test::CrosswordsSize;
     use crate::event::VoidListener;
     use winit::window::WindowId;
@@ -2497,6 +2773,262 @@ mod tests {
         );
     }
 
+    /// Feed `input` per char and batched, asserting both grids are the same.
+    fn input_per_char_and_batched(
+        setup: impl Fn(&mut Crosswords<VoidListener>),
+        input: &str,
+    ) -> Crosswords<VoidListener> {
+        let mut per_char = Crosswords::new(7, 3, VoidListener {}, WindowId::from(0));
+        let mut batched = Crosswords::new(7, 3, VoidListener {}, WindowId::from(0));
+        setup(&mut per_char);
+        setup(&mut batched);
+
+        for c in input.chars() {
+            per_char.input(c);
+        }
+        batched.input_str(input);
+
+        assert_eq!(per_char.grid.total_lines(), batched.grid.total_lines());
+        let lines = per_char.grid.topmost_line().0..=per_char.grid.bottommost_line().0;
+        for line in lines {
+            assert_eq!(
+                per_char.grid[Line(line)][..],
+                batched.grid[Line(line)][..],
+                "line {line} differs for {input:?}"
+            );
+        }
+        assert_eq!(per_char.grid.cursor.pos, batched.grid.cursor.pos);
+        assert_eq!(
+            per_char.grid.cursor.should_wrap,
+            batched.grid.cursor.should_wrap
+        );
+
+        batched
+    }
+
+    #[test]
+    fn input_str_matches_input() {
+        let inputs = [
+            "hello",
+            "a line longer than seven columns wraps and scrolls",
+            "abc日本語def😀g\u{7f}h\u{301}ij",
+            "ab\u{301}cdefghijklmn",
+        ];
+
+        for input in inputs {
+            input_per_char_and_batched(|_| {}, input);
+        }
+    }
+
+    #[test]
+    fn input_str_over_wide_chars() {
+        let batched = input_per_char_and_batched(
+            |term| {
+                term.input_str("日本語");
+                term.goto(Line(0), Column(1));
+            },
+            "abcd",
+        );
+
+        assert_eq!(batched.grid[Line(0)][Column(0)].c, ' ');
+        assert_eq!(batched.grid[Line(0)][Column(5)].c, ' ');
+    }
+
+    #[test]
+    fn input_str_without_line_wrap() {
+        let batched = input_per_char_and_batched(
+            |term| term.mode.remove(Mode::LINE_WRAP),
+            "abcdefghijkl",
+        );
+
+        assert_eq!(batched.grid[Line(0)][Column(6)].c, 'l');
+        assert_eq!(batched.grid.cursor.pos.row, Line(0));
+    }
+
+    #[test]
+    fn input_str_uses_charset() {
+        let batched = input_per_char_and_batched(
+            |term| {
+                term.configure_charset(
+                    CharsetIndex::G0,
+                    StandardCharset::SpecialCharacterAndLineDrawing,
+                )
+            },
+            "lqqk",
+        );
+
+        assert_eq!(batched.grid[Line(0)][Column(0)].c, '┌');
+    }
+
+    fn damaged_lines(damage: TermDamage) -> Vec<usize> {
+        match damage {
+            TermDamage::Full => panic!("expected partial damage"),
+            TermDamage::Partial(lines) => lines.iter().map(|line| line.line).collect(),
+        }
+    }
+
+    #[test]
+    fn damage_starts_full_and_resets() {
+        let mut cw = Crosswords::new(10, 5, VoidListener {}, WindowId::from(0));
+        assert_eq!(cw.damage(None), TermDamage::Full);
+
+        cw.reset_damage();
+        assert_eq!(damaged_lines(cw.damage(None)), vec![0]);
+    }
+
+    #[test]
+    fn damage_tracks_input_and_cursor() {
+        let mut cw = Crosswords::new(10, 5, VoidListener {}, WindowId::from(0));
+        cw.damage(None);
+        cw.reset_damage();
+
+        cw.goto(Line(2), Column(0));
+        cw.input_str("abc");
+        cw.goto(Line(3), Column(4));
+        cw.input('d');
+
+        let damage = cw.damage(None);
+        assert_eq!(damaged_lines(damage.clone()), vec![0, 2, 3]);
+        if let TermDamage::Partial(lines) = damage {
+            assert_eq!((lines[1].left, lines[1].right), (0, 3));
+        }
+        cw.reset_damage();
+
+        // Only the old and new cursor positions are left once it moves.
+        cw.goto(Line(1), Column(0));
+        assert_eq!(damaged_lines(cw.damage(None)), vec![1, 3]);
+    }
+
+    #[test]
+    fn damage_tracks_selection() {
+        let mut cw = Crosswords::new(10, 5, VoidListener {}, WindowId::from(0));
+        cw.damage(None);
+        cw.reset_damage();
+
+        let selection = SelectionRange::new(
+            Pos::new(Line(1), Column(2)),
+            Pos::new(Line(2), Column(3)),
+            false,
+        );
+        assert_eq!(damaged_lines(cw.damage(Some(selection))), vec![0, 1, 2]);
+        cw.reset_damage();
+
+        // An unchanged selection doesn't damage anything.
+        assert_eq!(damaged_lines(cw.damage(Some(selection))), vec![0]);
+        cw.reset_damage();
+
+        assert_eq!(damaged_lines(cw.damage(None)), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn damage_is_full_after_scrolling_the_display() {
+        let mut cw = Crosswords::new(10, 5, VoidListener {}, WindowId::from(0));
+        for _ in 0..10 {
+            cw.linefeed();
+        }
+        cw.damage(None);
+        cw.reset_damage();
+
+        cw.scroll_display(Scroll::Delta(2));
+        assert_eq!(cw.damage(None), TermDamage::Full);
+    }
+
+    #[test]
+    fn snapshot_only_copies_damaged_rows() {
+        let mut cw = Crosswords::new(10, 5, VoidListener {}, WindowId::from(0));
+        let snapshot = cw.snapshot();
+        assert!(snapshot.rows.iter().all(Option::is_some));
+
+        cw.goto(Line(3), Column(0));
+        cw.input_str("abc");
+        let snapshot = cw.snapshot();
+        let damaged: Vec<usize> =
+            (0..5).filter(|i| snapshot.rows[*i].is_some()).collect();
+        assert_eq!(damaged, vec![0, 3]);
+        assert_eq!(snapshot.rows[3].as_ref().unwrap()[Column(1)].c, 'b');
+        assert_eq!(snapshot.cursor.pos, Pos::new(Line(3), Column(3)));
+
+        // The damage was reset by the previous snapshot.
+        let snapshot = cw.snapshot();
+        let damaged: Vec<usize> =
+            (0..5).filter(|i| snapshot.rows[*i].is_some()).collect();
+        assert_eq!(damaged, vec![3]);
+    }
+
+    #[test]
+    fn scroll_regions_and_insert_mode_keep_damage_partial() {
+        let mut cw = Crosswords::new(10, 5, VoidListener {}, WindowId::from(0));
+        // Like tmux, a status line below the region.
+        cw.set_scrolling_region(1, Some(4));
+        cw.goto(Line(1), Column(0));
+        cw.snapshot();
+
+        cw.input_str("abc");
+        let snapshot = cw.snapshot();
+        assert_eq!(snapshot.rows.len(), 5);
+        let damaged: Vec<usize> =
+            (0..5).filter(|i| snapshot.rows[*i].is_some()).collect();
+        assert_eq!(damaged, vec![1]);
+
+        cw.goto(Line(3), Column(0));
+        cw.snapshot();
+        cw.linefeed();
+        let snapshot = cw.snapshot();
+        let damaged: Vec<usize> =
+            (0..5).filter(|i| snapshot.rows[*i].is_some()).collect();
+        assert_eq!(damaged, vec![0, 1, 2, 3]);
+        assert_eq!(snapshot.rows[0].as_ref().unwrap()[Column(0)].c, 'a');
+
+        cw.set_mode(AnsiMode::Insert);
+        cw.goto(Line(2), Column(0));
+        cw.snapshot();
+        cw.input('x');
+        let snapshot = cw.snapshot();
+        let damaged: Vec<usize> =
+            (0..5).filter(|i| snapshot.rows[*i].is_some()).collect();
+        assert_eq!(damaged, vec![2]);
+    }
+
+    #[test]
+    fn prompt_marks_follow_their_rows_into_history() {
+        let mut cw = Crosswords::new(10, 5, VoidListener {}, WindowId::from(0));
+        cw.goto(Line(1), Column(0));
+        cw.mark_prompt();
+        assert_eq!(cw.prompt_lines(), vec![Line(1)]);
+
+        for _ in 0..5 {
+            cw.linefeed();
+        }
+        assert_eq!(cw.prompt_lines(), vec![Line(-1)]);
+    }
+
+    #[test]
+    fn prompt_lines_are_followed_without_scanning_the_rows() {
+        let mut cw = Crosswords::new(10, 5, VoidListener {}, WindowId::from(0));
+        cw.mark_prompt();
+        for _ in 0..7 {
+            cw.linefeed();
+        }
+        cw.mark_prompt();
+        cw.goto(Line(2), Column(0));
+        cw.mark_prompt();
+        assert_eq!(cw.prompt_lines, Some(vec![Line(-3), Line(2), Line(4)]));
+
+        // Cleared rows are dropped once read.
+        cw.goto(Line(1), Column(0));
+        cw.clear_screen(ClearMode::Below);
+        assert_eq!(cw.prompt_lines(), vec![Line(-3)]);
+
+        // Scrolling only part of the screen isn't followed.
+        cw.goto(Line(4), Column(0));
+        cw.mark_prompt();
+        cw.set_scrolling_region(2, Some(5));
+        cw.goto(Line(4), Column(0));
+        cw.linefeed();
+        assert_eq!(cw.prompt_lines, None);
+        assert_eq!(cw.prompt_lines(), vec![Line(-4), Line(3)]);
+    }
+
     #[test]
     fn parse_cargo_version() {
         assert_eq!(version_number("0.0.1-canary"), 1);
@@ -2,13 +2,15 @@ pub mod sync;
 
 use crate::clipboard::ClipboardType;
 use crate::crosswords::grid::Scroll;
+use crate::router::confirm::Confirmation;
 use crate::router::ErrorReport;
 use rio_config::colors::ColorRgb;
 use std::borrow::Cow;
 use std::fmt::Debug;
 use std::fmt::Formatter;
-use std::sync::Arc;
-use teletypewriter::WinsizeBuilder;
+use std::path::PathBuf;
+use std::sync::{mpsc, Arc};
+use teletypewriter::{ExitStatus, ForegroundProcess, WinsizeBuilder};
 use winit::event_loop::EventLoopProxy;
 use winit::window::WindowId;
 
@@ -31,6 +33,15 @@ pub enum ClickState {
     TripleClick,
 }
 
+/// Screenshot waiting for the next frame of a window.
+#[derive(Debug, Clone, Default)]
+pub struct ScreenshotRequest {
+    /// File or directory to save to, `screenshot-directory` otherwise.
+    pub path: Option<PathBuf>,
+    /// Receives where the screenshot was saved or why it failed.
+    pub reply: Option<mpsc::Sender<Result<PathBuf, String>>>,
+}
+
 #[derive(Clone)]
 pub enum RioEvent {
     PrepareRender(u64),
@@ -49,6 +60,9 @@ pub enum RioEvent {
     SelectNativeTabNext,
     SelectNativeTabPrev,
 
+    /// Save the next frame as a PNG.
+    Screenshot(ScreenshotRequest),
+
     ReportToAssistant(ErrorReport),
 
     /// Grid has changed possibly requiring a mouse cursor shape change.
@@ -60,6 +74,9 @@ pub enum RioEvent {
     /// Reset to the default window title.
     ResetTitle,
 
+    /// A terminal changed its own title, with OSC 0 or 2.
+    TerminalTitle,
+
     /// Request to store a text string in the clipboard.
     ClipboardStore(ClipboardType, String),
 
@@ -96,6 +113,18 @@ pub enum RioEvent {
     /// Terminal bell ring.
     Bell,
 
+    /// The child of a terminal exited, sent before it's held or closed.
+    ChildExit(ExitStatus),
+
+    /// Another process took the foreground of a terminal.
+    ForegroundProcess(ForegroundProcess),
+
+    /// Closing waits for the user to confirm it.
+    ConfirmClose(Confirmation),
+
+    /// Quit Rio, after a confirmation if programs are running in any window.
+    Quit,
+
     /// Shutdown request.
     Exit,
 }
@@ -116,11 +145,20 @@ impl Debug for RioEvent {
             RioEvent::CursorBlinkingChange => write!(f, "CursorBlinkingChange"),
             RioEvent::MouseCursorDirty => write!(f, "MouseCursorDirty"),
             RioEvent::ResetTitle => write!(f, "ResetTitle"),
+            RioEvent::TerminalTitle => write!(f, "TerminalTitle"),
             RioEvent::Wakeup => write!(f, "Wakeup"),
             RioEvent::PrepareRender(millis) => write!(f, "PrepareRender({millis})"),
             RioEvent::Render => write!(f, "Render"),
             RioEvent::Scroll(scroll) => write!(f, "Scroll {scroll:?}"),
             RioEvent::Bell => write!(f, "Bell"),
+            RioEvent::ChildExit(status) => write!(f, "ChildExit({status})"),
+            RioEvent::ForegroundProcess(process) => {
+                write!(f, "ForegroundProcess({}, {})", process.pid, process.name)
+            }
+            RioEvent::ConfirmClose(confirmation) => {
+                write!(f, "ConfirmClose({:?})", confirmation.action)
+            }
+            RioEvent::Quit => write!(f, "Quit"),
             RioEvent::Exit => write!(f, "Exit"),
             RioEvent::CreateWindow => write!(f, "CreateWindow"),
             RioEvent::CloseWindow => write!(f, "CloseWindow"),
@@ -137,6 +175,7 @@ impl Debug for RioEvent {
                 write!(f, "ReportToAssistant({})", error_report.report)
             }
             RioEvent::ToggleFullScreen => write!(f, "FullScreen"),
+            RioEvent::Screenshot(request) => write!(f, "Screenshot({:?})", request.path),
         }
     }
 }
@@ -0,0 +1,200 @@
+// Unix socket to send messages to a running Rio, like `rio msg screenshot`.
+// Each connection sends one JSON message per line and gets the reply back
+// as a JSON `Result`.
+
+use crate::cli::SocketMessage;
+use crate::event::{EventProxy, RioEvent, ScreenshotRequest};
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::{mpsc, OnceLock};
+use std::time::Duration;
+use winit::window::WindowId;
+
+/// Environment variable with the socket of the instance, so programs
+/// running in Rio can talk to it.
+pub const RIO_SOCKET_ENV: &str = "RIO_SOCKET";
+
+/// Windows that aren't drawing, like minimized ones, never take the
+/// screenshot.
+const REPLY_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Reply = Result<String, String>;
+
+static SOCKET_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Socket of this instance, once it's listening.
+#[inline]
+pub fn socket_path() -> Option<&'static Path> {
+    SOCKET_PATH.get().map(PathBuf::as_path)
+}
+
+/// Listen for messages on a socket of this instance.
+pub fn spawn_ipc_socket(event_proxy: EventProxy) {
+    let socket_dir = std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(std::env::temp_dir);
+    let Some(listener) = bind_socket(&socket_dir) else {
+        return;
+    };
+
+    std::thread::spawn(move || {
+        for stream in listener.incoming().filter_map(Result::ok) {
+            if let Err(err) = handle_connection(stream, &event_proxy) {
+                log::warn!("unable to reply ipc message: {err}");
+            }
+        }
+    });
+}
+
+fn bind_socket(socket_dir: &Path) -> Option<UnixListener> {
+    let socket_path = socket_dir.join(format!("rio-{}.sock", std::process::id()));
+    let _ = std::fs::remove_file(&socket_path);
+
+    match UnixListener::bind(&socket_path) {
+        Ok(listener) => {
+            let _ = SOCKET_PATH.set(socket_path);
+            Some(listener)
+        }
+        Err(err) => {
+            log::warn!(
+                "unable to create ipc socket {}: {err}",
+                socket_path.display()
+            );
+            None
+        }
+    }
+}
+
+/// Remove the socket of this instance, so `rio msg` doesn't find it once
+/// Rio is gone. Called right before exiting.
+pub fn remove_socket() {
+    if let Some(socket_path) = socket_path() {
+        let _ = std::fs::remove_file(socket_path);
+    }
+}
+
+fn handle_connection(
+    stream: UnixStream,
+    event_proxy: &EventProxy,
+) -> std::io::Result<()> {
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+
+    let reply: Reply = match serde_json::from_str::<SocketMessage>(&line) {
+        Ok(SocketMessage::Screenshot(options)) => {
+            let (sender, receiver) = mpsc::channel();
+            event_proxy.send_event(
+                RioEvent::Screenshot(ScreenshotRequest {
+                    path: options.output,
+                    reply: Some(sender),
+                })
+                .into(),
+                WindowId::from(0),
+            );
+
+            match receiver.recv_timeout(REPLY_TIMEOUT) {
+                Ok(result) => result.map(|path| path.display().to_string()),
+                Err(_) => Err(String::from("timed out waiting for the screenshot")),
+            }
+        }
+        Err(err) => Err(format!("invalid message: {err}")),
+    };
+
+    write_line(&stream, &reply)
+}
+
+fn write_line<T: serde::Serialize>(
+    mut stream: &UnixStream,
+    value: &T,
+) -> std::io::Result<()> {
+    let mut message = serde_json::to_string(value)?;
+    message.push('\n');
+    stream.write_all(message.as_bytes())
+}
+
+/// Send a message to the instance at `socket`, or the one this process
+/// runs in, returning its reply.
+pub fn send_message(socket: Option<PathBuf>, mut message: SocketMessage) -> Reply {
+    let socket = socket
+        .or_else(|| std::env::var_os(RIO_SOCKET_ENV).map(PathBuf::from))
+        .ok_or_else(|| format!("no socket given and {RIO_SOCKET_ENV} isn't set"))?;
+
+    // Rio runs somewhere else, relative paths start from here.
+    let SocketMessage::Screenshot(options) = &mut message;
+    if let Some(output) = &mut options.output {
+        if output.is_relative() {
+            if let Ok(current_dir) = std::env::current_dir() {
+                *output = current_dir.join(&*output);
+            }
+        }
+    }
+
+    let connect = || -> std::io::Result<String> {
+        let stream = UnixStream::connect(&socket)?;
+        write_line(&stream, &message)?;
+
+        let mut line = String::new();
+        BufReader::new(&stream).read_line(&mut line)?;
+        Ok(line)
+    };
+    let line = connect().map_err(|err| format!("{}: {err}", socket.display()))?;
+
+    serde_json::from_str::<Reply>(&line).map_err(|err| format!("invalid reply: {err}"))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cli::ScreenshotOptions;
+
+    #[test]
+    fn messages_are_sent_as_json_lines() {
+        let socket = std::env::temp_dir()
+            .join(format!("rio-ipc-test-{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).unwrap();
+
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut line = String::new();
+            BufReader::new(&stream).read_line(&mut line).unwrap();
+            let message: SocketMessage = serde_json::from_str(&line).unwrap();
+            write_line(&stream, &Reply::Ok(String::from("/tmp/rio-1.png"))).unwrap();
+            message
+        });
+
+        let reply = send_message(
+            Some(socket.clone()),
+            SocketMessage::Screenshot(ScreenshotOptions {
+                output: Some(PathBuf::from("frame.png")),
+            }),
+        );
+        assert_eq!(reply, Ok(String::from("/tmp/rio-1.png")));
+
+        let SocketMessage::Screenshot(options) = server.join().unwrap();
+        let output = options.output.unwrap();
+        assert!(output.is_absolute());
+        assert!(output.ends_with("frame.png"));
+
+        let _ = std::fs::remove_file(socket);
+    }
+
+    #[test]
+    fn socket_is_removed_on_exit() {
+        let socket_dir = std::env::temp_dir()
+            .join(format!("rio-ipc-exit-test-{}", std::process::id()));
+        std::fs::create_dir_all(&socket_dir).unwrap();
+
+        let listener = bind_socket(&socket_dir).unwrap();
+        let socket = socket_path().unwrap();
+        assert!(socket.starts_with(&socket_dir));
+        assert!(socket.exists());
+
+        remove_socket();
+        drop(listener);
+        assert!(!socket.exists());
+
+        let _ = std::fs::remove_dir(socket_dir);
+    }
+}
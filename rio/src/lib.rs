@@ -0,0 +1,20 @@
+pub mod ansi;
+pub mod cli;
+pub mod clipboard;
+pub mod crosswords;
+pub mod event;
+pub mod ime;
+#[cfg(unix)]
+pub mod ipc;
+pub mod logger;
+#[cfg(windows)]
+pub mod panic;
+pub mod performer;
+pub mod platform;
+pub mod router;
+pub mod scheduler;
+pub mod screen;
+pub mod selection;
+pub mod sequencer;
+pub mod ui;
+pub mod watch;
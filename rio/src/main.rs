@@ -4,28 +4,16 @@
 // See https://msdn.microsoft.com/en-us/library/4cc7ya5b.aspx for more details.
 #![windows_subsystem = "windows"]
 
-mod ansi;
-mod cli;
-mod clipboard;
-mod crosswords;
-mod event;
-mod ime;
-mod logger;
-#[cfg(windows)]
-mod panic;
-mod performer;
-mod platform;
-mod router;
-mod scheduler;
-mod screen;
-mod selection;
-mod sequencer;
-mod ui;
-mod watch;
-use crate::event::EventP;
-use crate::sequencer::Sequencer;
 use log::{info, LevelFilter, SetLoggerError};
-use logger::Logger;
+use rioterm::event::EventP;
+#[cfg(unix)]
+use rioterm::ipc;
+use rioterm::logger::Logger;
+#[cfg(windows)]
+use rioterm::panic;
+#[cfg(target_os = "macos")]
+use rioterm::platform;
+use rioterm::{cli, sequencer::Sequencer};
 use std::str::FromStr;
 
 #[cfg(windows)]
@@ -34,19 +22,6 @@ use windows_sys::Win32::System::Console::{
 };
 
 pub fn setup_environment_variables(config: &rio_config::Config) {
-    #[cfg(unix)]
-    let terminfo = if teletypewriter::terminfo_exists("rio") {
-        "rio"
-    } else {
-        "xterm-256color"
-    };
-
-    #[cfg(unix)]
-    {
-        info!("[setup_environment_variables] terminfo: {terminfo}");
-        std::env::set_var("TERM", terminfo);
-    }
-
     // https://github.com/raphamorim/rio/issues/200
     std::env::set_var("TERM_PROGRAM", "rio");
     std::env::set_var("TERM_PROGRAM_VERSION", env!("CARGO_PKG_VERSION"));
@@ -87,6 +62,63 @@ fn setup_logs_by_filter_level(log_level: &str) -> Result<(), SetLoggerError> {
     log::set_logger(&LOGGER).map(|()| log::set_max_level(filter_level))
 }
 
+/// Print the issues of the configuration, returns the exit code.
+fn check_config(config_options: &cli::ConfigOptions, profile: Option<&str>) -> i32 {
+    let path = config_options.path();
+    let path = path.display();
+    let mut has_warnings = false;
+    let errors = match rio_config::Config::try_load_from_path(
+        &config_options.path(),
+        &config_options.options,
+    ) {
+        Ok(mut config) => {
+            let mut errors = vec![];
+            has_warnings = !config.diagnostics.is_empty();
+            if let Err(error) = config.apply_profile(profile) {
+                errors.push(error);
+            }
+            for diagnostic in &config.diagnostics {
+                println!("{}: {diagnostic}", diagnostic.level);
+            }
+            errors
+        }
+        Err(rio_config::ConfigError::PathNotFound) => {
+            println!("{path} does not exist, the default configuration is used");
+            match rio_config::Config::try_load_overrides(&config_options.options) {
+                Ok(_) => return 0,
+                Err(error) => vec![error],
+            }
+        }
+        Err(error) => vec![error],
+    };
+
+    for error in &errors {
+        match error {
+            rio_config::ConfigError::ErrLoadingConfig(diagnostics) => {
+                for diagnostic in diagnostics {
+                    eprintln!("{}: {diagnostic}", diagnostic.level);
+                }
+            }
+            rio_config::ConfigError::ErrLoadingTheme(message) => {
+                eprintln!("error: unable to load the theme: {message}");
+            }
+            rio_config::ConfigError::ProfileNotFound(name) => {
+                eprintln!("error: profile {name} was not found");
+            }
+            rio_config::ConfigError::PathNotFound => {}
+        }
+    }
+
+    if !errors.is_empty() {
+        return 1;
+    }
+
+    if !has_warnings {
+        println!("{path} is valid");
+    }
+    0
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     #[cfg(windows)]
@@ -103,25 +135,32 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // Load command line options.
     let options = cli::Options::new();
 
-    let mut config_error: Option<rio_config::ConfigError> = None;
-    let mut config = match rio_config::Config::try_load() {
-        Ok(config) => config,
-        Err(error) => {
-            config_error = Some(error);
-            rio_config::Config::default()
+    #[cfg(unix)]
+    if let Some(cli::Subcommands::Msg(message_options)) = options.subcommands {
+        match ipc::send_message(message_options.socket, message_options.message) {
+            Ok(reply) => {
+                println!("{reply}");
+                return Ok(());
+            }
+            Err(err) => {
+                eprintln!("{err}");
+                std::process::exit(1);
+            }
         }
-    };
+    }
+
+    if options.check_config {
+        let profile = options.window_options.terminal_options.profile.as_deref();
+        std::process::exit(check_config(&options.config_options, profile));
+    }
+
+    let (mut config, config_error) = options.config_options.load(&options.window_options);
 
     let setup_logs = setup_logs_by_filter_level(&config.developer.log_level);
     if setup_logs.is_err() {
         println!("unable to configure log level");
     }
 
-    if let Some(command) = options.window_options.terminal_options.command() {
-        config.shell = command;
-        config.use_fork = false;
-    }
-
     #[cfg(target_os = "linux")]
     {
         // If running inside a flatpak sandbox.
@@ -138,7 +177,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             .build()
             .unwrap();
 
-    let mut sequencer = Sequencer::new(config, config_error);
+    let mut sequencer = Sequencer::new(
+        config,
+        config_error,
+        options.config_options,
+        options.window_options,
+    );
     let _ = sequencer.run(window_event_loop).await;
 
     #[cfg(windows)]
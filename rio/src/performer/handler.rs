@@ -140,6 +140,13 @@ pub trait Handler {
     /// A character to be displayed.
     fn input(&mut self, _c: char) {}
 
+    /// A run of characters to be displayed, never contains C0 controls.
+    fn input_str(&mut self, s: &str) {
+        for c in s.chars() {
+            self.input(c);
+        }
+    }
+
     /// Set cursor to position.
     fn goto(&mut self, _: Line, _: Column) {}
 
@@ -323,6 +330,9 @@ pub trait Handler {
     /// Set mouse cursor icon.
     fn set_mouse_cursor_icon(&mut self, _: CursorIcon) {}
 
+    /// Mark the start of a prompt on the cursor line.
+    fn mark_prompt(&mut self) {}
+
     /// Report current keyboard mode.
     fn report_keyboard_mode(&mut self) {}
 
@@ -411,6 +421,26 @@ impl ParserProcessor {
         }
     }
 
+    /// Process a slice of bytes from the PTY.
+    #[inline]
+    pub fn advance_bytes<H>(&mut self, handler: &mut H, bytes: &[u8])
+    where
+        H: Handler,
+    {
+        let mut processed = 0;
+        while processed < bytes.len() {
+            if self.state.sync_state.timeout.is_none() {
+                let mut performer = Performer::new(&mut self.state, handler);
+                processed += self
+                    .parser
+                    .advance_bytes(&mut performer, &bytes[processed..]);
+            } else {
+                self.advance_sync(handler, bytes[processed]);
+                processed += 1;
+            }
+        }
+    }
+
     /// End a synchronized update.
     pub fn stop_sync<H>(&mut self, handler: &mut H)
     where
@@ -493,6 +523,16 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
         self.state.preceding_char = Some(c);
     }
 
+    fn print_str(&mut self, s: &str) {
+        self.handler.input_str(s);
+        self.state.preceding_char = s.chars().next_back();
+    }
+
+    #[inline]
+    fn terminated(&self) -> bool {
+        self.state.sync_state.timeout.is_some()
+    }
+
     fn execute(&mut self, byte: u8) {
         log::trace!("[execute] {byte:04x}");
 
@@ -723,6 +763,13 @@ impl<U: Handler> copa::Perform for Performer<'_, U> {
             // Reset text cursor color.
             b"112" => self.handler.reset_color(NamedColor::Cursor as usize),
 
+            // Shell integration, only the start of prompts is used.
+            b"133" => match params.get(1).and_then(|param| param.first()) {
+                Some(b'A') => self.handler.mark_prompt(),
+                Some(b'B' | b'C' | b'D') => (),
+                _ => unhandled(params),
+            },
+
             _ => unhandled(params),
         }
     }
@@ -29,6 +29,19 @@ where
         .expect("thread spawn works")
 }
 
+/// Banner written below the output of a held terminal once its child exits.
+fn exit_banner(status: teletypewriter::ExitStatus) -> String {
+    // Reset the attributes, start a new line and hide the cursor.
+    format!(
+        "\x1b[0m\r\n\x1b[7m Process {status} \x1b[0m Enter to close, r to run it again\x1b[?25l"
+    )
+}
+
+/// Delay after some activity on the PTY before looking at its foreground
+/// process again, so programs that write nothing are noticed too.
+#[cfg(not(target_os = "windows"))]
+const FOREGROUND_CHECK_DELAY: std::time::Duration = std::time::Duration::from_millis(200);
+
 const READ_BUFFER_SIZE: usize = 0x10_0000;
 /// Max bytes to read from the PTY while the terminal is locked.
 const MAX_LOCKED_READ: usize = u16::MAX as usize;
@@ -41,6 +54,11 @@ pub struct Machine<T: teletypewriter::EventedPty, U: EventListener> {
     terminal: Arc<FairMutex<Crosswords<U>>>,
     event_proxy: U,
     window_id: WindowId,
+    #[cfg(not(target_os = "windows"))]
+    foreground: Option<teletypewriter::ForegroundTracker>,
+    /// Next time the foreground process is checked without a read.
+    #[cfg(not(target_os = "windows"))]
+    foreground_check: Option<Instant>,
 }
 
 #[derive(Default)]
@@ -132,9 +150,49 @@ where
             terminal,
             event_proxy,
             window_id,
+            #[cfg(not(target_os = "windows"))]
+            foreground: None,
+            #[cfg(not(target_os = "windows"))]
+            foreground_check: None,
         })
     }
 
+    /// Report the changes of the foreground process of the PTY.
+    #[cfg(not(target_os = "windows"))]
+    pub fn track_foreground(&mut self, tracker: teletypewriter::ForegroundTracker) {
+        self.foreground = Some(tracker);
+        self.schedule_foreground_check();
+    }
+
+    /// Check the foreground process soon, unless it's already planned.
+    #[cfg(not(target_os = "windows"))]
+    #[inline]
+    fn schedule_foreground_check(&mut self) {
+        if self.foreground.is_some() {
+            self.foreground_check
+                .get_or_insert_with(|| Instant::now() + FOREGROUND_CHECK_DELAY);
+        }
+    }
+
+    /// Report the foreground process if it changed, it's checked again
+    /// while a program other than the shell has it.
+    #[cfg(not(target_os = "windows"))]
+    fn check_foreground(&mut self) {
+        self.foreground_check = None;
+        let Some(tracker) = &mut self.foreground else {
+            return;
+        };
+
+        if let Some(process) = tracker.changed() {
+            self.terminal.lock().foreground_process = Some(process.clone());
+            self.event_proxy
+                .send_event(RioEvent::ForegroundProcess(process), self.window_id);
+        }
+        if !tracker.is_shell() {
+            self.schedule_foreground_check();
+        }
+    }
+
     #[inline]
     fn pty_read(&mut self, state: &mut State, buf: &mut [u8]) -> io::Result<()> {
         let mut unprocessed = 0;
@@ -175,9 +233,9 @@ where
             };
 
             // Parse the incoming bytes.
-            for byte in &buf[..unprocessed] {
-                state.parser.advance(&mut **terminal, *byte);
-            }
+            state
+                .parser
+                .advance_bytes(&mut **terminal, &buf[..unprocessed]);
 
             processed += unprocessed;
             unprocessed = 0;
@@ -188,6 +246,16 @@ where
             }
         }
 
+        // Programs starting or exiting are noticed from what they write.
+        #[cfg(not(target_os = "windows"))]
+        if let (Some(tracker), Some(terminal)) = (&mut self.foreground, &mut terminal) {
+            if let Some(process) = tracker.changed() {
+                terminal.foreground_process = Some(process.clone());
+                self.event_proxy
+                    .send_event(RioEvent::ForegroundProcess(process), self.window_id);
+            }
+        }
+
         // Queue terminal redraw unless all processed bytes were synchronized.
         if state.parser.sync_bytes_count() < processed && processed > 0 {
             self.event_proxy
@@ -292,9 +360,14 @@ where
 
             'event_loop: loop {
                 // Wakeup the event loop when a synchronized update timeout was reached.
-                let sync_timeout = state.parser.sync_timeout();
-                let timeout =
-                    sync_timeout.map(|st| st.saturating_duration_since(Instant::now()));
+                let sync_timeout = state.parser.sync_timeout().copied();
+                #[cfg(not(target_os = "windows"))]
+                let deadline =
+                    sync_timeout.into_iter().chain(self.foreground_check).min();
+                #[cfg(target_os = "windows")]
+                let deadline = sync_timeout;
+                let timeout = deadline
+                    .map(|deadline| deadline.saturating_duration_since(Instant::now()));
 
                 if let Err(err) = self.poll.poll(&mut events, timeout) {
                     match err.kind() {
@@ -303,11 +376,21 @@ where
                     }
                 }
 
+                #[cfg(not(target_os = "windows"))]
+                if self
+                    .foreground_check
+                    .is_some_and(|check| check <= Instant::now())
+                {
+                    self.check_foreground();
+                }
+
                 // Handle synchronized update timeout.
                 if events.is_empty() {
-                    state.parser.stop_sync(&mut *self.terminal.lock());
-                    self.event_proxy
-                        .send_event(RioEvent::Wakeup, self.window_id);
+                    if sync_timeout.is_some_and(|timeout| timeout <= Instant::now()) {
+                        state.parser.stop_sync(&mut *self.terminal.lock());
+                        self.event_proxy
+                            .send_event(RioEvent::Wakeup, self.window_id);
+                    }
                     continue;
                 }
 
@@ -320,19 +403,29 @@ where
                             }
                         }
                         token if token == self.pty.child_event_token() => {
-                            if let Some(teletypewriter::ChildEvent::Exited) =
+                            if let Some(teletypewriter::ChildEvent::Exited(status)) =
                                 self.pty.next_child_event()
                             {
-                                // In the future allow configure exit
-                                // if self.hold {
-                                //     With hold enabled, make sure the PTY is drained.
-                                //     let _ = self.pty_read(&mut state, &mut buf);
-                                // } else {
-                                //     // Without hold, shutdown the terminal.
-                                //     self.terminal.lock().exit();
-                                // }
-
-                                self.terminal.lock().exit();
+                                self.event_proxy.send_event(
+                                    RioEvent::ChildExit(status),
+                                    self.window_id,
+                                );
+
+                                if self.terminal.lock().hold {
+                                    // With hold enabled, make sure the PTY is drained.
+                                    let _ = self.pty_read(&mut state, &mut buf);
+
+                                    let mut terminal = self.terminal.lock();
+                                    state.parser.advance_bytes(
+                                        &mut *terminal,
+                                        exit_banner(status).as_bytes(),
+                                    );
+                                    terminal.held = true;
+                                } else {
+                                    // Without hold, shutdown the terminal.
+                                    self.terminal.lock().exit();
+                                }
+
                                 self.event_proxy
                                     .send_event(RioEvent::Wakeup, self.window_id);
                                 break 'event_loop;
@@ -377,6 +470,11 @@ where
                     }
                 }
 
+                // Input, resizes and output may come along with another
+                // program in the foreground.
+                #[cfg(not(target_os = "windows"))]
+                self.schedule_foreground_check();
+
                 // Register write interest if necessary.
                 let mut interest = Ready::readable();
                 if state.needs_write() {
@@ -396,3 +494,75 @@ where
         });
     }
 }
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[derive(Clone)]
+    struct ChannelListener(mpsc::Sender<RioEvent>);
+
+    impl EventListener for ChannelListener {
+        fn send_event(&self, event: RioEvent, _id: WindowId) {
+            let _ = self.0.send(event);
+        }
+    }
+
+    #[test]
+    fn silent_foreground_programs_are_reported() {
+        // With job control the shell hands the terminal over to `sleep`,
+        // which never writes anything.
+        let command = teletypewriter::PtyCommand::new("sh").args(["-mc", "sleep 5"]);
+        let pty = teletypewriter::create_pty_with_spawn(&command, 80, 25).unwrap();
+        let (main_fd, shell_pid) = (*pty.child.id, *pty.child.pid);
+
+        let (sender, receiver) = mpsc::channel();
+        let window_id = WindowId::from(0);
+        let terminal = Arc::new(FairMutex::new(Crosswords::new(
+            80,
+            25,
+            ChannelListener(sender.clone()),
+            window_id,
+        )));
+        let mut machine = Machine::new(
+            Arc::clone(&terminal),
+            pty,
+            ChannelListener(sender),
+            window_id,
+        )
+        .unwrap();
+        machine.track_foreground(teletypewriter::ForegroundTracker::new(
+            main_fd,
+            shell_pid as u32,
+        ));
+        let channel = machine.channel();
+        machine.spawn();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        let process = loop {
+            match receiver
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            {
+                Ok(RioEvent::ForegroundProcess(process)) if process.name == "sleep" => {
+                    break process
+                }
+                Ok(_) => continue,
+                Err(_) => panic!("sleep was never reported"),
+            }
+        };
+        assert_eq!(
+            terminal
+                .lock()
+                .foreground_process
+                .as_ref()
+                .map(|process| process.pid),
+            Some(process.pid)
+        );
+
+        teletypewriter::kill_pid(process.pid);
+        teletypewriter::kill_pid(shell_pid);
+        let _ = channel.send(Msg::Shutdown);
+    }
+}
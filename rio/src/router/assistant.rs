@@ -1,3 +1,4 @@
+use rio_config::diagnostics::Diagnostic;
 use rio_config::ConfigError;
 use std::fmt;
 use std::fmt::Display;
@@ -20,14 +21,18 @@ pub struct ErrorReport {
 impl From<ConfigError> for ErrorReport {
     fn from(error: ConfigError) -> Self {
         match error {
-            ConfigError::ErrLoadingConfig(message) => ErrorReport {
-                report: AssistantReport::InvalidConfigurationFormat(message),
+            ConfigError::ErrLoadingConfig(diagnostics) => ErrorReport {
+                report: AssistantReport::InvalidConfigurationFormat(diagnostics),
                 level: AssistantReportLevel::Warning,
             },
             ConfigError::ErrLoadingTheme(message) => ErrorReport {
                 report: AssistantReport::InvalidConfigurationTheme(message),
                 level: AssistantReportLevel::Warning,
             },
+            ConfigError::ProfileNotFound(name) => ErrorReport {
+                report: AssistantReport::ProfileNotFound(name),
+                level: AssistantReportLevel::Warning,
+            },
             ConfigError::PathNotFound => ErrorReport {
                 report: AssistantReport::ConfigurationNotFound,
                 level: AssistantReportLevel::Warning,
@@ -36,6 +41,20 @@ impl From<ConfigError> for ErrorReport {
     }
 }
 
+impl ErrorReport {
+    /// Warnings of a configuration that loaded, if there are any.
+    pub fn from_config_warnings(config: &rio_config::Config) -> Option<ErrorReport> {
+        if config.diagnostics.is_empty() {
+            return None;
+        }
+
+        Some(ErrorReport {
+            report: AssistantReport::ConfigurationWarnings(config.diagnostics.to_owned()),
+            level: AssistantReportLevel::Warning,
+        })
+    }
+}
+
 #[derive(Clone, PartialEq)]
 pub enum AssistantReport {
     // font was not found
@@ -48,9 +67,15 @@ pub enum AssistantReport {
     // configurlation file was not found
     ConfigurationNotFound,
     // configuration file have an invalid format
-    InvalidConfigurationFormat(String),
+    InvalidConfigurationFormat(Vec<Diagnostic>),
+    // configuration file loaded with issues, like unknown keys
+    ConfigurationWarnings(Vec<Diagnostic>),
     // configuration invalid theme
     InvalidConfigurationTheme(String),
+    // custom shader could not be read or compiled
+    InvalidShader(String),
+    // selected profile is not in the configuration
+    ProfileNotFound(String),
 
     // reports that are ignored by AssistantReport
     IgnoredReport,
@@ -81,16 +106,38 @@ impl std::fmt::Display for AssistantReport {
                 write!(f, "Error initializing Rio terminal:\n{message}")
             }
             AssistantReport::IgnoredReport => write!(f, ""),
-            AssistantReport::InvalidConfigurationFormat(message) => {
-                write!(f, "Found an issue loading the configuration file:\n\n{message}\n\nRio will proceed with the default configuration\nhttps://raphamorim.io/rio/docs/#configuration-file")
+            AssistantReport::InvalidConfigurationFormat(diagnostics) => {
+                write!(f, "Found an issue loading the configuration file:\n\n{}\n\nRio will proceed with the default configuration\nhttps://raphamorim.io/rio/docs/#configuration-file", list(diagnostics))
+            }
+            AssistantReport::ConfigurationWarnings(diagnostics) => {
+                write!(
+                    f,
+                    "Found issues in the configuration file, Rio ignored them:\n\n{}",
+                    list(diagnostics)
+                )
             }
             AssistantReport::InvalidConfigurationTheme(message) => {
                 write!(f, "Found an issue in the configured theme:\n\n{message}")
             }
+            AssistantReport::InvalidShader(message) => {
+                write!(f, "Custom shader failed to load:\n\n{message}")
+            }
+            AssistantReport::ProfileNotFound(name) => {
+                write!(f, "Profile \"{name}\" was not found in the configuration\n\nRio will proceed without it")
+            }
         }
     }
 }
 
+#[inline]
+fn list(diagnostics: &[Diagnostic]) -> String {
+    diagnostics
+        .iter()
+        .map(|diagnostic| format!("• {diagnostic}"))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
 impl Display for Assistant {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if let Some(error) = &self.inner {
@@ -110,6 +157,7 @@ impl Display for Assistant {
     }
 }
 
+#[derive(Default)]
 pub struct Assistant {
     pub inner: Option<ErrorReport>,
 }
@@ -0,0 +1,204 @@
+use sugarloaf::components::rect::Rect;
+use sugarloaf::font::FONT_ID_BUILTIN;
+use sugarloaf::Sugarloaf;
+use winit::event::ElementState;
+use winit::keyboard::Key;
+
+const PANEL_WIDTH: f32 = 420.;
+const LINE_HEIGHT: f32 = 20.;
+
+/// What runs once the user confirms it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmAction {
+    CloseTab,
+    CloseWindow,
+    Quit,
+}
+
+/// Answer of the user to a close waiting for a confirmation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmAnswer {
+    Confirmed(ConfirmAction),
+    Cancelled,
+}
+
+/// Close waiting for a confirmation because of the programs running in
+/// the foreground of the tabs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Confirmation {
+    pub action: ConfirmAction,
+    pub processes: Vec<String>,
+}
+
+impl Confirmation {
+    #[inline]
+    fn title(&self) -> &'static str {
+        match self.action {
+            ConfirmAction::CloseTab => "Close this tab?",
+            ConfirmAction::CloseWindow => "Close this window?",
+            ConfirmAction::Quit => "Quit Rio?",
+        }
+    }
+}
+
+/// Answer of `key` to the confirmation shown, enter confirms and escape
+/// cancels. Both take the confirmation away, other keys leave it shown.
+pub fn answer(
+    confirmation: &mut Option<Confirmation>,
+    key: &Key,
+    state: ElementState,
+) -> Option<ConfirmAnswer> {
+    if confirmation.is_none() || state != ElementState::Pressed {
+        return None;
+    }
+
+    match key {
+        Key::Enter => confirmation
+            .take()
+            .map(|confirmation| ConfirmAnswer::Confirmed(confirmation.action)),
+        Key::Escape => {
+            *confirmation = None;
+            Some(ConfirmAnswer::Cancelled)
+        }
+        _ => None,
+    }
+}
+
+/// Panel in the middle of the window, drawn over the terminal content.
+#[inline]
+pub fn overlay(sugarloaf: &mut Sugarloaf, confirm: &Confirmation) {
+    let blue = [0.1764706, 0.6039216, 1.0, 1.0];
+    let yellow = [0.9882353, 0.7294118, 0.15686275, 1.0];
+    let background = [0.0627451, 0.0627451, 0.0784314, 1.0];
+
+    let scale_factor = sugarloaf.layout.scale_factor;
+    let width = sugarloaf.layout.width / scale_factor;
+    let height = sugarloaf.layout.height / scale_factor;
+
+    let panel_width = PANEL_WIDTH.min(width);
+    let panel_height = LINE_HEIGHT * (confirm.processes.len() as f32 + 5.);
+    let x = (width - panel_width) / 2.;
+    let y = ((height - panel_height) / 2.).max(0.);
+
+    sugarloaf.overlay_rects(vec![
+        Rect {
+            position: [x, y],
+            color: background,
+            size: [panel_width * 2., panel_height],
+        },
+        Rect {
+            position: [x, y],
+            color: blue,
+            size: [8., panel_height],
+        },
+    ]);
+
+    let text_x = x + 20.;
+    sugarloaf.overlay_text(
+        (text_x, y + LINE_HEIGHT),
+        confirm.title().to_string(),
+        FONT_ID_BUILTIN,
+        18.,
+        [1., 1., 1., 1.],
+        true,
+    );
+
+    sugarloaf.overlay_text(
+        (text_x, y + LINE_HEIGHT * 2.),
+        "(press enter to close or escape to cancel)".to_string(),
+        FONT_ID_BUILTIN,
+        14.,
+        yellow,
+        true,
+    );
+
+    sugarloaf.overlay_text(
+        (text_x, y + LINE_HEIGHT * 3.5),
+        "Programs still running:".to_string(),
+        FONT_ID_BUILTIN,
+        14.,
+        [1., 1., 1., 1.],
+        true,
+    );
+
+    for (index, process) in confirm.processes.iter().enumerate() {
+        sugarloaf.overlay_text(
+            (text_x, y + LINE_HEIGHT * (index as f32 + 4.5)),
+            format!("• {process}"),
+            FONT_ID_BUILTIN,
+            14.,
+            [1., 1., 1., 1.],
+            true,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn pressed(
+        confirmation: &mut Option<Confirmation>,
+        key: Key,
+    ) -> Option<ConfirmAnswer> {
+        answer(confirmation, &key, ElementState::Pressed)
+    }
+
+    fn shown(action: ConfirmAction) -> Option<Confirmation> {
+        Some(Confirmation {
+            action,
+            processes: vec![String::from("vim")],
+        })
+    }
+
+    #[test]
+    fn test_enter_confirms_the_action() {
+        for action in [
+            ConfirmAction::Quit,
+            ConfirmAction::CloseWindow,
+            ConfirmAction::CloseTab,
+        ] {
+            let mut confirmation = shown(action);
+            assert_eq!(
+                pressed(&mut confirmation, Key::Enter),
+                Some(ConfirmAnswer::Confirmed(action))
+            );
+            assert_eq!(confirmation, None);
+        }
+    }
+
+    #[test]
+    fn test_escape_cancels() {
+        for action in [
+            ConfirmAction::Quit,
+            ConfirmAction::CloseWindow,
+            ConfirmAction::CloseTab,
+        ] {
+            let mut confirmation = shown(action);
+            assert_eq!(
+                pressed(&mut confirmation, Key::Escape),
+                Some(ConfirmAnswer::Cancelled)
+            );
+            assert_eq!(confirmation, None);
+        }
+    }
+
+    #[test]
+    fn test_other_keys_keep_the_confirmation() {
+        let mut confirmation = shown(ConfirmAction::Quit);
+        assert_eq!(pressed(&mut confirmation, Key::Tab), None);
+        assert_eq!(pressed(&mut confirmation, Key::Character("y".into())), None);
+        assert_eq!(
+            answer(&mut confirmation, &Key::Enter, ElementState::Released),
+            None
+        );
+        assert_eq!(confirmation, shown(ConfirmAction::Quit));
+    }
+
+    #[test]
+    fn test_nothing_to_answer() {
+        let mut confirmation = None;
+        assert_eq!(pressed(&mut confirmation, Key::Enter), None);
+        assert_eq!(pressed(&mut confirmation, Key::Escape), None);
+    }
+}
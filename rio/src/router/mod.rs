@@ -1,16 +1,19 @@
 pub mod assistant;
+pub mod confirm;
 pub mod settings;
 pub mod welcome;
 
+use crate::event::EventP;
 use crate::event::EventProxy;
 use crate::screen::window::{configure_window, create_window_builder};
 use crate::screen::Screen;
-use crate::EventP;
 use assistant::{Assistant, AssistantReport};
+use confirm::{ConfirmAnswer, Confirmation};
 use settings::Settings;
 use std::collections::HashMap;
 use std::error::Error;
 use std::rc::Rc;
+use std::sync::Arc;
 use sugarloaf::font::loader;
 use winit::event_loop::EventLoop;
 use winit::event_loop::EventLoopWindowTarget;
@@ -23,6 +26,8 @@ pub struct Route {
     pub assistant: Assistant,
     pub settings: Settings,
     pub path: RoutePath,
+    /// Close waiting for an answer, shown over the terminal.
+    pub confirmation: Option<Confirmation>,
     pub window: RouteWindow,
 }
 
@@ -69,6 +74,40 @@ impl Route {
         self.path = RoutePath::Assistant;
     }
 
+    #[inline]
+    pub fn confirm_close(&mut self, confirmation: Confirmation) {
+        self.confirmation = Some(confirmation);
+    }
+
+    /// Answer of `key_event` to the confirmation shown, enter confirms and
+    /// escape cancels. Both are consumed, they don't reach the terminal.
+    #[inline]
+    pub fn answer_confirm_close(
+        &mut self,
+        key_event: &winit::event::KeyEvent,
+    ) -> Option<ConfirmAnswer> {
+        confirm::answer(
+            &mut self.confirmation,
+            &key_event.logical_key,
+            key_event.state,
+        )
+    }
+
+    /// Whether input goes to the terminal, with nothing shown over it.
+    #[inline]
+    pub fn is_terminal_focused(&self) -> bool {
+        self.path == RoutePath::Terminal && self.confirmation.is_none()
+    }
+
+    /// Renders the terminal, with the confirmation over it when there is one.
+    #[inline]
+    pub fn render_terminal(&mut self) {
+        match &self.confirmation {
+            Some(confirmation) => self.window.screen.render_confirm_close(confirmation),
+            None => self.window.screen.render(),
+        }
+    }
+
     #[inline]
     pub fn clear_errors(&mut self) {
         self.assistant.clear();
@@ -77,6 +116,10 @@ impl Route {
 
     #[inline]
     pub fn has_key_wait(&mut self, key_event: &winit::event::KeyEvent) -> bool {
+        if self.confirmation.is_some() {
+            return true;
+        }
+
         if self.path == RoutePath::Terminal {
             return false;
         }
@@ -142,10 +185,12 @@ pub enum RoutePath {
 pub struct Router {
     pub routes: HashMap<WindowId, Route>,
     propagated_report: Option<ErrorReport>,
-    pub font_database: loader::Database,
+    pub font_database: Arc<loader::Database>,
 }
 
 impl Router {
+    // Loading the system fonts is too expensive for a `Default`.
+    #[allow(clippy::new_without_default)]
     pub fn new() -> Self {
         let mut font_database = loader::Database::new();
         font_database.load_system_fonts();
@@ -153,7 +198,7 @@ impl Router {
         Router {
             routes: HashMap::new(),
             propagated_report: None,
-            font_database,
+            font_database: Arc::new(font_database),
         }
     }
 
@@ -168,6 +213,7 @@ impl Router {
         let mut route = Route {
             window: route_window,
             path: RoutePath::Terminal,
+            confirmation: None,
             settings: Settings::new(&self.font_database),
             assistant: Assistant::new(),
         };
@@ -201,6 +247,7 @@ impl Router {
                 window,
                 settings: Settings::new(&self.font_database),
                 path: RoutePath::Terminal,
+                confirmation: None,
                 assistant: Assistant::new(),
             },
         );
@@ -229,6 +276,7 @@ impl Router {
                 window,
                 settings: Settings::new(&self.font_database),
                 path: RoutePath::Terminal,
+                confirmation: None,
                 assistant: Assistant::new(),
             },
         );
@@ -248,7 +296,7 @@ impl RouteWindow {
     pub async fn new(
         event_loop: &EventLoop<EventP>,
         config: &Rc<rio_config::Config>,
-        font_database: &loader::Database,
+        font_database: &Arc<loader::Database>,
     ) -> Result<Self, Box<dyn Error>> {
         let proxy = event_loop.create_proxy();
         let event_proxy = EventProxy::new(proxy.clone());
@@ -279,7 +327,7 @@ impl RouteWindow {
         event_loop: &EventLoopWindowTarget<EventP>,
         event_proxy: EventProxy,
         config: &Rc<rio_config::Config>,
-        font_database: &loader::Database,
+        font_database: &Arc<loader::Database>,
         window_name: &str,
         tab_id: Option<String>,
     ) -> Self {
@@ -244,9 +244,15 @@ pub enum Action {
     #[allow(dead_code)]
     ConfigEditor,
 
-    /// Create a new Rio tab.
+    /// Save the window as a PNG in the screenshot directory.
+    Screenshot,
+
+    /// Create a new Rio tab, with the shell, environment and colors of
+    /// `profile` if there's one.
     #[allow(dead_code)]
-    TabCreateNew,
+    TabCreateNew {
+        profile: Option<String>,
+    },
 
     /// Switch to next tab.
     #[allow(dead_code)]
@@ -666,7 +672,8 @@ fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
         }
     }
 
-    let mut action: Action = match config_key_binding.action.to_lowercase().as_str() {
+    let action_name = config_key_binding.action.to_lowercase();
+    let mut action: Action = match action_name.as_str() {
         "paste" => Action::Paste,
         "quit" => Action::Quit,
         "copy" => Action::Copy,
@@ -674,9 +681,17 @@ fn convert(config_key_binding: ConfigKeyBinding) -> Result<KeyBinding, String> {
         "increasefontsize" => Action::IncreaseFontSize,
         "decreasefontsize" => Action::DecreaseFontSize,
         "createwindow" => Action::WindowCreateNew,
-        "createtab" => Action::TabCreateNew,
+        "createtab" => Action::TabCreateNew { profile: None },
+        // createtab(<profile>), the name keeps its case
+        name if name.starts_with("createtab(") && name.ends_with(')') => {
+            let action = &config_key_binding.action;
+            Action::TabCreateNew {
+                profile: Some(action["createtab(".len()..action.len() - 1].to_string()),
+            }
+        }
         "closetab" => Action::TabCloseCurrent,
         "openconfigeditor" => Action::ConfigEditor,
+        "screenshot" => Action::Screenshot,
         "selectprevtab" => Action::SelectPrevTab,
         "selectnexttab" => Action::SelectNextTab,
         "selecttab1" => Action::SelectTab1,
@@ -808,7 +823,7 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
         "m", ModifiersState::SUPER; Action::Minimize;
         "q", ModifiersState::SUPER; Action::Quit;
         "n", ModifiersState::SUPER; Action::WindowCreateNew;
-        "t", ModifiersState::SUPER; Action::TabCreateNew;
+        "t", ModifiersState::SUPER; Action::TabCreateNew { profile: None };
         Tab, ModifiersState::CONTROL; Action::SelectNextTab;
         Tab, ModifiersState::CONTROL | ModifiersState::SHIFT; Action::SelectPrevTab;
         "[", ModifiersState::SUPER | ModifiersState::SHIFT; Action::SelectNextTab;
@@ -844,7 +859,7 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
         "-",          ModifiersState::CONTROL;  Action::DecreaseFontSize;
         "-", ModifiersState::CONTROL;  Action::DecreaseFontSize;
         "n", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::WindowCreateNew;
-        "t", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::TabCreateNew;
+        "t", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::TabCreateNew { profile: None };
         Tab, ModifiersState::CONTROL; Action::SelectNextTab;
         "[", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::SelectNextTab;
         "]", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::SelectPrevTab;
@@ -870,7 +885,7 @@ pub fn platform_key_bindings() -> Vec<KeyBinding> {
         "-",          ModifiersState::CONTROL;  Action::DecreaseFontSize;
         "-", ModifiersState::CONTROL;  Action::DecreaseFontSize;
         Enter, ModifiersState::ALT; Action::ToggleFullscreen;
-        "t", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::TabCreateNew;
+        "t", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::TabCreateNew { profile: None };
         Tab, ModifiersState::CONTROL; Action::SelectNextTab;
         "w", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::TabCloseCurrent;
         "n", ModifiersState::CONTROL | ModifiersState::SHIFT; Action::WindowCreateNew;
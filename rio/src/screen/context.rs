@@ -1,20 +1,27 @@
 use crate::crosswords::pos::CursorState;
 use crate::event::sync::FairMutex;
-use crate::event::{EventListener, RioEvent};
+use crate::event::{EventListener, RioEvent, ScreenshotRequest};
 use crate::performer::Machine;
-use crate::router::assistant::AssistantReport::{FontsNotFound, InitializationError};
+use crate::router::assistant::AssistantReport::{
+    FontsNotFound, InitializationError, InvalidShader,
+};
 use crate::router::assistant::{AssistantReportLevel, ErrorReport};
+use crate::router::confirm::{ConfirmAction, Confirmation};
 use crate::screen::Crosswords;
 use crate::screen::Messenger;
-use rio_config::Shell;
-use std::borrow::Cow;
+use rio_config::colors::Colors;
+use rio_config::profile::Profile;
+use rio_config::{ConfirmClose, Shell};
 use std::collections::HashMap;
 use std::error::Error;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+#[cfg(unix)]
+use std::sync::OnceLock;
 use sugarloaf::{font::SugarloafFont, SugarloafErrors};
 use winit::window::WindowId;
 
+use teletypewriter::PtyCommand;
+
 #[cfg(target_os = "windows")]
 use teletypewriter::create_pty;
 #[cfg(not(target_os = "windows"))]
@@ -29,6 +36,10 @@ pub struct Context<T: EventListener> {
     pub main_fd: Arc<i32>,
     #[cfg(not(target_os = "windows"))]
     pub shell_pid: u32,
+    /// Colors of the profile the tab was created with.
+    pub colors: Option<Colors>,
+    /// Profile the tab was created with.
+    pub profile: Option<String>,
 }
 
 #[derive(Clone, Default)]
@@ -41,10 +52,80 @@ pub struct ContextManagerConfig {
     pub is_collapsed: bool,
     pub is_native: bool,
     pub should_update_titles: bool,
+    /// Set for the shell only, on top of the environment of Rio.
+    pub env_vars: Vec<String>,
+    pub colors: Option<Colors>,
+    pub profiles: HashMap<String, Profile>,
+    /// Keep the tabs open when their child exits.
+    pub hold: bool,
+    pub confirm_close: ConfirmClose,
+}
+
+impl ContextManagerConfig {
+    /// Config of a tab created with `profile`.
+    pub fn with_profile(&self, profile: &Profile) -> ContextManagerConfig {
+        let mut config = self.clone();
+        if let Some(shell) = &profile.shell {
+            config.shell = shell.to_owned();
+        }
+        if profile.working_dir.is_some() {
+            config.working_dir = profile.working_dir.to_owned();
+        }
+        config.env_vars.extend(profile.env_vars.iter().cloned());
+        if profile.colors.is_some() {
+            config.colors = profile.colors;
+        }
+        config
+    }
+
+    /// Command of the tabs, the shell of the user runs as a login shell.
+    pub fn command(&self) -> PtyCommand {
+        let mut command = PtyCommand::new(&self.shell.program)
+            .args(&self.shell.args)
+            .login(self.shell.program.is_empty());
+        #[cfg(unix)]
+        {
+            command = command.term(terminfo());
+        }
+        if let Some(working_dir) = &self.working_dir {
+            command = command.cwd(working_dir);
+        }
+        #[cfg(unix)]
+        if let Some(socket_path) = crate::ipc::socket_path() {
+            command =
+                command.env(crate::ipc::RIO_SOCKET_ENV, socket_path.to_string_lossy());
+        }
+        for env_var in &self.env_vars {
+            if let Some((key, value)) = env_var.split_once('=') {
+                // `TERM` is set after the environment, so it's replaced.
+                command = if key == "TERM" {
+                    command.term(value)
+                } else {
+                    command.env(key, value)
+                };
+            }
+        }
+        command
+    }
+}
+
+/// `TERM` of the tabs, `rio` when its terminfo is installed.
+#[cfg(unix)]
+fn terminfo() -> &'static str {
+    static TERMINFO: OnceLock<&str> = OnceLock::new();
+    TERMINFO.get_or_init(|| {
+        let terminfo = if teletypewriter::terminfo_exists("rio") {
+            "rio"
+        } else {
+            "xterm-256color"
+        };
+        log::info!("terminfo: {terminfo}");
+        terminfo
+    })
 }
 
 pub struct ContextManagerTitles {
-    last_title_update: Instant,
+    outdated: bool,
     pub titles: HashMap<usize, [String; 2]>,
     pub key: String,
 }
@@ -55,14 +136,13 @@ impl ContextManagerTitles {
         program: String,
         terminal_title: String,
     ) -> ContextManagerTitles {
-        let last_title_update = Instant::now();
         ContextManagerTitles {
             titles: HashMap::from([(
                 idx,
                 [program.to_owned(), terminal_title.to_owned()],
             )]),
             key: format!("{}{}{};", idx, program, terminal_title),
-            last_title_update,
+            outdated: true,
         }
     }
 
@@ -99,6 +179,8 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             shell_pid: 1,
             messenger: Messenger::new(sender),
             terminal,
+            colors: None,
+            profile: None,
         }
     }
 
@@ -116,15 +198,17 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             Crosswords::new(cols_rows.0, cols_rows.1, event_proxy, window_id);
         terminal.cursor_shape = cursor_state.0.content;
         terminal.blinking_cursor = cursor_state.1;
+        terminal.hold = config.hold;
         let terminal: Arc<FairMutex<Crosswords<T>>> = Arc::new(FairMutex::new(terminal));
 
+        let command = config.command();
         let pty;
         #[cfg(not(target_os = "windows"))]
         {
             if config.use_fork {
                 log::info!("rio -> teletypewriter: create_pty_with_fork");
                 pty = match create_pty_with_fork(
-                    &Cow::Borrowed(&config.shell.program),
+                    &command,
                     cols_rows.0 as u16,
                     cols_rows.1 as u16,
                 ) {
@@ -137,9 +221,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             } else {
                 log::info!("rio -> teletypewriter: create_pty_with_spawn");
                 pty = match create_pty_with_spawn(
-                    &Cow::Borrowed(&config.shell.program),
-                    config.shell.args.clone(),
-                    &config.working_dir,
+                    &command,
                     cols_rows.0 as u16,
                     cols_rows.1 as u16,
                 ) {
@@ -154,13 +236,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
 
         #[cfg(target_os = "windows")]
         {
-            pty = create_pty(
-                &Cow::Borrowed(&config.shell.program),
-                config.shell.args.clone(),
-                &config.working_dir,
-                cols_rows.0 as u16,
-                cols_rows.1 as u16,
-            );
+            pty = create_pty(&command, cols_rows.0 as u16, cols_rows.1 as u16);
         }
 
         #[cfg(not(target_os = "windows"))]
@@ -168,8 +244,13 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         #[cfg(not(target_os = "windows"))]
         let shell_pid = *pty.child.pid.clone() as u32;
 
-        let machine =
+        #[allow(unused_mut)]
+        let mut machine =
             Machine::new(Arc::clone(&terminal), pty, event_proxy_clone, window_id)?;
+        #[cfg(not(target_os = "windows"))]
+        machine.track_foreground(teletypewriter::ForegroundTracker::new(
+            *main_fd, shell_pid,
+        ));
         let channel = machine.channel();
         if config.spawn_performer {
             machine.spawn();
@@ -188,6 +269,8 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             shell_pid,
             messenger,
             terminal,
+            colors: config.colors,
+            profile: None,
         })
     }
 
@@ -272,6 +355,11 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             is_native: false,
             should_update_titles: false,
             use_current_path: false,
+            env_vars: vec![],
+            colors: None,
+            profiles: HashMap::new(),
+            hold: false,
+            confirm_close: ConfirmClose::default(),
         };
         let initial_context = ContextManager::create_context(
             (100, 100),
@@ -295,6 +383,18 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         })
     }
 
+    #[inline]
+    pub fn schedule_render(&self) {
+        self.event_proxy
+            .send_event(RioEvent::Render, self.window_id);
+    }
+
+    #[inline]
+    pub fn schedule_render_after(&self, millis: u64) {
+        self.event_proxy
+            .send_event(RioEvent::PrepareRender(millis), self.window_id);
+    }
+
     #[inline]
     pub fn schedule_cursor_blinking_render(&self) {
         self.event_proxy
@@ -316,6 +416,16 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
     }
 
+    pub fn report_error_invalid_shader(&self, message: String) {
+        self.event_proxy.send_event(
+            RioEvent::ReportToAssistant(ErrorReport {
+                report: InvalidShader(message),
+                level: AssistantReportLevel::Warning,
+            }),
+            self.window_id,
+        );
+    }
+
     #[inline]
     pub fn create_new_window(&self) {
         self.event_proxy
@@ -328,6 +438,68 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
             .send_event(RioEvent::CreateNativeTab, self.window_id);
     }
 
+    /// Ask to quit Rio, once the processes of every window are checked.
+    #[inline]
+    pub fn quit(&self) {
+        self.event_proxy.send_event(RioEvent::Quit, self.window_id);
+    }
+
+    /// Whether `action` can run right away, otherwise the user is asked to
+    /// confirm it first.
+    pub fn can_close(&self, action: ConfirmAction) -> bool {
+        let processes = self.busy_processes(action == ConfirmAction::CloseTab);
+        if processes.is_empty() {
+            return true;
+        }
+
+        self.event_proxy.send_event(
+            RioEvent::ConfirmClose(Confirmation { action, processes }),
+            self.window_id,
+        );
+        false
+    }
+
+    /// Programs in the foreground of the tabs, or of the current one only,
+    /// other than their shells and the allowlist of `confirm-close`.
+    pub fn busy_processes(&self, current_only: bool) -> Vec<String> {
+        if !self.config.confirm_close.enabled {
+            return vec![];
+        }
+
+        #[cfg(not(target_os = "windows"))]
+        {
+            let contexts = if current_only {
+                std::slice::from_ref(self.current())
+            } else {
+                &self.contexts[..]
+            };
+
+            // Tracked by the `Machine` of each tab, nothing is read here.
+            contexts
+                .iter()
+                .filter_map(|context| {
+                    let terminal = context.terminal.lock();
+                    let process = terminal.foreground_process.as_ref()?;
+                    // The shell of the tab is idle, whatever its name is.
+                    if process.is_shell {
+                        return None;
+                    }
+                    Some(process.name.to_owned())
+                })
+                .filter(|name| {
+                    !name.is_empty()
+                        && !self.config.confirm_close.allowlist.contains(name)
+                })
+                .collect()
+        }
+
+        #[cfg(target_os = "windows")]
+        {
+            let _ = current_only;
+            vec![]
+        }
+    }
+
     #[inline]
     pub fn close_current_window(&self) {
         self.event_proxy
@@ -373,6 +545,14 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         self.set_current(self.contexts.len() - 1);
     }
 
+    #[inline]
+    pub fn screenshot(&self) {
+        self.event_proxy.send_event(
+            RioEvent::Screenshot(ScreenshotRequest::default()),
+            self.window_id,
+        );
+    }
+
     #[inline]
     pub fn switch_to_settings(&self) {
         self.event_proxy
@@ -384,77 +564,67 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         self.contexts.len()
     }
 
+    /// Read the titles again on the next render.
     #[inline]
+    pub fn outdate_titles(&mut self) {
+        self.titles.outdated = true;
+    }
+
+    /// Titles of the tabs, from the foreground process the `Machine` of
+    /// each one reports, only read again once outdated.
     pub fn update_titles(&mut self) {
-        if !self.config.should_update_titles {
+        if !self.config.should_update_titles || !self.titles.outdated {
             return;
         }
+        self.titles.outdated = false;
 
         #[cfg(not(target_os = "windows"))]
         {
-            let interval_time = if self.config.is_native {
-                Duration::from_secs(3)
-            } else {
-                Duration::from_secs(5)
-            };
-
-            if self.titles.last_title_update.elapsed() > interval_time {
-                self.titles.last_title_update = Instant::now();
-                let mut id = String::from("");
-                for (i, context) in self.contexts.iter_mut().enumerate() {
-                    let program = teletypewriter::foreground_process_name(
-                        *context.main_fd,
-                        context.shell_pid,
-                    );
-
-                    #[cfg(not(target_os = "macos"))]
-                    let terminal_title = String::from("");
-
-                    #[cfg(target_os = "macos")]
-                    #[allow(unused)]
-                    let mut terminal_title = String::from("");
-
-                    #[cfg(target_os = "macos")]
-                    {
-                        let terminal = context.terminal.lock();
-                        terminal_title = terminal.title.to_string();
-                        drop(terminal);
-                    }
-
-                    if self.config.is_native {
-                        let window_title = if terminal_title.is_empty() {
-                            program.to_owned()
-                        } else {
-                            terminal_title.to_owned()
-                        };
-
-                        self.event_proxy
-                            .send_event(RioEvent::Title(window_title), self.window_id);
-                    }
-
-                    id =
-                        id.to_owned() + &(format!("{}{}{};", i, program, terminal_title));
-                    self.titles.set_key_val(i, program, terminal_title);
+            let mut id = String::from("");
+            for (i, context) in self.contexts.iter().enumerate() {
+                let terminal = context.terminal.lock();
+                let program = terminal
+                    .foreground_process
+                    .as_ref()
+                    .map(|process| process.name.to_owned())
+                    .unwrap_or_default();
+
+                #[cfg(not(target_os = "macos"))]
+                let terminal_title = String::from("");
+
+                #[cfg(target_os = "macos")]
+                let terminal_title = terminal.title.to_string();
+
+                drop(terminal);
+
+                if self.config.is_native {
+                    let window_title = if terminal_title.is_empty() {
+                        program.to_owned()
+                    } else {
+                        terminal_title.to_owned()
+                    };
+
+                    self.event_proxy
+                        .send_event(RioEvent::Title(window_title), self.window_id);
                 }
-                self.titles.set_key(id);
+
+                id = id.to_owned() + &(format!("{}{}{};", i, program, terminal_title));
+                self.titles.set_key_val(i, program, terminal_title);
             }
+            self.titles.set_key(id);
         }
 
         #[cfg(target_os = "windows")]
         {
-            if self.titles.last_title_update.elapsed() > Duration::from_secs(5) {
-                self.titles.last_title_update = Instant::now();
-                let mut id = String::from("");
-                for (i, context) in self.contexts.iter_mut().enumerate() {
-                    let program = self.config.shell.program.to_owned();
-                    let terminal_title = String::from("");
-
-                    id =
-                        id.to_owned() + &(format!("{}{}{};", i, program, terminal_title));
-                    self.titles.set_key_val(i, program, terminal_title);
-                }
-                self.titles.set_key(id);
+            let mut id = String::from("");
+            for i in 0..self.contexts.len() {
+                let program = self.config.shell.program.to_owned();
+                let terminal_title = String::from("");
+
+                id = id.to_owned() + &(format!("{}{}{};", i, program, terminal_title));
+                self.titles.set_key_val(i, program, terminal_title);
             }
+            self.titles.set_key(id);
         }
     }
 
@@ -491,6 +661,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
 
         self.titles.titles.remove(&index_to_remove);
         self.contexts.remove(index_to_remove);
+        self.outdate_titles();
     }
 
     #[inline]
@@ -504,6 +675,16 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
 
         #[cfg(not(target_os = "windows"))]
         {
+            let mut terminal = self.contexts[index_to_remove].terminal.lock();
+            if terminal.held {
+                // The child is gone already, the tab closes right away.
+                terminal.exit();
+                return;
+            }
+            // Closing it on purpose shouldn't hold it.
+            terminal.hold = false;
+            drop(terminal);
+
             let pid = self.contexts[index_to_remove].shell_pid;
             if pid > 0 {
                 teletypewriter::kill_pid(pid as i32);
@@ -581,6 +762,49 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         }
     }
 
+    /// Config of the tabs created with `profile`.
+    fn config_for(&self, profile: Option<&str>) -> ContextManagerConfig {
+        match profile {
+            Some(name) => match self.config.profiles.get(name) {
+                Some(profile) => self.config.with_profile(profile),
+                None => {
+                    log::warn!("profile {name} was not found");
+                    self.config.clone()
+                }
+            },
+            None => self.config.clone(),
+        }
+    }
+
+    /// Run the shell or command of the current context again, in a new
+    /// context taking its place.
+    pub fn rerun_current_context(
+        &mut self,
+        dimensions: (u32, u32),
+        col_rows: (usize, usize),
+        cursor_state: (&CursorState, bool),
+    ) {
+        let profile = self.current().profile.to_owned();
+        let config = self.config_for(profile.as_deref());
+        match ContextManager::create_context(
+            dimensions,
+            col_rows,
+            cursor_state,
+            self.event_proxy.clone(),
+            self.window_id,
+            &config,
+        ) {
+            Ok(mut new_context) => {
+                new_context.profile = profile;
+                self.contexts[self.current_index] = new_context;
+                self.outdate_titles();
+            }
+            Err(..) => {
+                log::error!("not able to run the context again");
+            }
+        }
+    }
+
     #[inline]
     pub fn add_context(
         &mut self,
@@ -588,6 +812,7 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         dimensions: (u32, u32),
         col_rows: (usize, usize),
         cursor_state: (&CursorState, bool),
+        profile: Option<&str>,
     ) {
         // Native tabs do not use Context tabbing API, instead it will
         // ask winit to create a window with a tab id
@@ -600,10 +825,8 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
         if size < self.capacity {
             let last_index = self.contexts.len();
 
-            #[cfg(target_os = "windows")]
-            let cloned_config = &self.config;
-            #[cfg(not(target_os = "windows"))]
-            let mut cloned_config = self.config.clone();
+            #[allow(unused_mut)]
+            let mut cloned_config = self.config_for(profile);
 
             #[cfg(not(target_os = "windows"))]
             {
@@ -627,8 +850,10 @@ impl<T: EventListener + Clone + std::marker::Send + 'static> ContextManager<T> {
                 self.window_id,
                 &cloned_config,
             ) {
-                Ok(new_context) => {
+                Ok(mut new_context) => {
+                    new_context.profile = profile.map(String::from);
                     self.contexts.push(new_context);
+                    self.outdate_titles();
                     if redirect {
                         self.current_index = last_index;
                     }
@@ -674,6 +899,7 @@ pub mod test {
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         assert_eq!(context_manager.capacity, 5);
         assert_eq!(context_manager.current_index, 0);
@@ -684,6 +910,7 @@ pub mod test {
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         assert_eq!(context_manager.capacity, 5);
         assert_eq!(context_manager.current_index, 2);
@@ -702,6 +929,7 @@ pub mod test {
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         assert_eq!(context_manager.len(), 2);
         context_manager.add_context(
@@ -709,6 +937,7 @@ pub mod test {
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         assert_eq!(context_manager.len(), 3);
 
@@ -718,6 +947,7 @@ pub mod test {
                 (100, 100),
                 (1, 1),
                 (&CursorState::default(), false),
+                None,
             );
         }
 
@@ -737,6 +967,7 @@ pub mod test {
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         assert_eq!(context_manager.current_index, 1);
         context_manager.set_current(0);
@@ -750,12 +981,14 @@ pub mod test {
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         context_manager.add_context(
             should_redirect,
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         context_manager.set_current(3);
         assert_eq!(context_manager.current_index, 3);
@@ -776,12 +1009,14 @@ pub mod test {
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         context_manager.add_context(
             should_redirect,
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         assert_eq!(context_manager.len(), 3);
 
@@ -808,24 +1043,28 @@ pub mod test {
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         context_manager.add_context(
             should_redirect,
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         context_manager.add_context(
             should_redirect,
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         context_manager.add_context(
             should_redirect,
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
 
         context_manager.close_context();
@@ -841,6 +1080,7 @@ pub mod test {
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
 
         assert_eq!(context_manager.len(), 2);
@@ -863,12 +1103,14 @@ pub mod test {
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         context_manager.add_context(
             should_redirect,
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         assert_eq!(context_manager.len(), 2);
         assert_eq!(context_manager.current_index, 0);
@@ -893,30 +1135,35 @@ pub mod test {
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         context_manager.add_context(
             should_redirect,
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         context_manager.add_context(
             should_redirect,
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         context_manager.add_context(
             should_redirect,
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         context_manager.add_context(
             should_redirect,
             (100, 100),
             (1, 1),
             (&CursorState::default(), false),
+            None,
         );
         assert_eq!(context_manager.len(), 5);
         assert_eq!(context_manager.current_index, 0);
@@ -934,4 +1181,120 @@ pub mod test {
         context_manager.switch_to_next();
         assert_eq!(context_manager.current_index, 1);
     }
+
+    #[test]
+    fn test_rerun_current_context() {
+        let mut context_manager =
+            ContextManager::start_with_capacity(2, VoidListener {}, WindowId::from(0))
+                .unwrap();
+        context_manager.add_context(
+            true,
+            (100, 100),
+            (1, 1),
+            (&CursorState::default(), false),
+            Some("missing"),
+        );
+        assert_eq!(context_manager.current_index, 1);
+        let terminal = Arc::clone(&context_manager.current().terminal);
+        terminal.lock().held = true;
+
+        context_manager.rerun_current_context(
+            (100, 100),
+            (1, 1),
+            (&CursorState::default(), false),
+        );
+        assert_eq!(context_manager.len(), 2);
+        assert_eq!(context_manager.current_index, 1);
+        assert_eq!(
+            context_manager.current().profile.as_deref(),
+            Some("missing")
+        );
+        assert!(!Arc::ptr_eq(&terminal, &context_manager.current().terminal));
+        assert!(!context_manager.current().terminal.lock().held);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_command_term() {
+        let mut config = ContextManagerConfig::default();
+        let term = |command: PtyCommand| {
+            command
+                .get_envs()
+                .filter(|(key, _)| *key == "TERM")
+                .last()
+                .and_then(|(_, value)| value.map(String::from))
+        };
+        assert_eq!(term(config.command()).as_deref(), Some(terminfo()));
+
+        config.env_vars = vec![String::from("TERM=xterm")];
+        assert_eq!(term(config.command()).as_deref(), Some("xterm"));
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_busy_processes() {
+        let mut context_manager =
+            ContextManager::start_with_capacity(2, VoidListener {}, WindowId::from(0))
+                .unwrap();
+        context_manager.config.confirm_close.allowlist = vec![];
+        context_manager.add_context(
+            true,
+            (100, 100),
+            (1, 1),
+            (&CursorState::default(), false),
+            None,
+        );
+
+        // Nothing was reported by a `Machine` yet.
+        assert!(context_manager.busy_processes(false).is_empty());
+
+        context_manager.current().terminal.lock().foreground_process =
+            Some(teletypewriter::ForegroundProcess {
+                pid: 1,
+                name: String::from("vim"),
+                cwd: None,
+                is_shell: false,
+            });
+        assert_eq!(
+            context_manager.busy_processes(true),
+            vec![String::from("vim")]
+        );
+        assert_eq!(
+            context_manager.busy_processes(false),
+            vec![String::from("vim")]
+        );
+
+        context_manager.config.confirm_close.allowlist = vec![String::from("vim")];
+        assert!(context_manager.busy_processes(true).is_empty());
+
+        context_manager.config.confirm_close.allowlist = vec![];
+        context_manager.config.confirm_close.enabled = false;
+        assert!(context_manager.busy_processes(false).is_empty());
+
+        for context in context_manager.contexts() {
+            teletypewriter::kill_pid(context.shell_pid as i32);
+        }
+    }
+
+    #[test]
+    #[cfg(not(target_os = "windows"))]
+    fn test_busy_processes_idle_shell() {
+        let mut context_manager =
+            ContextManager::start_with_capacity(1, VoidListener {}, WindowId::from(0))
+                .unwrap();
+        context_manager.config.confirm_close.allowlist = vec![];
+
+        // Shells that aren't in the allowlist, like ssh in a profile.
+        let shell_pid = context_manager.current().shell_pid as i32;
+        context_manager.current().terminal.lock().foreground_process =
+            Some(teletypewriter::ForegroundProcess {
+                pid: shell_pid,
+                name: String::from("ssh"),
+                cwd: None,
+                is_shell: true,
+            });
+        assert!(context_manager.busy_processes(false).is_empty());
+
+        teletypewriter::kill_pid(shell_pid);
+    }
 }
@@ -12,12 +12,17 @@ mod context;
 mod messenger;
 mod mouse;
 mod navigation;
+mod notice;
+mod scroll;
+mod scrollbar;
 mod state;
 pub mod window;
 
 use crate::crosswords::vi_mode::ViMotion;
 use crate::screen::bindings::MouseBinding;
 use std::borrow::Cow;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
 use winit::event::KeyEvent;
 use winit::event::Modifiers;
 use winit::event::MouseButton;
@@ -31,9 +36,10 @@ use crate::crosswords::{
     pos::{Pos, Side},
     Crosswords, Mode, MIN_COLUMNS, MIN_LINES,
 };
-use crate::event::{ClickState, EventProxy};
+use crate::event::{ClickState, EventProxy, ScreenshotRequest};
 use crate::ime::Ime;
 use crate::router;
+use crate::router::confirm::ConfirmAction;
 #[cfg(target_os = "macos")]
 use crate::screen::constants::{DEADZONE_END_Y, DEADZONE_START_X, DEADZONE_START_Y};
 use crate::screen::{
@@ -43,12 +49,16 @@ use crate::screen::{
 };
 use crate::selection::{Selection, SelectionType};
 use messenger::Messenger;
+use notice::Notice;
 use rio_config::colors::{term::List, ColorWGPU};
+use scroll::SmoothScroll;
+use scrollbar::Scrollbar;
 use state::State;
 use std::cmp::max;
 use std::cmp::min;
 use std::error::Error;
 use std::rc::Rc;
+use std::sync::Arc;
 use sugarloaf::{layout::SugarloafLayout, Sugarloaf, SugarloafErrors};
 use winit::event::ElementState;
 #[cfg(target_os = "macos")]
@@ -85,10 +95,15 @@ pub struct Screen {
     clipboard: Clipboard,
     pub modifiers: Modifiers,
     pub mouse: Mouse,
+    smooth_scroll: SmoothScroll,
+    scrollbar: Scrollbar,
+    notice: Notice,
     pub ime: Ime,
     pub state: State,
     pub sugarloaf: Sugarloaf,
     pub context_manager: context::ContextManager<EventProxy>,
+    screenshot: Option<ScreenshotRequest>,
+    screenshot_directory: PathBuf,
 }
 
 impl Screen {
@@ -96,7 +111,7 @@ impl Screen {
         winit_window: &winit::window::Window,
         config: &Rc<rio_config::Config>,
         event_proxy: EventProxy,
-        font_database: &sugarloaf::font::loader::Database,
+        font_database: &Arc<sugarloaf::font::loader::Database>,
     ) -> Result<Screen, Box<dyn Error>> {
         let size = winit_window.inner_size();
         let scale = winit_window.scale_factor();
@@ -175,6 +190,11 @@ impl Screen {
             // does not make sense fetch for foreground process names
             should_update_titles: !(is_collapsed
                 && config.navigation.color_automation.is_empty()),
+            env_vars: config.env_vars.to_owned(),
+            colors: None,
+            profiles: config.profiles.to_owned(),
+            hold: config.hold_on_exit,
+            confirm_close: config.confirm_close.to_owned(),
         };
         let context_manager = context::ContextManager::start(
             (sugarloaf.layout.width_u32, sugarloaf.layout.height_u32),
@@ -186,17 +206,47 @@ impl Screen {
             sugarloaf_errors,
         )?;
 
-        Ok(Screen {
+        let mut screen = Screen {
             mouse_bindings: bindings::default_mouse_bindings(),
             modifiers: Modifiers::default(),
             context_manager,
             ime,
             sugarloaf,
             mouse: Mouse::default(),
+            smooth_scroll: SmoothScroll::new(&config.scroll),
+            scrollbar: Scrollbar::new(&config.scrollbar),
+            notice: Notice::default(),
             state,
             bindings,
             clipboard,
-        })
+            screenshot: None,
+            screenshot_directory: config.screenshot_directory(),
+        };
+        screen.load_custom_shader(config);
+
+        Ok(screen)
+    }
+
+    /// Read the custom shader from the configuration, a shader that fails
+    /// to load is reported and the previous one is kept.
+    fn load_custom_shader(&mut self, config: &rio_config::Config) {
+        let source = match config.custom_shader_path() {
+            Some(path) => match std::fs::read_to_string(&path) {
+                Ok(source) => Some(source),
+                Err(err) => {
+                    self.context_manager.report_error_invalid_shader(format!(
+                        "{}: {err}",
+                        path.display()
+                    ));
+                    return;
+                }
+            },
+            None => None,
+        };
+
+        if let Err(message) = self.sugarloaf.set_custom_shader(source.as_deref()) {
+            self.context_manager.report_error_invalid_shader(message);
+        }
     }
 
     #[inline]
@@ -237,8 +287,11 @@ impl Screen {
         // println!("{:?}", layout.sugarwidth);
         // println!("{:?}", col);
 
+        // Rows are moved down by the fraction of a line of smooth scrolling.
+        let scroll_offset = self.sugarloaf.scroll_offset() * line_fac as f32;
         let line = self.mouse.y.saturating_sub(
-            (layout.margin.top_y * 2. * self.sugarloaf.layout.scale_factor) as usize,
+            (layout.margin.top_y * 2. * self.sugarloaf.layout.scale_factor
+                + scroll_offset) as usize,
         ) / line_fac;
         let calc_line = std::cmp::min(line, layout.lines - 1);
         let line = Line(calc_line as i32) - (display_offset);
@@ -308,11 +361,17 @@ impl Screen {
 
         self.sugarloaf.layout.update();
         self.state = State::new(config, current_theme);
+        self.context_manager.config.profiles = config.profiles.to_owned();
+        self.context_manager.config.hold = config.hold_on_exit;
+        self.context_manager.config.confirm_close = config.confirm_close.to_owned();
+        self.smooth_scroll = SmoothScroll::new(&config.scroll);
+        self.scrollbar = Scrollbar::new(&config.scrollbar);
 
         for context in self.ctx().contexts() {
             let mut terminal = context.terminal.lock();
             terminal.cursor_shape = self.state.get_cursor_state_from_ref().content;
             terminal.blinking_cursor = config.blinking_cursor;
+            terminal.hold = config.hold_on_exit;
         }
 
         let width = self.sugarloaf.layout.width_u32 as u16;
@@ -321,6 +380,9 @@ impl Screen {
         let lines = self.sugarloaf.layout.lines;
         self.resize_all_contexts(width, height, columns, lines);
 
+        self.load_custom_shader(config);
+        self.screenshot_directory = config.screenshot_directory();
+
         self.init(
             self.state.named_colors.background.1,
             config.background.mode.is_image(),
@@ -472,6 +534,32 @@ impl Screen {
             return;
         }
 
+        if mods.is_empty() && self.ctx().current().terminal.lock().held {
+            match key.logical_key.as_ref() {
+                Key::Enter | Key::Escape | Key::Character("q") => {
+                    self.ctx().current().terminal.lock().exit();
+                    return;
+                }
+                Key::Character("r") => {
+                    self.clear_selection();
+                    self.context_manager.rerun_current_context(
+                        (
+                            self.sugarloaf.layout.width_u32,
+                            self.sugarloaf.layout.height_u32,
+                        ),
+                        (self.sugarloaf.layout.columns, self.sugarloaf.layout.lines),
+                        (
+                            &self.state.get_cursor_state_from_ref(),
+                            self.state.has_blinking_enabled,
+                        ),
+                    );
+                    self.render();
+                    return;
+                }
+                _ => {}
+            }
+        }
+
         let binding_mode = BindingMode::new(&mode);
         let mut ignore_chars = None;
 
@@ -527,10 +615,13 @@ impl Screen {
                     Act::ConfigEditor => {
                         self.context_manager.switch_to_settings();
                     }
+                    Act::Screenshot => {
+                        self.context_manager.screenshot();
+                    }
                     Act::WindowCreateNew => {
                         self.context_manager.create_new_window();
                     }
-                    Act::TabCreateNew => {
+                    Act::TabCreateNew { profile } => {
                         let redirect = true;
 
                         self.context_manager.add_context(
@@ -544,6 +635,7 @@ impl Screen {
                                 &self.state.get_cursor_state_from_ref(),
                                 self.state.has_blinking_enabled,
                             ),
+                            profile.as_deref(),
                         );
 
                         self.render();
@@ -551,17 +643,12 @@ impl Screen {
                     Act::TabCloseCurrent => {
                         self.clear_selection();
 
-                        if self.context_manager.config.is_native {
-                            self.context_manager.close_current_window();
-                        } else {
-                            // Kill current context will trigger terminal.exit
-                            // then RioEvent::Exit and eventually try_close_existent_tab
-                            self.context_manager.kill_current_context();
+                        if self.context_manager.can_close(ConfirmAction::CloseTab) {
+                            self.close_tab();
                         }
                     }
                     Act::Quit => {
-                        // TODO: Add it in event system
-                        std::process::exit(0);
+                        self.context_manager.quit();
                     }
                     Act::IncreaseFontSize => {
                         self.change_font_size(FontSizeAction::Increase);
@@ -573,6 +660,10 @@ impl Screen {
                         self.change_font_size(FontSizeAction::Reset);
                     }
                     Act::ScrollPageUp => {
+                        if self.animate_scroll(1) {
+                            continue;
+                        }
+
                         // Move vi mode cursor.
                         let mut terminal =
                             self.context_manager.current_mut().terminal.lock();
@@ -583,6 +674,10 @@ impl Screen {
                         drop(terminal);
                     }
                     Act::ScrollPageDown => {
+                        if self.animate_scroll(-1) {
+                            continue;
+                        }
+
                         // Move vi mode cursor.
                         let mut terminal =
                             self.context_manager.current_mut().terminal.lock();
@@ -1068,6 +1163,17 @@ impl Screen {
         payload.into_bytes()
     }
 
+    #[inline]
+    pub fn close_tab(&mut self) {
+        if self.context_manager.config.is_native {
+            self.context_manager.close_current_window();
+        } else {
+            // Kill current context will trigger terminal.exit
+            // then RioEvent::Exit and eventually try_close_existent_tab
+            self.context_manager.kill_current_context();
+        }
+    }
+
     #[inline]
     pub fn try_close_existent_tab(&mut self) -> bool {
         if self.context_manager.len() > 1 {
@@ -1326,46 +1432,119 @@ impl Screen {
         }
     }
 
+    /// Save the next frame, see [`Screen::present`].
+    #[inline]
+    pub fn request_screenshot(&mut self, request: ScreenshotRequest) {
+        self.screenshot = Some(request);
+    }
+
+    /// Render the frame, saving it when a screenshot was requested.
+    fn present(&mut self) {
+        let request = match self.screenshot.take() {
+            Some(request) => request,
+            None => return self.sugarloaf.render(),
+        };
+
+        let path = screenshot_path(request.path, &self.screenshot_directory);
+        let result = match self.sugarloaf.render_screenshot() {
+            Some(screenshot) => path
+                .parent()
+                .map_or(Ok(()), std::fs::create_dir_all)
+                .map_err(|err| err.to_string())
+                .and_then(|_| screenshot.save_png(&path))
+                .map(|_| path),
+            None => Err(String::from("unable to read the frame")),
+        };
+
+        match &result {
+            Ok(path) => log::info!("screenshot saved to {}", path.display()),
+            Err(err) => log::error!("unable to save screenshot: {err}"),
+        }
+
+        if let Some(reply) = request.reply {
+            let _ = reply.send(result);
+        }
+    }
+
     #[inline]
     pub fn render_settings(&mut self, settings: &router::settings::Settings) {
         crate::router::settings::screen::render(&mut self.sugarloaf, settings);
-        self.sugarloaf.render();
+        self.present();
     }
 
     #[inline]
     pub fn render_assistant(&mut self, assistant: &router::assistant::Assistant) {
         crate::router::assistant::screen(&mut self.sugarloaf, assistant);
-        self.sugarloaf.render();
+        self.present();
+    }
+
+    #[inline]
+    pub fn render_confirm_close(&mut self, confirmation: &router::confirm::Confirmation) {
+        crate::router::confirm::overlay(&mut self.sugarloaf, confirmation);
+        self.render();
     }
 
     #[inline]
     pub fn render_welcome(&mut self) {
         crate::router::welcome::screen(&mut self.sugarloaf);
-        self.sugarloaf.render();
+        self.present();
     }
 
     #[inline]
     pub fn render(&mut self) {
-        let mut terminal = self.ctx().current().terminal.lock();
-        let visible_rows = terminal.visible_rows();
-        let cursor = terminal.cursor();
-        let display_offset = terminal.display_offset();
-        let terminal_has_blinking_enabled = terminal.blinking_cursor;
+        // Only the snapshot is taken under the lock, the frame is prepared
+        // from it without blocking the pty reader.
+        let terminal = &self.context_manager.current().terminal;
+        let is_showing = self.state.is_showing(terminal);
+        let mut terminal = terminal.lock();
+        if !is_showing {
+            terminal.mark_fully_damaged();
+            self.smooth_scroll.reset();
+        }
+        self.smooth_scroll.sync(terminal.display_offset());
+        if let Some(lines) = self.smooth_scroll.tick(terminal.history_size()) {
+            if lines != 0 {
+                terminal.scroll_display(Scroll::Delta(lines));
+            }
+        }
+        let snapshot = terminal.snapshot();
+        self.scrollbar.update(
+            snapshot.display_offset as f32 + self.smooth_scroll.offset(),
+            snapshot.history_size,
+            snapshot.rows.len(),
+            &self.sugarloaf.layout,
+        );
+        let prompt_lines = if self.scrollbar.shows_markers() {
+            terminal.prompt_lines()
+        } else {
+            vec![]
+        };
         drop(terminal);
+        self.sugarloaf
+            .set_scroll_offset(self.smooth_scroll.offset());
+        let terminal_has_blinking_enabled = snapshot.blinking_cursor;
         self.context_manager.update_titles();
 
         self.state.set_ime(self.ime.preedit());
 
-        self.state.prepare_term(
-            visible_rows,
-            cursor,
-            &mut self.sugarloaf,
-            &self.context_manager,
-            display_offset as i32,
-            terminal_has_blinking_enabled,
-        );
-
-        self.sugarloaf.render();
+        self.state
+            .prepare_term(snapshot, &mut self.sugarloaf, &self.context_manager);
+        self.sugarloaf
+            .pile_rects(self.scrollbar.rects(&prompt_lines));
+        self.notice.overlay(&mut self.sugarloaf);
+
+        self.present();
+
+        let hide_in = [self.scrollbar.hide_in(), self.notice.hide_in()]
+            .into_iter()
+            .flatten()
+            .min();
+        if self.smooth_scroll.is_animating() {
+            self.context_manager.schedule_render();
+        } else if let Some(delay) = hide_in {
+            self.context_manager
+                .schedule_render_after(delay.as_millis() as u64 + 1);
+        }
 
         // In this case the configuration of blinking cursor is enabled
         // and the terminal also have instructions of blinking enabled
@@ -1470,6 +1649,99 @@ impl Screen {
         self.ctx_mut().current_mut().messenger.send_bytes(msg);
     }
 
+    /// Logical position of the pointer, for the scrollbar.
+    #[inline]
+    fn scrollbar_pointer(&self) -> (f32, f32) {
+        let scale = self.sugarloaf.layout.scale_factor;
+        (self.mouse.x as f32 / scale, self.mouse.y as f32 / scale)
+    }
+
+    /// Programs with mouse mode get the pointer instead of the scrollbar.
+    #[inline]
+    fn scrollbar_is_bypassed(&self) -> bool {
+        self.mouse_mode() && !self.modifiers.state().shift_key()
+    }
+
+    /// Shows `message` at the bottom of the window for a few seconds.
+    #[inline]
+    pub fn show_notice(&mut self, message: String) {
+        self.notice.show(message);
+    }
+
+    /// Press on the scrollbar, jumping to where it was clicked. Returns
+    /// false if the pointer isn't over it.
+    pub fn scrollbar_press(&mut self) -> bool {
+        if self.scrollbar_is_bypassed() {
+            return false;
+        }
+
+        let (x, y) = self.scrollbar_pointer();
+        if !self.scrollbar.press(x, y) {
+            return false;
+        }
+
+        self.scrollbar_drag();
+        true
+    }
+
+    /// Scroll to follow the pointer while the scrollbar is dragged, returns
+    /// false if it isn't.
+    pub fn scrollbar_drag(&mut self) -> bool {
+        let (_, y) = self.scrollbar_pointer();
+        let display_offset = match self.scrollbar.drag_to(y) {
+            Some(display_offset) => display_offset,
+            None => return false,
+        };
+
+        let mut terminal = self.context_manager.current().terminal.lock();
+        let lines = display_offset as i32 - terminal.display_offset() as i32;
+        if lines != 0 {
+            terminal.scroll_display(Scroll::Delta(lines));
+        }
+        drop(terminal);
+        true
+    }
+
+    #[inline]
+    pub fn scrollbar_release(&mut self) -> bool {
+        self.scrollbar.release()
+    }
+
+    /// Show the scrollbar under the pointer, returns true while it's hovered.
+    pub fn scrollbar_hover(&mut self) -> bool {
+        let (x, y) = self.scrollbar_pointer();
+        // Selections are dragged over it as well.
+        let is_selecting = self.mouse.left_button_state == ElementState::Pressed
+            && !self.selection_is_empty();
+        let has_changed = if self.scrollbar_is_bypassed() || is_selecting {
+            self.scrollbar.leave()
+        } else {
+            self.scrollbar.hover(x, y)
+        };
+        if has_changed {
+            self.context_manager.schedule_render();
+        }
+        self.scrollbar.is_hovered()
+    }
+
+    /// Animate page up (`pages` > 0) and page down with smooth scrolling,
+    /// returns false if they have to scroll right away instead.
+    fn animate_scroll(&mut self, pages: i32) -> bool {
+        if !self.smooth_scroll.is_enabled || self.get_mode().contains(Mode::VI) {
+            return false;
+        }
+
+        let mut terminal = self.context_manager.current().terminal.lock();
+        let lines = pages * terminal.grid.screen_lines() as i32;
+        self.smooth_scroll.sync(terminal.display_offset());
+        self.smooth_scroll
+            .animate(lines as f64, terminal.history_size());
+        drop(terminal);
+
+        self.context_manager.schedule_render();
+        true
+    }
+
     #[inline]
     pub fn scroll(&mut self, new_scroll_x_px: f64, new_scroll_y_px: f64) {
         let width = self.sugarloaf.layout.width as f64;
@@ -1542,6 +1814,20 @@ impl Screen {
             if !content.is_empty() {
                 self.ctx_mut().current_mut().messenger.send_bytes(content);
             }
+        } else if self.smooth_scroll.is_enabled {
+            let lines = new_scroll_y_px * self.mouse.multiplier
+                / self.sugarloaf.layout.font_size as f64;
+
+            let mut terminal = self.context_manager.current().terminal.lock();
+            self.smooth_scroll.sync(terminal.display_offset());
+            let lines = self.smooth_scroll.scroll(lines, terminal.history_size());
+            if lines != 0 {
+                terminal.scroll_display(Scroll::Delta(lines));
+            }
+            drop(terminal);
+
+            // The fraction of a line changes without damage in the terminal.
+            self.context_manager.schedule_render();
         } else {
             self.mouse.accumulated_scroll.y += new_scroll_y_px * self.mouse.multiplier;
             let lines = (self.mouse.accumulated_scroll.y
@@ -1558,3 +1844,41 @@ impl Screen {
         self.mouse.accumulated_scroll.y %= height;
     }
 }
+
+/// File a screenshot is saved to, directories get a name from the time.
+fn screenshot_path(path: Option<PathBuf>, directory: &Path) -> PathBuf {
+    let directory = match path {
+        Some(path) if !path.is_dir() => return path,
+        Some(path) => path,
+        None => directory.to_path_buf(),
+    };
+
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis())
+        .unwrap_or_default();
+    directory.join(format!("rio-{millis}.png"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn screenshot_paths() {
+        let directory = std::env::temp_dir();
+        let path = screenshot_path(None, &directory);
+        assert_eq!(path.parent(), Some(directory.as_path()));
+        let name = path.file_name().unwrap().to_str().unwrap();
+        assert!(name.starts_with("rio-") && name.ends_with(".png"));
+
+        let path = screenshot_path(Some(directory.clone()), Path::new("/unused"));
+        assert_eq!(path.parent(), Some(directory.as_path()));
+
+        let file = directory.join("frame.png");
+        assert_eq!(
+            screenshot_path(Some(file.clone()), Path::new("/unused")),
+            file
+        );
+    }
+}
@@ -0,0 +1,89 @@
+use std::time::{Duration, Instant};
+use sugarloaf::components::rect::Rect;
+use sugarloaf::font::FONT_ID_BUILTIN;
+use sugarloaf::Sugarloaf;
+
+/// Time a notice stays over the terminal.
+const DURATION: Duration = Duration::from_secs(4);
+const HEIGHT: f32 = 28.;
+
+/// Brief message shown at the bottom of the window, like a program of a tab
+/// being killed, positions are in logical pixels.
+#[derive(Default)]
+pub struct Notice {
+    message: Option<(String, Instant)>,
+}
+
+impl Notice {
+    #[inline]
+    pub fn show(&mut self, message: String) {
+        self.message = Some((message, Instant::now()));
+    }
+
+    /// Time left before the notice hides.
+    pub fn hide_in(&self) -> Option<Duration> {
+        let (_, shown_at) = self.message.as_ref()?;
+        DURATION.checked_sub(shown_at.elapsed())
+    }
+
+    /// Draws the notice over the terminal, once it's expired it goes away.
+    pub fn overlay(&mut self, sugarloaf: &mut Sugarloaf) {
+        if self.hide_in().is_none() {
+            self.message = None;
+            return;
+        }
+
+        let Some((message, _)) = &self.message else {
+            return;
+        };
+
+        let red = [1.0, 0.07058824, 0.38039216, 1.0];
+        let background = [0.0627451, 0.0627451, 0.0784314, 1.0];
+        let scale_factor = sugarloaf.layout.scale_factor;
+        let width = sugarloaf.layout.width / scale_factor;
+        let y = sugarloaf.layout.height / scale_factor - HEIGHT;
+
+        sugarloaf.overlay_rects(vec![
+            Rect {
+                position: [0., y],
+                color: background,
+                size: [width * 2., HEIGHT],
+            },
+            Rect {
+                position: [0., y],
+                color: red,
+                size: [8., HEIGHT],
+            },
+        ]);
+
+        sugarloaf.overlay_text(
+            (16., y + HEIGHT / 2.),
+            message.to_owned(),
+            FONT_ID_BUILTIN,
+            14.,
+            [1., 1., 1., 1.],
+            true,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hide_in() {
+        let mut notice = Notice::default();
+        assert_eq!(notice.hide_in(), None);
+
+        notice.show(String::from("Process killed by signal 9"));
+        let hide_in = notice.hide_in().unwrap();
+        assert!(hide_in <= DURATION && hide_in > Duration::ZERO);
+
+        notice.message = Some((
+            String::from("Process killed by signal 9"),
+            Instant::now() - DURATION,
+        ));
+        assert_eq!(notice.hide_in(), None);
+    }
+}
@@ -0,0 +1,183 @@
+use std::time::{Duration, Instant};
+
+/// Viewport position of smooth scrolling, the fraction of a line it's
+/// scrolled past the display offset is drawn by sugarloaf as a translation.
+#[derive(Debug)]
+pub struct SmoothScroll {
+    pub is_enabled: bool,
+    duration: Duration,
+    /// Display offset the fraction belongs to, scrolls from anywhere else
+    /// drop the fraction.
+    display_offset: usize,
+    /// Lines between 0 and 1 the viewport is above `display_offset`.
+    fraction: f64,
+    animation: Option<Animation>,
+}
+
+#[derive(Debug)]
+struct Animation {
+    from: f64,
+    to: f64,
+    start: Instant,
+}
+
+impl SmoothScroll {
+    pub fn new(config: &rio_config::Scroll) -> SmoothScroll {
+        SmoothScroll {
+            is_enabled: config.smooth,
+            duration: Duration::from_millis(config.animation_duration),
+            display_offset: 0,
+            fraction: 0.,
+            animation: None,
+        }
+    }
+
+    /// Follow the display offset of the terminal, anything but this
+    /// scrolled it if they differ.
+    #[inline]
+    pub fn sync(&mut self, display_offset: usize) {
+        if self.display_offset != display_offset {
+            self.display_offset = display_offset;
+            self.reset();
+        }
+    }
+
+    #[inline]
+    pub fn reset(&mut self) {
+        self.fraction = 0.;
+        self.animation = None;
+    }
+
+    /// Lines between 0 and 1 the rows have to be moved down by.
+    #[inline]
+    pub fn offset(&self) -> f32 {
+        self.fraction as f32
+    }
+
+    #[inline]
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_some()
+    }
+
+    /// Scroll by `lines`, positive goes up into the history. Returns the
+    /// lines the display offset of the terminal has to be scrolled by.
+    pub fn scroll(&mut self, lines: f64, history_size: usize) -> i32 {
+        self.animation = None;
+        self.move_to(self.position() + lines, history_size)
+    }
+
+    /// Start an animation scrolling by `lines`, from wherever a running one
+    /// would end.
+    pub fn animate(&mut self, lines: f64, history_size: usize) {
+        let from = self.position();
+        let to = self
+            .animation
+            .as_ref()
+            .map_or(from, |animation| animation.to)
+            + lines;
+
+        self.animation = Some(Animation {
+            from,
+            to: to.clamp(0., history_size as f64),
+            start: Instant::now(),
+        });
+    }
+
+    /// Move the running animation to the current time, returns the lines
+    /// the display offset of the terminal has to be scrolled by.
+    pub fn tick(&mut self, history_size: usize) -> Option<i32> {
+        let animation = self.animation.as_ref()?;
+        let progress = if self.duration.is_zero() {
+            1.
+        } else {
+            (animation.start.elapsed().as_secs_f64() / self.duration.as_secs_f64())
+                .min(1.)
+        };
+
+        let position =
+            animation.from + (animation.to - animation.from) * ease_out(progress);
+        if progress >= 1. {
+            self.animation = None;
+        }
+
+        Some(self.move_to(position, history_size))
+    }
+
+    #[inline]
+    fn position(&self) -> f64 {
+        self.display_offset as f64 + self.fraction
+    }
+
+    fn move_to(&mut self, position: f64, history_size: usize) -> i32 {
+        let position = position.clamp(0., history_size as f64);
+        let display_offset = position.floor();
+        let delta = display_offset as i32 - self.display_offset as i32;
+
+        self.display_offset = display_offset as usize;
+        self.fraction = position - display_offset;
+        delta
+    }
+}
+
+/// Cubic ease out, fast at the start and slowing down to the end.
+#[inline]
+fn ease_out(progress: f64) -> f64 {
+    1. - (1. - progress).powi(3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn smooth_scroll(animation_duration: u64) -> SmoothScroll {
+        SmoothScroll::new(&rio_config::Scroll {
+            smooth: true,
+            animation_duration,
+        })
+    }
+
+    #[test]
+    fn scroll_keeps_the_fraction_of_a_line() {
+        let mut scroll = smooth_scroll(150);
+
+        assert_eq!(scroll.scroll(0.5, 100), 0);
+        assert_eq!(scroll.offset(), 0.5);
+        assert_eq!(scroll.scroll(1.75, 100), 2);
+        assert_eq!(scroll.offset(), 0.25);
+        assert_eq!(scroll.scroll(-0.5, 100), -1);
+        assert_eq!(scroll.offset(), 0.75);
+    }
+
+    #[test]
+    fn scroll_stops_at_the_ends_of_the_history() {
+        let mut scroll = smooth_scroll(150);
+
+        assert_eq!(scroll.scroll(-3.5, 10), 0);
+        assert_eq!(scroll.offset(), 0.);
+        assert_eq!(scroll.scroll(12.5, 10), 10);
+        assert_eq!(scroll.offset(), 0.);
+    }
+
+    #[test]
+    fn other_scrolls_drop_the_fraction() {
+        let mut scroll = smooth_scroll(150);
+        scroll.scroll(2.5, 100);
+
+        scroll.sync(2);
+        assert_eq!(scroll.offset(), 0.5);
+        scroll.sync(0);
+        assert_eq!(scroll.offset(), 0.);
+    }
+
+    #[test]
+    fn animation_ends_on_the_page() {
+        let mut scroll = smooth_scroll(0);
+        scroll.animate(24., 100);
+        assert!(scroll.is_animating());
+
+        assert_eq!(scroll.tick(100), Some(24));
+        assert!(!scroll.is_animating());
+        assert_eq!(scroll.tick(100), None);
+        assert_eq!(scroll.offset(), 0.);
+    }
+}
@@ -0,0 +1,293 @@
+use crate::crosswords::pos::Line;
+use rio_config::scrollbar::{Scrollbar as ScrollbarConfig, ScrollbarVisibility};
+use std::time::{Duration, Instant};
+use sugarloaf::components::rect::Rect;
+use sugarloaf::layout::SugarloafLayout;
+
+/// Time the scrollbar stays after scrolling with `Auto` visibility.
+const AUTO_HIDE: Duration = Duration::from_millis(1000);
+const MIN_THUMB_HEIGHT: f32 = 16.;
+const MARKER_HEIGHT: f32 = 2.;
+/// Space between the scrollbar and the right side of the window.
+const MARGIN_RIGHT: f32 = 2.;
+/// Extra space on the left of the scrollbar the pointer can grab it from.
+const GRAB_MARGIN: f32 = 4.;
+
+/// Overlay scrollbar over the right side of the terminal, positions are in
+/// logical pixels.
+pub struct Scrollbar {
+    visibility: ScrollbarVisibility,
+    width: f32,
+    color: [f32; 4],
+    markers: bool,
+    marker_color: [f32; 4],
+    x: f32,
+    top: f32,
+    height: f32,
+    display_offset: f32,
+    history_size: usize,
+    screen_lines: usize,
+    last_scroll: Option<Instant>,
+    is_hovered: bool,
+    /// Distance from the top of the thumb to the pointer while dragging.
+    grab: Option<f32>,
+}
+
+impl Scrollbar {
+    pub fn new(config: &ScrollbarConfig) -> Scrollbar {
+        Scrollbar {
+            visibility: config.visibility,
+            width: config.width,
+            color: config.color,
+            markers: config.markers,
+            marker_color: config.marker_color,
+            x: 0.,
+            top: 0.,
+            height: 0.,
+            display_offset: 0.,
+            history_size: 0,
+            screen_lines: 0,
+            last_scroll: None,
+            is_hovered: false,
+            grab: None,
+        }
+    }
+
+    /// Follow the viewport of the terminal and the layout, any change of
+    /// `display_offset` counts as scrolling.
+    pub fn update(
+        &mut self,
+        display_offset: f32,
+        history_size: usize,
+        screen_lines: usize,
+        layout: &SugarloafLayout,
+    ) {
+        if display_offset != self.display_offset {
+            self.last_scroll = Some(Instant::now());
+        }
+
+        self.display_offset = display_offset;
+        self.history_size = history_size;
+        self.screen_lines = screen_lines;
+        self.x = layout.width / layout.scale_factor - self.width - MARGIN_RIGHT;
+        self.top = layout.margin.top_y * 2.;
+        self.height = layout.lines as f32 * layout.sugarheight;
+    }
+
+    #[inline]
+    fn is_available(&self) -> bool {
+        self.visibility != ScrollbarVisibility::Never && self.history_size > 0
+    }
+
+    pub fn is_visible(&self) -> bool {
+        if !self.is_available() {
+            return false;
+        }
+
+        match self.visibility {
+            ScrollbarVisibility::Always => true,
+            _ => {
+                self.is_hovered
+                    || self.grab.is_some()
+                    || self
+                        .last_scroll
+                        .is_some_and(|instant| instant.elapsed() < AUTO_HIDE)
+            }
+        }
+    }
+
+    #[inline]
+    pub fn shows_markers(&self) -> bool {
+        self.markers && self.is_visible()
+    }
+
+    /// Time left before an `Auto` scrollbar shown by scrolling hides.
+    pub fn hide_in(&self) -> Option<Duration> {
+        if self.visibility != ScrollbarVisibility::Auto
+            || self.is_hovered
+            || self.grab.is_some()
+        {
+            return None;
+        }
+
+        AUTO_HIDE.checked_sub(self.last_scroll?.elapsed())
+    }
+
+    #[inline]
+    fn thumb_height(&self) -> f32 {
+        let total_lines = (self.history_size + self.screen_lines) as f32;
+        (self.height * self.screen_lines as f32 / total_lines)
+            .max(MIN_THUMB_HEIGHT)
+            .min(self.height)
+    }
+
+    #[inline]
+    fn thumb_top(&self) -> f32 {
+        let progress = 1. - self.display_offset / self.history_size as f32;
+        self.top + (self.height - self.thumb_height()) * progress
+    }
+
+    #[inline]
+    fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x - GRAB_MARGIN && y >= self.top && y <= self.top + self.height
+    }
+
+    /// Track the pointer, returns true if the hover state changed. A
+    /// hidden scrollbar isn't hovered, the pointer is left to the terminal.
+    pub fn hover(&mut self, x: f32, y: f32) -> bool {
+        let is_hovered = self.is_visible() && self.contains(x, y);
+        self.set_hovered(is_hovered)
+    }
+
+    /// Forget the pointer, returns true if it was hovered.
+    #[inline]
+    pub fn leave(&mut self) -> bool {
+        self.set_hovered(false)
+    }
+
+    #[inline]
+    fn set_hovered(&mut self, is_hovered: bool) -> bool {
+        let has_changed = is_hovered != self.is_hovered;
+        self.is_hovered = is_hovered;
+        has_changed
+    }
+
+    #[inline]
+    pub fn is_hovered(&self) -> bool {
+        self.is_hovered
+    }
+
+    /// Grab the thumb, a press outside of it grabs it by the middle so the
+    /// next [`Scrollbar::drag_to`] jumps there.
+    pub fn press(&mut self, x: f32, y: f32) -> bool {
+        if !self.is_visible() || !self.contains(x, y) {
+            return false;
+        }
+
+        let thumb_top = self.thumb_top();
+        let thumb_height = self.thumb_height();
+        self.grab = if y >= thumb_top && y <= thumb_top + thumb_height {
+            Some(y - thumb_top)
+        } else {
+            Some(thumb_height / 2.)
+        };
+        true
+    }
+
+    /// Display offset for the pointer at `y` while dragging.
+    pub fn drag_to(&self, y: f32) -> Option<usize> {
+        let grab = self.grab?;
+        let track = self.height - self.thumb_height();
+        let progress = if track > 0. {
+            ((y - grab - self.top) / track).clamp(0., 1.)
+        } else {
+            0.
+        };
+
+        Some(((1. - progress) * self.history_size as f32).round() as usize)
+    }
+
+    /// Stop dragging, returns false if it wasn't.
+    #[inline]
+    pub fn release(&mut self) -> bool {
+        self.grab.take().is_some()
+    }
+
+    /// Rects of the thumb and of the prompts in `prompt_lines`, lines of
+    /// the history are negative.
+    pub fn rects(&self, prompt_lines: &[Line]) -> Vec<Rect> {
+        if !self.is_visible() {
+            return vec![];
+        }
+
+        let mut rects = vec![Rect {
+            position: [self.x, self.thumb_top()],
+            color: self.color,
+            size: [self.width * 2., self.thumb_height()],
+        }];
+
+        if self.markers {
+            let total_lines = (self.history_size + self.screen_lines) as f32;
+            for line in prompt_lines {
+                let progress = (line.0 + self.history_size as i32) as f32 / total_lines;
+                rects.push(Rect {
+                    position: [self.x, self.top + progress * self.height],
+                    color: self.marker_color,
+                    size: [self.width * 2., MARKER_HEIGHT],
+                });
+            }
+        }
+
+        rects
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scrollbar(display_offset: f32, history_size: usize) -> Scrollbar {
+        let mut scrollbar = Scrollbar::new(&ScrollbarConfig {
+            visibility: ScrollbarVisibility::Always,
+            ..ScrollbarConfig::default()
+        });
+        scrollbar.display_offset = display_offset;
+        scrollbar.history_size = history_size;
+        scrollbar.screen_lines = 10;
+        scrollbar.x = 100.;
+        scrollbar.top = 0.;
+        scrollbar.height = 200.;
+        scrollbar
+    }
+
+    #[test]
+    fn thumb_follows_the_display_offset() {
+        let bottom = scrollbar(0., 30);
+        assert_eq!(bottom.thumb_height(), 50.);
+        assert_eq!(bottom.thumb_top(), 150.);
+
+        let top = scrollbar(30., 30);
+        assert_eq!(top.thumb_top(), 0.);
+        assert!(scrollbar(0., 0).rects(&[]).is_empty());
+    }
+
+    #[test]
+    fn click_jumps_and_drag_follows() {
+        let mut scrollbar = scrollbar(0., 30);
+        assert!(!scrollbar.press(10., 25.));
+
+        // Outside of the thumb it's grabbed by the middle.
+        assert!(scrollbar.press(102., 25.));
+        assert_eq!(scrollbar.drag_to(25.), Some(30));
+        assert_eq!(scrollbar.drag_to(100.), Some(15));
+        assert_eq!(scrollbar.drag_to(500.), Some(0));
+        assert!(scrollbar.release());
+        assert_eq!(scrollbar.drag_to(100.), None);
+    }
+
+    #[test]
+    fn markers_are_placed_along_the_history() {
+        let scrollbar = scrollbar(0., 30);
+        let rects = scrollbar.rects(&[Line(-30), Line(0)]);
+        assert_eq!(rects.len(), 3);
+        assert_eq!(rects[1].position, [100., 0.]);
+        assert_eq!(rects[2].position, [100., 150.]);
+    }
+
+    #[test]
+    fn hidden_auto_scrollbar_is_not_hovered() {
+        let mut scrollbar = scrollbar(0., 30);
+        scrollbar.visibility = ScrollbarVisibility::Auto;
+        assert!(!scrollbar.is_visible());
+        assert!(!scrollbar.hover(102., 25.));
+        assert!(!scrollbar.is_hovered());
+        assert!(!scrollbar.press(102., 25.));
+
+        // Once shown by scrolling, hovering keeps it.
+        scrollbar.last_scroll = Some(Instant::now());
+        assert!(scrollbar.hover(102., 25.));
+        assert_eq!(scrollbar.hide_in(), None);
+        assert!(scrollbar.leave());
+        assert!(!scrollbar.is_hovered());
+    }
+}
@@ -3,6 +3,9 @@ use crate::crosswords::grid::row::Row;
 use crate::crosswords::pos;
 use crate::crosswords::pos::CursorState;
 use crate::crosswords::square::{Flags, Square};
+use crate::crosswords::{Crosswords, TermSnapshot};
+use crate::event::sync::FairMutex;
+use crate::event::EventListener;
 use crate::ime::Preedit;
 use crate::screen::navigation::ScreenNavigation;
 use crate::screen::{context, EventProxy};
@@ -14,6 +17,7 @@ use rio_config::colors::{
 use rio_config::Config;
 use std::collections::HashMap;
 use std::rc::Rc;
+use std::sync::Arc;
 use std::time::{Duration, Instant};
 use sugarloaf::core::{Sugar, SugarDecoration, SugarStack, SugarStyle};
 use sugarloaf::Sugarloaf;
@@ -31,6 +35,10 @@ pub struct State {
     is_ime_enabled: bool,
     pub last_typing: Option<Instant>,
     pub named_colors: Colors,
+    // Colors of the configuration, tabs of a profile with a theme have
+    // their own.
+    base_colors: Colors,
+    has_background_image: bool,
     font_size: f32,
     pub colors: List,
     navigation: ScreenNavigation,
@@ -40,6 +48,12 @@ pub struct State {
     pub is_blinking: bool,
     ignore_selection_fg_color: bool,
     dynamic_background: ([f32; 4], wgpu::Color),
+    // Rows kept from previous frames only describe the terminal drawn last,
+    // `None` means they can't be trusted at all.
+    last_terminal: Option<usize>,
+    last_cursor_row: Option<usize>,
+    // Viewport rows of the last terminal drawn, updated from snapshots.
+    rows: Vec<Row<Square>>,
 }
 
 // TODO: Finish from
@@ -88,7 +102,8 @@ impl State {
             }
         }
 
-        let dynamic_background = if config.background.mode.is_image() {
+        let has_background_image = config.background.mode.is_image();
+        let dynamic_background = if has_background_image {
             ([0., 0., 0., 0.], wgpu::Color::TRANSPARENT)
         } else {
             named_colors.background
@@ -122,15 +137,28 @@ impl State {
             font_size: config.fonts.size,
             selection_range: None,
             named_colors,
+            base_colors: named_colors,
+            has_background_image,
             dynamic_background,
             cursor: Cursor {
                 content: config.cursor,
                 content_ref: config.cursor,
                 state: CursorState::new(config.cursor),
             },
+            last_terminal: None,
+            last_cursor_row: None,
+            rows: vec![],
         }
     }
 
+    fn set_named_colors(&mut self, named_colors: Colors, sugarloaf: &mut Sugarloaf) {
+        self.named_colors = named_colors;
+        if !self.has_background_image {
+            self.dynamic_background = named_colors.background;
+        }
+        sugarloaf.set_background_color(named_colors.background.1);
+    }
+
     #[inline]
     pub fn get_cursor_state_from_ref(&self) -> CursorState {
         CursorState::new(self.cursor.content_ref)
@@ -449,22 +477,90 @@ impl State {
         self.selection_range = selection_range;
     }
 
+    /// Whether the rows kept from previous frames belong to `terminal`.
+    ///
+    /// If not, the terminal has to be fully damaged before taking the next
+    /// snapshot. The address can be reused by a new terminal, but that one
+    /// starts out fully damaged anyway.
     #[inline]
-    pub fn prepare_term(
+    pub fn is_showing(&self, terminal: &Arc<FairMutex<Crosswords<EventProxy>>>) -> bool {
+        self.last_terminal == Some(Arc::as_ptr(terminal) as usize)
+    }
+
+    #[inline]
+    pub fn prepare_term<T: EventListener + Clone + Send + 'static>(
         &mut self,
-        rows: Vec<Row<Square>>,
-        cursor: CursorState,
+        snapshot: TermSnapshot,
         sugarloaf: &mut Sugarloaf,
-        context_manager: &context::ContextManager<EventProxy>,
-        display_offset: i32,
-        terminal_has_blinking_enabled: bool,
+        context_manager: &context::ContextManager<T>,
     ) {
-        self.cursor.state = cursor;
+        self.cursor.state = snapshot.cursor;
         let mut is_cursor_visible = self.cursor.state.is_visible();
+        let display_offset = snapshot.display_offset as i32;
+
+        let named_colors = context_manager.current().colors.unwrap_or(self.base_colors);
+        let has_changed_colors = named_colors != self.named_colors;
+        if has_changed_colors {
+            self.set_named_colors(named_colors, sugarloaf);
+        }
+
+        self.last_terminal =
+            Some(Arc::as_ptr(&context_manager.current().terminal) as usize);
+
+        let lines = snapshot.rows.len();
+        let mut damaged_rows = vec![false; lines];
+        self.rows.truncate(lines);
+        for (i, row) in snapshot.rows.into_iter().enumerate() {
+            match row {
+                Some(row) if i < self.rows.len() => {
+                    self.rows[i] = row;
+                    damaged_rows[i] = true;
+                }
+                Some(row) => {
+                    self.rows.push(row);
+                    damaged_rows[i] = true;
+                }
+                // Only partial snapshots leave rows out, those never change
+                // the number of lines.
+                None if i >= self.rows.len() => {
+                    self.rows.push(Row::new(sugarloaf.layout.columns));
+                    damaged_rows[i] = true;
+                }
+                None => {}
+            }
+        }
+
+        if has_changed_colors {
+            damaged_rows.fill(true);
+        }
+
+        // The cursor row changes with blinking and IME, so it's always redrawn.
+        let cursor_row = self.cursor.state.pos.row.0 as usize;
+        for row in [self.last_cursor_row, Some(cursor_row)]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(damaged) = damaged_rows.get_mut(row) {
+                *damaged = true;
+            }
+        }
+        self.last_cursor_row = Some(cursor_row);
 
         self.font_size = sugarloaf.layout.font_size;
-        if let Some(active_selection) = self.selection_range {
+        if sugarloaf.scroll_offset() > 0. {
+            if let Some(row) = &snapshot.above {
+                let sugar_stack = self.create_sugar_stack(row, false);
+                sugarloaf.stack_above(sugar_stack);
+            }
+        }
+
+        let rows = std::mem::take(&mut self.rows);
+        if let Some(active_selection) = snapshot.selection {
             for (i, row) in rows.iter().enumerate() {
+                if !damaged_rows[i] && sugarloaf.stack_cached_row(i) {
+                    continue;
+                }
+
                 let has_cursor = is_cursor_visible && self.cursor.state.pos.row == i;
                 let sugar_stack = self.create_sugar_stack_with_selection(
                     row,
@@ -473,11 +569,11 @@ impl State {
                     pos::Line(i as i32),
                     display_offset,
                 );
-                sugarloaf.stack(sugar_stack);
+                sugarloaf.stack_row(i, sugar_stack);
             }
         } else {
             // Only blink cursor if does not contain selection
-            if self.has_blinking_enabled && terminal_has_blinking_enabled {
+            if self.has_blinking_enabled && snapshot.blinking_cursor {
                 let mut should_blink = true;
                 if let Some(last_typing_time) = self.last_typing {
                     if last_typing_time.elapsed() < Duration::from_secs(1) {
@@ -492,11 +588,19 @@ impl State {
             }
 
             for (i, row) in rows.iter().enumerate() {
+                if !damaged_rows[i] && sugarloaf.stack_cached_row(i) {
+                    continue;
+                }
+
                 let has_cursor = is_cursor_visible && self.cursor.state.pos.row == i;
                 let sugar_stack = self.create_sugar_stack(row, has_cursor);
-                sugarloaf.stack(sugar_stack);
+                sugarloaf.stack_row(i, sugar_stack);
             }
         }
+        self.rows = rows;
+        sugarloaf.set_cursor_position(
+            is_cursor_visible.then_some((self.cursor.state.pos.col.0, cursor_row)),
+        );
 
         // This is a fake row created only for visual purposes
         let empty_last_line =
@@ -526,3 +630,143 @@ impl State {
         }
     }
 }
+
+// Frames of the software renderer compared against the PNGs of
+// rio/tests/golden, run with RIO_UPDATE_GOLDEN=1 to write them again.
+// Paddings and tabs differ between platforms, the PNGs are from Linux.
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::crosswords::Crosswords;
+    use crate::event::VoidListener;
+    use crate::performer::handler::ParserProcessor;
+    use crate::router::confirm::{self, ConfirmAction, Confirmation};
+    use crate::screen::context::ContextManager;
+    use std::path::PathBuf;
+    use sugarloaf::font::loader::Database;
+    use sugarloaf::layout::SugarloafLayout;
+    use sugarloaf::tools::screenshot::Screenshot;
+    use winit::window::WindowId;
+
+    const WIDTH: u32 = 320;
+    const HEIGHT: u32 = 120;
+    /// Channels may be this far from the PNG, rasterization isn't exactly
+    /// the same everywhere.
+    const TOLERANCE: u8 = 8;
+
+    fn render(input: &str) -> Screenshot {
+        render_with(WIDTH, HEIGHT, input, |_| {})
+    }
+
+    /// Frame of `input` with whatever `overlay` draws over it.
+    fn render_with(
+        width: u32,
+        height: u32,
+        input: &str,
+        overlay: impl FnOnce(&mut Sugarloaf),
+    ) -> Screenshot {
+        let config = Rc::new(Config::default());
+        let layout = SugarloafLayout::new(
+            width as f32,
+            height as f32,
+            (config.padding_x, 10., 0.),
+            1.,
+            config.fonts.size,
+            config.line_height,
+            (2, 1),
+        );
+        // Without system fonts only the ones of Sugarloaf are used.
+        let mut sugarloaf = Sugarloaf::new_software(
+            width,
+            height,
+            1.,
+            config.fonts.to_owned(),
+            layout,
+            Some(&Arc::new(Database::new())),
+        )
+        .unwrap_or_else(|with_errors| with_errors.instance);
+        sugarloaf.set_background_color(config.colors.background.1);
+        sugarloaf.calculate_bounds();
+
+        let mut state = State::new(&config, None);
+        let mut terminal = Crosswords::new(
+            sugarloaf.layout.columns,
+            sugarloaf.layout.lines,
+            VoidListener {},
+            WindowId::from(0),
+        );
+        ParserProcessor::new().advance_bytes(&mut terminal, input.as_bytes());
+        let context_manager =
+            ContextManager::start_with_capacity(1, VoidListener {}, WindowId::from(0))
+                .unwrap();
+
+        state.prepare_term(terminal.snapshot(), &mut sugarloaf, &context_manager);
+        overlay(&mut sugarloaf);
+        sugarloaf.render_screenshot().unwrap()
+    }
+
+    fn assert_golden(name: &str, frame: Screenshot) {
+        let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("tests/golden")
+            .join(format!("{name}.png"));
+
+        if std::env::var_os("RIO_UPDATE_GOLDEN").is_some() {
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            frame.save_png(&path).unwrap();
+            return;
+        }
+
+        let golden = image::open(&path)
+            .unwrap_or_else(|err| panic!("{}: {err}", path.display()))
+            .to_rgba8();
+        assert_eq!(golden.dimensions(), (frame.width, frame.height));
+
+        let different = golden
+            .as_raw()
+            .iter()
+            .zip(&frame.data)
+            .filter(|(golden, frame)| golden.abs_diff(**frame) > TOLERANCE)
+            .count();
+        if different > 0 {
+            let output = std::env::temp_dir().join(format!("rio-golden-{name}.png"));
+            let _ = frame.save_png(&output);
+            panic!(
+                "{different} channels differ from {}, the frame is at {}",
+                path.display(),
+                output.display()
+            );
+        }
+    }
+
+    #[test]
+    fn golden_text_and_cursor() {
+        assert_golden("text_and_cursor", render("~ $ echo rio\r\nrio\r\n~ $ "));
+    }
+
+    #[test]
+    fn golden_colors_and_styles() {
+        assert_golden(
+            "colors_and_styles",
+            render(concat!(
+                "\x1b[31mred\x1b[0m \x1b[1;32mbold\x1b[0m \x1b[3;34mitalic\x1b[0m\r\n",
+                "\x1b[4munderline\x1b[0m \x1b[30;43m highlight \x1b[0m \x1b[7mreverse\x1b[0m\r\n",
+                "\u{250c}\u{2500}\u{2510} \u{2588}\u{2592} \u{2801}\u{28ff}",
+            )),
+        );
+    }
+
+    #[test]
+    fn golden_confirm_overlay() {
+        let confirmation = Confirmation {
+            action: ConfirmAction::CloseTab,
+            processes: vec![String::from("vim"), String::from("cargo")],
+        };
+        let input = "~ $ echo rio\r\nrio\r\n".repeat(8) + "~ $ vim";
+        assert_golden(
+            "confirm_overlay",
+            render_with(480, 240, &input, |sugarloaf| {
+                confirm::overlay(sugarloaf, &confirmation)
+            }),
+        );
+    }
+}
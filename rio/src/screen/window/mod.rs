@@ -21,7 +21,7 @@ pub fn create_window_builder(
     .unwrap();
 
     let mut window_builder = WindowBuilder::new()
-        .with_title(title)
+        .with_title(config.window.title.as_deref().unwrap_or(title))
         .with_min_inner_size(winit::dpi::LogicalSize {
             width: DEFAULT_MINIMUM_WINDOW_WIDTH,
             height: DEFAULT_MINIMUM_WINDOW_HEIGHT,
@@ -34,14 +34,16 @@ pub fn create_window_builder(
     {
         use crate::screen::constants::APPLICATION_ID;
         use winit::platform::x11::WindowBuilderExtX11;
-        window_builder = window_builder.with_name(APPLICATION_ID, "");
+        let class = config.window.class.as_deref().unwrap_or(APPLICATION_ID);
+        window_builder = window_builder.with_name(class, "");
     }
 
     #[cfg(all(feature = "wayland", not(any(target_os = "macos", windows))))]
     {
         use crate::screen::constants::APPLICATION_ID;
         use winit::platform::wayland::WindowBuilderExtWayland;
-        window_builder = window_builder.with_name(APPLICATION_ID, "");
+        let class = config.window.class.as_deref().unwrap_or(APPLICATION_ID);
+        window_builder = window_builder.with_name(class, "");
     }
 
     #[cfg(target_os = "macos")]
@@ -1,6 +1,11 @@
+use crate::cli;
 use crate::clipboard::ClipboardType;
 use crate::event::{ClickState, EventP, EventProxy, RioEvent, RioEventType};
 use crate::ime::Preedit;
+#[cfg(unix)]
+use crate::ipc;
+use crate::router::assistant::ErrorReport;
+use crate::router::confirm::{ConfirmAction, ConfirmAnswer, Confirmation};
 use crate::router::{RoutePath, RouteWindow, Router};
 use crate::scheduler::{Scheduler, TimerId, Topic};
 use crate::watch::watch;
@@ -23,6 +28,9 @@ use winit::window::{CursorIcon, Fullscreen};
 
 pub struct Sequencer {
     config: Rc<rio_config::Config>,
+    /// Options of the command line, applied again on reloads.
+    config_options: cli::ConfigOptions,
+    window_options: cli::WindowOptions,
     event_proxy: Option<EventProxy>,
     router: Router,
 }
@@ -31,14 +39,20 @@ impl Sequencer {
     pub fn new(
         config: rio_config::Config,
         config_error: Option<rio_config::ConfigError>,
+        config_options: cli::ConfigOptions,
+        window_options: cli::WindowOptions,
     ) -> Sequencer {
         let mut router = Router::new();
         if let Some(error) = config_error {
             router.propagate_error_to_next_route(error.into());
+        } else if let Some(warnings) = ErrorReport::from_config_warnings(&config) {
+            router.propagate_error_to_next_route(warnings);
         }
 
         Sequencer {
             config: Rc::new(config),
+            config_options,
+            window_options,
             event_proxy: None,
             router,
         }
@@ -50,10 +64,16 @@ impl Sequencer {
     ) -> Result<(), Box<dyn Error>> {
         let proxy = event_loop.create_proxy();
         self.event_proxy = Some(EventProxy::new(proxy.clone()));
-        let _ = watch(
+        let mut config_watcher = watch(
             rio_config::config_dir_path(),
             self.event_proxy.clone().unwrap(),
-        );
+        )
+        .ok();
+        if let Some(config_watcher) = &mut config_watcher {
+            config_watcher.track(&self.config.loaded_files);
+        }
+        #[cfg(unix)]
+        ipc::spawn_ipc_socket(self.event_proxy.clone().unwrap());
         let mut scheduler = Scheduler::new(proxy);
 
         let window =
@@ -97,15 +117,16 @@ impl Sequencer {
                                 }
                             }
                             RioEventType::Rio(RioEvent::UpdateConfig) => {
-                                let mut config_error: Option<rio_config::ConfigError> =
-                                    None;
-                                let config = match rio_config::Config::try_load() {
-                                    Ok(config) => config,
-                                    Err(error) => {
-                                        config_error = Some(error);
-                                        rio_config::Config::default()
+                                let (config, config_error) =
+                                    self.config_options.load(&self.window_options);
+
+                                // A configuration that failed to load keeps
+                                // the files tracked so far, to reload once fixed.
+                                if let Some(config_watcher) = &mut config_watcher {
+                                    if !config.loaded_files.is_empty() {
+                                        config_watcher.track(&config.loaded_files);
                                     }
-                                };
+                                }
 
                                 self.config = config.into();
                                 for (_id, route) in self.router.routes.iter_mut() {
@@ -116,6 +137,10 @@ impl Sequencer {
 
                                     if let Some(error) = &config_error {
                                         route.report_error(&error.to_owned().into());
+                                    } else if let Some(warnings) =
+                                        ErrorReport::from_config_warnings(&self.config)
+                                    {
+                                        route.report_error(&warnings);
                                     } else {
                                         route.clear_errors();
                                     }
@@ -123,6 +148,90 @@ impl Sequencer {
                                     route.redraw();
                                 }
                             }
+                            RioEventType::Rio(RioEvent::ChildExit(status)) => {
+                                // Rio hangs up the processes of the tabs it closes,
+                                // any other signal is worth a warning and a notice
+                                // on the window.
+                                #[cfg(unix)]
+                                let killed = status
+                                    .signal
+                                    .is_some_and(|signal| signal != libc::SIGHUP);
+                                #[cfg(not(unix))]
+                                let killed = false;
+
+                                if killed {
+                                    log::warn!("child process {status}");
+                                    if let Some(route) =
+                                        self.router.routes.get_mut(&window_id)
+                                    {
+                                        route
+                                            .window
+                                            .screen
+                                            .show_notice(format!("Process {status}"));
+                                        route.redraw();
+                                    }
+                                } else {
+                                    log::info!("child process {status}");
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::TerminalTitle) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.window.screen.context_manager.outdate_titles();
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::ForegroundProcess(process)) => {
+                                log::debug!(
+                                    "foreground process {} ({})",
+                                    process.name,
+                                    process.pid
+                                );
+
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.window.screen.context_manager.outdate_titles();
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::ConfirmClose(confirmation)) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.confirm_close(confirmation);
+                                    route.redraw();
+                                }
+                            }
+                            RioEventType::Rio(RioEvent::Quit) => {
+                                let processes: Vec<String> = self
+                                    .router
+                                    .routes
+                                    .values()
+                                    .flat_map(|route| {
+                                        route
+                                            .window
+                                            .screen
+                                            .context_manager
+                                            .busy_processes(false)
+                                    })
+                                    .collect();
+                                if processes.is_empty() {
+                                    *control_flow = winit::event_loop::ControlFlow::Exit;
+                                    return;
+                                }
+
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.confirm_close(Confirmation {
+                                        action: ConfirmAction::Quit,
+                                        processes,
+                                    });
+                                    route.redraw();
+                                }
+                            }
                             RioEventType::Rio(RioEvent::Exit) => {
                                 if let Some(route) =
                                     self.router.routes.get_mut(&window_id)
@@ -141,7 +250,7 @@ impl Sequencer {
                                 if let Some(route) =
                                     self.router.routes.get_mut(&window_id)
                                 {
-                                    route.window.screen.render();
+                                    route.render_terminal();
                                 }
                             }
                             RioEventType::Rio(RioEvent::PrepareRender(millis)) => {
@@ -276,6 +385,28 @@ impl Sequencer {
                                     );
                                 }
                             }
+                            RioEventType::Rio(RioEvent::Screenshot(request)) => {
+                                // Requests from the socket aren't tied to a window,
+                                // those go to the focused one.
+                                let window_id =
+                                    if self.router.routes.contains_key(&window_id) {
+                                        Some(window_id)
+                                    } else {
+                                        self.router
+                                            .routes
+                                            .iter()
+                                            .find(|(_, route)| route.window.is_focused)
+                                            .or_else(|| self.router.routes.iter().next())
+                                            .map(|(id, _)| *id)
+                                    };
+
+                                if let Some(route) = window_id
+                                    .and_then(|id| self.router.routes.get_mut(&id))
+                                {
+                                    route.window.screen.request_screenshot(request);
+                                    route.redraw();
+                                }
+                            }
                             RioEventType::Rio(RioEvent::CreateConfigEditor) => {
                                 if let Some(route) =
                                     self.router.routes.get_mut(&window_id)
@@ -376,6 +507,19 @@ impl Sequencer {
                         window_id,
                         ..
                     } => {
+                        if let Some(route) = self.router.routes.get_mut(&window_id) {
+                            let processes =
+                                route.window.screen.context_manager.busy_processes(false);
+                            if !processes.is_empty() {
+                                route.confirm_close(Confirmation {
+                                    action: ConfirmAction::CloseWindow,
+                                    processes,
+                                });
+                                route.redraw();
+                                return;
+                            }
+                        }
+
                         self.router.routes.remove(&window_id);
 
                         if self.router.routes.is_empty() {
@@ -399,7 +543,7 @@ impl Sequencer {
                         ..
                     } => {
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
-                            if route.path != RoutePath::Terminal {
+                            if !route.is_terminal_focused() {
                                 return;
                             }
 
@@ -425,6 +569,17 @@ impl Sequencer {
                                 }
                             }
 
+                            if button == MouseButton::Left {
+                                let screen = &mut route.window.screen;
+                                let is_scrollbar = match state {
+                                    ElementState::Pressed => screen.scrollbar_press(),
+                                    ElementState::Released => screen.scrollbar_release(),
+                                };
+                                if is_scrollbar {
+                                    return;
+                                }
+                            }
+
                             match state {
                                 ElementState::Pressed => {
                                     // Process mouse press before bindings to update the `click_state`.
@@ -548,7 +703,7 @@ impl Sequencer {
                         ..
                     } => {
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
-                            if route.path != RoutePath::Terminal {
+                            if !route.is_terminal_focused() {
                                 route
                                     .window
                                     .winit_window
@@ -639,6 +794,16 @@ impl Sequencer {
                             route.window.screen.mouse.x = x;
                             route.window.screen.mouse.y = y;
 
+                            if route.window.screen.scrollbar_drag()
+                                || route.window.screen.scrollbar_hover()
+                            {
+                                route
+                                    .window
+                                    .winit_window
+                                    .set_cursor_icon(CursorIcon::Default);
+                                return;
+                            }
+
                             let point =
                                 route.window.screen.mouse_position(display_offset);
                             let square_changed = old_point != point;
@@ -703,7 +868,7 @@ impl Sequencer {
                         ..
                     } => {
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
-                            if route.path != RoutePath::Terminal {
+                            if !route.is_terminal_focused() {
                                 return;
                             }
 
@@ -757,6 +922,46 @@ impl Sequencer {
                         window_id,
                         ..
                     } => {
+                        let confirmed = self
+                            .router
+                            .routes
+                            .get_mut(&window_id)
+                            .and_then(|route| route.answer_confirm_close(&key_event));
+                        match confirmed {
+                            Some(ConfirmAnswer::Cancelled) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.redraw();
+                                }
+                                return;
+                            }
+                            Some(ConfirmAnswer::Confirmed(ConfirmAction::CloseTab)) => {
+                                if let Some(route) =
+                                    self.router.routes.get_mut(&window_id)
+                                {
+                                    route.window.screen.close_tab();
+                                    route.redraw();
+                                }
+                                return;
+                            }
+                            Some(ConfirmAnswer::Confirmed(
+                                ConfirmAction::CloseWindow,
+                            )) => {
+                                self.router.routes.remove(&window_id);
+
+                                if self.router.routes.is_empty() {
+                                    *control_flow = winit::event_loop::ControlFlow::Exit;
+                                }
+                                return;
+                            }
+                            Some(ConfirmAnswer::Confirmed(ConfirmAction::Quit)) => {
+                                *control_flow = winit::event_loop::ControlFlow::Exit;
+                                return;
+                            }
+                            None => {}
+                        }
+
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
                             if route.has_key_wait(&key_event) {
                                 if route.path == RoutePath::Settings
@@ -790,7 +995,9 @@ impl Sequencer {
                         ..
                     } => {
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
-                            if route.path == RoutePath::Assistant {
+                            if route.path == RoutePath::Assistant
+                                || route.confirmation.is_some()
+                            {
                                 return;
                             }
 
@@ -875,7 +1082,9 @@ impl Sequencer {
                         ..
                     } => {
                         if let Some(route) = self.router.routes.get_mut(&window_id) {
-                            if route.path == RoutePath::Assistant {
+                            if route.path == RoutePath::Assistant
+                                || route.confirmation.is_some()
+                            {
                                 return;
                             }
 
@@ -923,6 +1132,8 @@ impl Sequencer {
                         // TODO: Now we are forcing an exit operation
                         // but it should be revaluated since CloseRequested in MacOs
                         // not necessarily exit the process
+                        #[cfg(unix)]
+                        ipc::remove_socket();
                         std::process::exit(0);
                     }
 
@@ -969,7 +1180,7 @@ impl Sequencer {
                                     route.window.screen.render_welcome();
                                 }
                                 RoutePath::Terminal => {
-                                    route.window.screen.render();
+                                    route.render_terminal();
                                 }
                                 RoutePath::Settings => {
                                     route.window.screen.render_settings(&route.settings);
@@ -988,6 +1199,9 @@ impl Sequencer {
             },
         );
 
+        #[cfg(unix)]
+        ipc::remove_socket();
+
         Ok(())
     }
 }
@@ -1,18 +1,55 @@
 use crate::event::{EventListener, RioEvent};
 use notify::{Config, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use winit::window::WindowId;
 
 const POLLING_TIMEOUT: Duration = Duration::from_secs(1);
 
+/// Watcher of the configuration directory, which also follows the files
+/// imported from elsewhere.
+pub struct ConfigWatcher {
+    watcher: Arc<Mutex<RecommendedWatcher>>,
+    directory: PathBuf,
+    files: Vec<PathBuf>,
+}
+
+impl ConfigWatcher {
+    /// Watch the files of `loaded_files` out of the configuration
+    /// directory instead of the ones tracked so far. They're watched again
+    /// even if tracked already, editors saving by renaming drop the watch.
+    pub fn track(&mut self, loaded_files: &[PathBuf]) {
+        let Ok(mut watcher) = self.watcher.lock() else {
+            return;
+        };
+
+        for file in self.files.drain(..) {
+            let _ = watcher.unwatch(&file);
+        }
+
+        for file in loaded_files {
+            if file.starts_with(&self.directory) {
+                continue;
+            }
+
+            match watcher.watch(file, RecursiveMode::NonRecursive) {
+                Ok(()) => self.files.push(file.to_owned()),
+                Err(err_message) => {
+                    log::warn!("unable to watch imported config {err_message:?}")
+                }
+            }
+        }
+    }
+}
+
 pub fn watch<
     P: AsRef<Path> + std::marker::Send + 'static,
     T: EventListener + std::marker::Send + 'static,
 >(
     path: P,
     event_proxy: T,
-) -> notify::Result<()> {
+) -> notify::Result<ConfigWatcher> {
     let (tx, rx) = std::sync::mpsc::channel();
 
     // Automatically select the best implementation for your platform.
@@ -22,15 +59,22 @@ pub fn watch<
         Config::default().with_poll_interval(POLLING_TIMEOUT),
     )?;
 
-    tokio::spawn(async move {
-        // Add a path to be watched. All files and directories at that path and
-        // below will be monitored for changes.
-        if let Err(err_message) =
-            watcher.watch(path.as_ref(), RecursiveMode::NonRecursive)
-        {
-            log::warn!("unable to watch config directory {err_message:?}");
-        };
+    // Add a path to be watched. All files and directories at that path and
+    // below will be monitored for changes, like themes and shaders.
+    if let Err(err_message) = watcher.watch(path.as_ref(), RecursiveMode::Recursive) {
+        log::warn!("unable to watch config directory {err_message:?}");
+    };
 
+    let config_watcher = ConfigWatcher {
+        watcher: Arc::new(Mutex::new(watcher)),
+        directory: path.as_ref().to_path_buf(),
+        files: vec![],
+    };
+
+    // The watcher stops with the last reference to it.
+    let watcher = config_watcher.watcher.clone();
+    tokio::spawn(async move {
+        let _watcher = watcher;
         for res in rx {
             match res {
                 Ok(event) => match event.kind {
@@ -51,5 +95,5 @@ pub fn watch<
         }
     });
 
-    Ok(())
+    Ok(config_watcher)
 }
@@ -1,4 +1,4 @@
-mod atlas;
+pub mod atlas;
 mod raster;
 pub mod types;
 
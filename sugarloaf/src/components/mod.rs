@@ -1,4 +1,7 @@
 pub mod core;
 pub mod layer;
+pub mod post_processing;
 pub mod rect;
+#[cfg(feature = "software")]
+pub mod software;
 pub mod text;
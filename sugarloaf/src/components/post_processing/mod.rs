@@ -0,0 +1,352 @@
+// Custom shaders get the frame drawn into a texture and paint it to the
+// surface, for effects like CRT screens or higher contrast. They are WGSL
+// with a `fs_main` fragment entry point, the uniforms, the frame and the
+// vertex shader come from post_processing.wgsl.
+//
+// Example:
+//
+// @fragment
+// fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+//     let color = textureSample(frame, frame_sampler, input.uv);
+//     let scanline = 0.9 + 0.1 * sin(input.position.y * 3.14);
+//     return vec4<f32>(color.rgb * scanline, color.a);
+// }
+
+use crate::context::Context;
+use bytemuck::{Pod, Zeroable};
+use std::{borrow::Cow, mem};
+
+const PREAMBLE: &str = include_str!("post_processing.wgsl");
+const ENTRY_POINT: &str = "fs_main";
+
+/// Shader painting the frame as it is.
+pub const PASSTHROUGH: &str = "@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(frame, frame_sampler, input.uv);
+}";
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+struct Uniforms {
+    resolution: [f32; 2],
+    time: f32,
+    scale: f32,
+    cursor: [f32; 4],
+    background: [f32; 4],
+}
+
+/// Texture the frame is drawn into, along with the bind group sampling it.
+struct Frame {
+    size: (u32, u32),
+    texture: wgpu::Texture,
+    view: wgpu::TextureView,
+    bind_group: wgpu::BindGroup,
+}
+
+pub struct PostProcessing {
+    source: String,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniforms: wgpu::Buffer,
+    frame: Option<Frame>,
+    #[cfg(not(target_arch = "wasm32"))]
+    started: std::time::Instant,
+}
+
+/// Validate a custom shader, returning it along with the preamble.
+///
+/// The preamble goes after the source, WGSL doesn't mind the order of
+/// declarations and errors keep the lines of the file.
+pub fn compile(source: &str) -> Result<String, String> {
+    let full_source = format!("{source}\n{PREAMBLE}");
+    let module = naga::front::wgsl::parse_str(&full_source)
+        .map_err(|err| err.emit_to_string(&full_source))?;
+
+    if !module.entry_points.iter().any(|entry_point| {
+        entry_point.name == ENTRY_POINT
+            && entry_point.stage == naga::ShaderStage::Fragment
+    }) {
+        return Err(format!("missing @fragment fn {ENTRY_POINT}"));
+    }
+
+    // The pipeline layout only has the frame and its uniforms.
+    let has_other_bindings = module
+        .global_variables
+        .iter()
+        .filter_map(|(_, variable)| variable.binding.as_ref())
+        .any(|binding| binding.group != 0 || binding.binding > 2);
+    if has_other_bindings {
+        return Err(String::from(
+            "only the bindings of the frame and the uniforms are available",
+        ));
+    }
+
+    naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::empty(),
+    )
+    .validate(&module)
+    .map_err(|err| err.emit_to_string(&full_source))?;
+
+    Ok(full_source)
+}
+
+impl PostProcessing {
+    pub fn new(context: &Context, source: &str) -> Result<PostProcessing, String> {
+        let full_source = compile(source)?;
+        let device = &context.device;
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("post_processing::shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Owned(full_source)),
+        });
+
+        let bind_group_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("post_processing::bind_group_layout"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(
+                                mem::size_of::<Uniforms>() as wgpu::BufferAddress,
+                            ),
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Texture {
+                            sample_type: wgpu::TextureSampleType::Float {
+                                filterable: true,
+                            },
+                            view_dimension: wgpu::TextureViewDimension::D2,
+                            multisampled: false,
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 2,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                ],
+            });
+
+        let pipeline_layout =
+            device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                label: None,
+                bind_group_layouts: &[&bind_group_layout],
+                push_constant_ranges: &[],
+            });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("post_processing::pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: ENTRY_POINT,
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: context.format,
+                    blend: None,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("post_processing::sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let uniforms = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("post_processing::uniforms"),
+            size: mem::size_of::<Uniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        Ok(PostProcessing {
+            source: source.to_owned(),
+            pipeline,
+            bind_group_layout,
+            sampler,
+            uniforms,
+            frame: None,
+            #[cfg(not(target_arch = "wasm32"))]
+            started: std::time::Instant::now(),
+        })
+    }
+
+    #[inline]
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// Texture to draw the frame into, created again when the surface
+    /// changes size.
+    pub fn frame(&mut self, context: &Context) -> &wgpu::TextureView {
+        let size = (context.size.width.max(1), context.size.height.max(1));
+        if self.frame.as_ref().map(|frame| frame.size) != Some(size) {
+            let texture = context.device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("post_processing::frame"),
+                size: wgpu::Extent3d {
+                    width: size.0,
+                    height: size.1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: context.format,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            });
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+            let bind_group =
+                context
+                    .device
+                    .create_bind_group(&wgpu::BindGroupDescriptor {
+                        label: Some("post_processing::bind_group"),
+                        layout: &self.bind_group_layout,
+                        entries: &[
+                            wgpu::BindGroupEntry {
+                                binding: 0,
+                                resource: self.uniforms.as_entire_binding(),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 1,
+                                resource: wgpu::BindingResource::TextureView(&view),
+                            },
+                            wgpu::BindGroupEntry {
+                                binding: 2,
+                                resource: wgpu::BindingResource::Sampler(&self.sampler),
+                            },
+                        ],
+                    });
+
+            self.frame = Some(Frame {
+                size,
+                texture,
+                view,
+                bind_group,
+            });
+        }
+
+        &self.frame.as_ref().unwrap().view
+    }
+
+    /// Texture holding the frame drawn before the shader.
+    #[inline]
+    pub fn frame_texture(&self) -> Option<&wgpu::Texture> {
+        self.frame.as_ref().map(|frame| &frame.texture)
+    }
+
+    /// Paint the frame to `target` through the shader. `cursor` is the cell
+    /// of the cursor in pixels.
+    pub fn render(
+        &mut self,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        context: &mut Context,
+        cursor: [f32; 4],
+        background: wgpu::Color,
+    ) {
+        let frame = match &self.frame {
+            Some(frame) => frame,
+            None => return,
+        };
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let time = self.started.elapsed().as_secs_f32();
+        #[cfg(target_arch = "wasm32")]
+        let time = 0.;
+
+        let uniforms = Uniforms {
+            resolution: [frame.size.0 as f32, frame.size.1 as f32],
+            time,
+            scale: context.scale,
+            cursor,
+            background: [
+                background.r as f32,
+                background.g as f32,
+                background.b as f32,
+                background.a as f32,
+            ],
+        };
+        context
+            .staging_belt
+            .write_buffer(
+                encoder,
+                &self.uniforms,
+                0,
+                wgpu::BufferSize::new(mem::size_of::<Uniforms>() as u64).unwrap(),
+                &context.device,
+            )
+            .copy_from_slice(bytemuck::bytes_of(&uniforms));
+
+        let mut rpass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("post_processing::render"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(background),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+        rpass.set_pipeline(&self.pipeline);
+        rpass.set_bind_group(0, &frame.bind_group, &[]);
+        rpass.draw(0..3, 0..1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shaders_get_the_preamble() {
+        let full_source = compile(PASSTHROUGH).unwrap();
+        assert!(full_source.starts_with(PASSTHROUGH));
+        assert!(full_source.contains("fn vs_main"));
+    }
+
+    #[test]
+    fn errors_point_to_the_lines_of_the_shader() {
+        let source = PASSTHROUGH.replace("input.uv)", "input.uv");
+        let err = compile(&source).unwrap_err();
+        assert!(err.contains(":3:"), "{err}");
+
+        let err = compile("fn main() {}").unwrap_err();
+        assert_eq!(err, "missing @fragment fn fs_main");
+
+        let source = format!("@group(1) @binding(0) var other: sampler;\n{PASSTHROUGH}");
+        assert!(compile(&source).is_err());
+    }
+}
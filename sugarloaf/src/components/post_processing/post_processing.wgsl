@@ -0,0 +1,35 @@
+// Appended to custom shaders, which only define `fs_main`.
+
+struct Uniforms {
+    // Size of the frame in pixels.
+    resolution: vec2<f32>,
+    // Seconds since the shader was loaded.
+    time: f32,
+    // Scale factor of the window.
+    scale: f32,
+    // Cell of the cursor as x, y, width and height in pixels, empty when
+    // it isn't visible.
+    cursor: vec4<f32>,
+    background: vec4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> uniforms: Uniforms;
+@group(0) @binding(1) var frame: texture_2d<f32>;
+@group(0) @binding(2) var frame_sampler: sampler;
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+}
+
+// A triangle covering the whole frame, `uv` goes from the top left (0, 0)
+// to the bottom right (1, 1).
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv * vec2<f32>(2.0, -2.0) + vec2<f32>(-1.0, 1.0), 0.0, 1.0);
+    out.uv = uv;
+    return out;
+}
@@ -0,0 +1,627 @@
+// Box drawing, block elements, braille and Powerline separators are meant
+// to connect with the ones of the cells around them. Glyphs of fonts are
+// made for some cell size, with a line height or a scale that doesn't
+// match it they end up showing gaps. Those chars are drawn here instead,
+// as rects computed from the cell itself.
+//
+// Everything is in physical pixels. Rects aren't anti-aliased, a pixel is
+// painted when its center is covered, so strokes of a whole number of
+// pixels always come out with that width wherever the cell starts.
+
+use super::Rect;
+
+/// Area of a cell or of a part of it, in physical pixels.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Cell {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Line {
+    None,
+    Light,
+    Heavy,
+    Double,
+}
+
+// Lines going up, right, down and left of U+2500 to U+257F, "." for the
+// chars drawn some other way.
+const BOX_LINES: [&str; 128] = [
+    "0l0l", "0h0h", "l0l0", "h0h0", ".", ".", ".", ".", //
+    ".", ".", ".", ".", "0ll0", "0hl0", "0lh0", "0hh0", //
+    "00ll", "00lh", "00hl", "00hh", "ll00", "lh00", "hl00", "hh00", //
+    "l00l", "l00h", "h00l", "h00h", "lll0", "lhl0", "hll0", "llh0", //
+    "hlh0", "hhl0", "lhh0", "hhh0", "l0ll", "l0lh", "h0ll", "l0hl", //
+    "h0hl", "h0lh", "l0hh", "h0hh", "0lll", "0llh", "0hll", "0hlh", //
+    "0lhl", "0lhh", "0hhl", "0hhh", "ll0l", "ll0h", "lh0l", "lh0h", //
+    "hl0l", "hl0h", "hh0l", "hh0h", "llll", "lllh", "lhll", "lhlh", //
+    "hlll", "llhl", "hlhl", "hllh", "hhll", "llhh", "lhhl", "hhlh", //
+    "lhhh", "hlhh", "hhhl", "hhhh", ".", ".", ".", ".", //
+    "0d0d", "d0d0", "0dl0", "0ld0", "0dd0", "00ld", "00dl", "00dd", //
+    "ld00", "dl00", "dd00", "l00d", "d00l", "d00d", "ldl0", "dld0", //
+    "ddd0", "l0ld", "d0dl", "d0dd", "0dld", "0ldl", "0ddd", "ld0d", //
+    "dl0l", "dd0d", "ldld", "dldl", "dddd", ".", ".", ".", //
+    ".", ".", ".", ".", "000l", "l000", "0l00", "00l0", //
+    "000h", "h000", "0h00", "00h0", "0h0l", "l0h0", "0l0h", "h0l0", //
+];
+
+/// Whether `content` is drawn by [`rects`] rather than taken from a font.
+#[inline]
+pub fn is_builtin(content: char) -> bool {
+    matches!(
+        content,
+        '\u{2500}'..='\u{259f}' | '\u{2800}'..='\u{28ff}' | '\u{e0b0}'..='\u{e0bf}'
+    )
+}
+
+/// Rects drawing `content` on `cell`, empty if it isn't builtin. `scale`
+/// is the one of the rect brush.
+pub fn rects(content: char, cell: Cell, color: [f32; 4], scale: f32) -> Vec<Rect> {
+    let color = [color[0], color[1], color[2], color[3] * alpha(content)];
+    boxes(content, cell)
+        .into_iter()
+        .map(|area| Rect {
+            // The rect pipeline grows rects by a pixel so backgrounds
+            // overlap, these have to take the exact area.
+            position: [(area.x + 0.5) / scale, (area.y + 0.5) / scale],
+            color,
+            size: [(area.width * 2. - 1.) / scale, (area.height - 1.) / scale],
+        })
+        .collect()
+}
+
+/// Shades are the full block, translucent.
+#[inline]
+fn alpha(content: char) -> f32 {
+    match content {
+        '\u{2591}' => 0.25,
+        '\u{2592}' => 0.5,
+        '\u{2593}' => 0.75,
+        _ => 1.,
+    }
+}
+
+fn boxes(content: char, cell: Cell) -> Vec<Cell> {
+    let mut boxes = vec![];
+    let stroke = (cell.width / 8.).round().max(1.);
+
+    match content {
+        '\u{2500}'..='\u{257f}' => {
+            let index = content as usize - 0x2500;
+            match content {
+                '\u{2504}'..='\u{250b}' | '\u{254c}'..='\u{254f}' => {
+                    let count = match index {
+                        0x04..=0x07 => 3,
+                        0x08..=0x0b => 4,
+                        _ => 2,
+                    };
+                    let heavy = index % 2 == 1;
+                    let vertical = index % 4 >= 2;
+                    dashes(cell, count, heavy, vertical, stroke, &mut boxes);
+                }
+                '\u{256d}'..='\u{2570}' => {
+                    let (flip_x, flip_y) = match content {
+                        '\u{256d}' => (false, false),
+                        '\u{256e}' => (true, false),
+                        '\u{256f}' => (true, true),
+                        _ => (false, true),
+                    };
+                    arc(cell, flip_x, flip_y, stroke, &mut boxes);
+                }
+                '\u{2571}'..='\u{2573}' => {
+                    if content != '\u{2572}' {
+                        diagonal(cell, true, stroke, &mut boxes);
+                    }
+                    if content != '\u{2571}' {
+                        diagonal(cell, false, stroke, &mut boxes);
+                    }
+                }
+                _ => {
+                    let mut arms = [Line::None; 4];
+                    for (arm, line) in arms.iter_mut().zip(BOX_LINES[index].chars()) {
+                        *arm = match line {
+                            'l' => Line::Light,
+                            'h' => Line::Heavy,
+                            'd' => Line::Double,
+                            _ => Line::None,
+                        };
+                    }
+                    lines(cell, arms, stroke, &mut boxes);
+                }
+            }
+        }
+        '\u{2580}'..='\u{259f}' => block(cell, content, &mut boxes),
+        '\u{2800}'..='\u{28ff}' => braille(cell, content as u32 - 0x2800, &mut boxes),
+        '\u{e0b0}'..='\u{e0bf}' => powerline(cell, content, stroke, &mut boxes),
+        _ => {}
+    }
+
+    boxes
+}
+
+/// Part of the cell between fractions of its width and height.
+#[inline]
+fn part(cell: Cell, left: f32, top: f32, right: f32, bottom: f32) -> Cell {
+    Cell {
+        x: cell.x + cell.width * left,
+        y: cell.y + cell.height * top,
+        width: cell.width * (right - left),
+        height: cell.height * (bottom - top),
+    }
+}
+
+#[inline]
+fn area(x0: f32, y0: f32, x1: f32, y1: f32) -> Cell {
+    Cell {
+        x: x0,
+        y: y0,
+        width: x1 - x0,
+        height: y1 - y0,
+    }
+}
+
+/// Width of a line across its length.
+#[inline]
+fn thickness(line: Line, stroke: f32) -> f32 {
+    match line {
+        Line::None => 0.,
+        Line::Light => stroke,
+        Line::Heavy => stroke * 2.,
+        Line::Double => stroke * 3.,
+    }
+}
+
+/// Lines of up, right, down and left going from the edges of the cell to
+/// its center, where they join.
+fn lines(cell: Cell, arms: [Line; 4], stroke: f32, boxes: &mut Vec<Cell>) {
+    let center_x = cell.x + cell.width / 2.;
+    let center_y = cell.y + cell.height / 2.;
+
+    for (index, line) in arms.iter().enumerate() {
+        if *line == Line::None {
+            continue;
+        }
+
+        let opposite = arms[(index + 2) % 4];
+        // Arms across this one, the one on the negative side first.
+        let sides = if index % 2 == 0 {
+            [arms[3], arms[1]]
+        } else {
+            [arms[0], arms[2]]
+        };
+
+        // Each stroke as its offset across the arm and how far past the
+        // center it goes, negative if it stops before it.
+        let mut strokes = vec![];
+        if *line == Line::Double {
+            for (side, offset) in [(0, -stroke), (1, stroke)] {
+                let past = if sides[side] != Line::None {
+                    // Stops at the stroke of that side.
+                    -stroke / 2.
+                } else if opposite != Line::None {
+                    0.
+                } else {
+                    // A corner, reaching the far edge of the other side.
+                    thickness(sides[1 - side], stroke) / 2.
+                };
+                strokes.push((offset, stroke, past));
+            }
+        } else {
+            let past = if opposite != Line::None {
+                0.
+            } else if sides.contains(&Line::Double) {
+                if sides[0] != Line::None && sides[1] != Line::None {
+                    // Stops at the near stroke of the double line.
+                    -stroke / 2.
+                } else {
+                    thickness(Line::Double, stroke) / 2.
+                }
+            } else {
+                thickness(sides[0], stroke).max(thickness(sides[1], stroke)) / 2.
+            };
+            strokes.push((0., thickness(*line, stroke), past));
+        }
+
+        for (offset, width, past) in strokes {
+            let (low, high) = (offset - width / 2., offset + width / 2.);
+            boxes.push(match index {
+                0 => area(center_x + low, cell.y, center_x + high, center_y + past),
+                1 => area(
+                    center_x - past,
+                    center_y + low,
+                    cell.x + cell.width,
+                    center_y + high,
+                ),
+                2 => area(
+                    center_x + low,
+                    center_y - past,
+                    center_x + high,
+                    cell.y + cell.height,
+                ),
+                _ => area(cell.x, center_y + low, center_x + past, center_y + high),
+            });
+        }
+    }
+}
+
+/// Light or heavy line broken in `count` dashes, spaced the same across
+/// cells.
+fn dashes(
+    cell: Cell,
+    count: usize,
+    heavy: bool,
+    vertical: bool,
+    stroke: f32,
+    boxes: &mut Vec<Cell>,
+) {
+    let width = if heavy { stroke * 2. } else { stroke };
+    let length = if vertical { cell.height } else { cell.width } / count as f32;
+    // At least a pixel, or dashes of cells next to each other could merge.
+    let gap = (length / 4.).max(1.);
+
+    for dash in 0..count {
+        let start = dash as f32 * length;
+        let end = start + length - gap;
+        boxes.push(if vertical {
+            let x = cell.x + (cell.width - width) / 2.;
+            area(x, cell.y + start, x + width, cell.y + end)
+        } else {
+            let y = cell.y + (cell.height - width) / 2.;
+            area(cell.x + start, y, cell.x + end, y + width)
+        });
+    }
+}
+
+/// One rect per row of pixels of the cell, `spans` takes the distance of
+/// the row from the top of the cell and returns what of it is painted,
+/// as distances from the left.
+fn rows<F>(cell: Cell, spans: F, boxes: &mut Vec<Cell>)
+where
+    F: Fn(f32) -> [(f32, f32); 2],
+{
+    let first = (cell.y - 0.5).ceil() as i32;
+    let last = (cell.y + cell.height - 0.5).ceil() as i32;
+    for row in first..last {
+        let row = row as f32;
+        for (left, right) in spans(row + 0.5 - cell.y) {
+            let (left, right) = (left.max(0.), right.min(cell.width));
+            if right > left {
+                boxes.push(area(cell.x + left, row, cell.x + right, row + 1.));
+            }
+        }
+    }
+}
+
+/// Same as [`rows`], mirroring the shape horizontally and vertically.
+fn mirrored_rows<F>(
+    cell: Cell,
+    flip_x: bool,
+    flip_y: bool,
+    spans: F,
+    boxes: &mut Vec<Cell>,
+) where
+    F: Fn(f32) -> [(f32, f32); 2],
+{
+    let width = cell.width;
+    let height = cell.height;
+    rows(
+        cell,
+        |y| {
+            let spans = spans(if flip_y { height - y } else { y });
+            if flip_x {
+                spans.map(|(left, right)| (width - right, width - left))
+            } else {
+                spans
+            }
+        },
+        boxes,
+    );
+}
+
+const EMPTY: (f32, f32) = (0., 0.);
+
+/// Quarter of a circle joining a line coming from the bottom and one
+/// coming from the right, as in ╭.
+fn arc(cell: Cell, flip_x: bool, flip_y: bool, stroke: f32, boxes: &mut Vec<Cell>) {
+    let radius = cell.width.min(cell.height) / 2.;
+    let center_x = cell.width / 2. + radius;
+    let center_y = cell.height / 2. + radius;
+    let outer = radius + stroke / 2.;
+    let inner = radius - stroke / 2.;
+
+    mirrored_rows(
+        cell,
+        flip_x,
+        flip_y,
+        |y| {
+            let dy = center_y - y;
+            if dy < 0. {
+                // The line going down from the arc.
+                let x = cell.width / 2.;
+                [(x - stroke / 2., x + stroke / 2.), EMPTY]
+            } else if dy <= outer {
+                let right = if dy < inner {
+                    center_x - (inner * inner - dy * dy).sqrt()
+                } else {
+                    center_x
+                };
+                // Cells wider than tall still get their line to the right.
+                let line = if (y - cell.height / 2.).abs() < stroke / 2. {
+                    (center_x, cell.width)
+                } else {
+                    EMPTY
+                };
+                [(center_x - (outer * outer - dy * dy).sqrt(), right), line]
+            } else {
+                [EMPTY; 2]
+            }
+        },
+        boxes,
+    );
+}
+
+/// Line between opposite corners of the cell, from the top right when
+/// `rising` as in ╱ or from the top left as in ╲.
+fn diagonal(cell: Cell, rising: bool, stroke: f32, boxes: &mut Vec<Cell>) {
+    let (width, height) = (cell.width, cell.height);
+    // Width of the line along a row of pixels.
+    let span = stroke * (width * width + height * height).sqrt() / height;
+
+    mirrored_rows(
+        cell,
+        !rising,
+        false,
+        |y| {
+            let x = width * (1. - y / height);
+            [(x - span / 2., x + span / 2.), EMPTY]
+        },
+        boxes,
+    );
+}
+
+fn block(cell: Cell, content: char, boxes: &mut Vec<Cell>) {
+    const EIGHTH: f32 = 1. / 8.;
+
+    let mut push = |left, top, right, bottom| {
+        boxes.push(part(cell, left, top, right, bottom));
+    };
+
+    match content {
+        '\u{2580}' => push(0., 0., 1., 0.5),
+        '\u{2581}'..='\u{2588}' => {
+            let eighths = (content as u32 - 0x2580) as f32;
+            push(0., 1. - eighths * EIGHTH, 1., 1.);
+        }
+        '\u{2589}'..='\u{258f}' => {
+            let eighths = (0x2590 - content as u32) as f32;
+            push(0., 0., eighths * EIGHTH, 1.);
+        }
+        '\u{2590}' => push(0.5, 0., 1., 1.),
+        '\u{2591}'..='\u{2593}' => push(0., 0., 1., 1.),
+        '\u{2594}' => push(0., 0., 1., EIGHTH),
+        '\u{2595}' => push(1. - EIGHTH, 0., 1., 1.),
+        _ => {
+            // Quadrants, upper left, upper right, lower left and lower right.
+            let quadrants: u8 = match content {
+                '\u{2596}' => 0b0010,
+                '\u{2597}' => 0b0001,
+                '\u{2598}' => 0b1000,
+                '\u{2599}' => 0b1011,
+                '\u{259a}' => 0b1001,
+                '\u{259b}' => 0b1110,
+                '\u{259c}' => 0b1101,
+                '\u{259d}' => 0b0100,
+                '\u{259e}' => 0b0110,
+                _ => 0b0111,
+            };
+            for (bit, (left, top)) in [(0., 0.), (0.5, 0.), (0., 0.5), (0.5, 0.5)]
+                .into_iter()
+                .enumerate()
+            {
+                if quadrants & (0b1000 >> bit) != 0 {
+                    push(left, top, left + 0.5, top + 0.5);
+                }
+            }
+        }
+    }
+}
+
+/// Eight dots, two columns of four, the bits of `dots` numbered as in
+/// the Unicode braille patterns.
+fn braille(cell: Cell, dots: u32, boxes: &mut Vec<Cell>) {
+    const DOTS: [(f32, f32); 8] = [
+        (0., 0.),
+        (0., 1.),
+        (0., 2.),
+        (1., 0.),
+        (1., 1.),
+        (1., 2.),
+        (0., 3.),
+        (1., 3.),
+    ];
+
+    let column = cell.width / 2.;
+    let row = cell.height / 4.;
+    let size = (column.min(row) / 2.).round().max(1.);
+    for (bit, (x, y)) in DOTS.into_iter().enumerate() {
+        if dots & (1 << bit) != 0 {
+            let left = cell.x + x * column + (column - size) / 2.;
+            let top = cell.y + y * row + (row - size) / 2.;
+            boxes.push(area(left, top, left + size, top + size));
+        }
+    }
+}
+
+/// Separators of Powerline, solid and thin triangles, half circles and
+/// diagonals.
+fn powerline(cell: Cell, content: char, stroke: f32, boxes: &mut Vec<Cell>) {
+    let (width, height) = (cell.width, cell.height);
+    // Left pointing separators mirror the right pointing ones.
+    let flip_x = matches!(content, '\u{e0b2}' | '\u{e0b3}' | '\u{e0b6}' | '\u{e0b7}');
+
+    match content {
+        '\u{e0b0}' | '\u{e0b2}' => mirrored_rows(
+            cell,
+            flip_x,
+            false,
+            |y| {
+                let tip = 1. - (2. * y / height - 1.).abs();
+                [(0., width * tip), EMPTY]
+            },
+            boxes,
+        ),
+        '\u{e0b1}' | '\u{e0b3}' => {
+            let half = height / 2.;
+            let span = stroke * (width * width + half * half).sqrt() / half;
+            mirrored_rows(
+                cell,
+                flip_x,
+                false,
+                |y| {
+                    let x = width * (1. - (2. * y / height - 1.).abs());
+                    [(x - span / 2., x + span / 2.), EMPTY]
+                },
+                boxes,
+            );
+        }
+        '\u{e0b4}' | '\u{e0b6}' => mirrored_rows(
+            cell,
+            flip_x,
+            false,
+            |y| {
+                let v = 2. * y / height - 1.;
+                [(0., width * (1. - v * v).max(0.).sqrt()), EMPTY]
+            },
+            boxes,
+        ),
+        '\u{e0b5}' | '\u{e0b7}' => {
+            let half = height / 2.;
+            mirrored_rows(
+                cell,
+                flip_x,
+                false,
+                |y| {
+                    let dy = (y - half).abs();
+                    let outer = width * (1. - (dy / half).powi(2)).max(0.).sqrt();
+                    let inner_half = half - stroke;
+                    let inner = if dy < inner_half {
+                        (width - stroke) * (1. - (dy / inner_half).powi(2)).sqrt()
+                    } else {
+                        0.
+                    };
+                    [(inner, outer), EMPTY]
+                },
+                boxes,
+            );
+        }
+        '\u{e0b8}' | '\u{e0ba}' | '\u{e0bc}' | '\u{e0be}' => mirrored_rows(
+            cell,
+            matches!(content, '\u{e0ba}' | '\u{e0be}'),
+            matches!(content, '\u{e0bc}' | '\u{e0be}'),
+            |y| [(0., width * y / height), EMPTY],
+            boxes,
+        ),
+        '\u{e0bb}' | '\u{e0bd}' => diagonal(cell, true, stroke, boxes),
+        _ => diagonal(cell, false, stroke, boxes),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CELL: Cell = Cell {
+        x: 10.,
+        y: 20.5,
+        width: 8.,
+        height: 17.3,
+    };
+
+    fn covers(boxes: &[Cell], x: f32, y: f32) -> bool {
+        boxes.iter().any(|area| {
+            x >= area.x
+                && x < area.x + area.width
+                && y >= area.y
+                && y < area.y + area.height
+        })
+    }
+
+    #[test]
+    fn lines_reach_the_edges_of_the_cell() {
+        let boxes = boxes('┼', CELL);
+        let (center_x, center_y) = (CELL.x + 4., CELL.y + CELL.height / 2.);
+
+        assert!(covers(&boxes, center_x, CELL.y));
+        assert!(covers(&boxes, center_x, CELL.y + CELL.height - 0.01));
+        assert!(covers(&boxes, CELL.x, center_y));
+        assert!(covers(&boxes, CELL.x + CELL.width - 0.01, center_y));
+        assert!(!covers(&boxes, CELL.x, CELL.y));
+
+        // Rows of a column share the same line.
+        let below = Cell {
+            y: CELL.y + CELL.height,
+            ..CELL
+        };
+        let line = boxes.iter().find(|area| area.y == CELL.y).unwrap();
+        let next = self::boxes('│', below);
+        assert_eq!(next[0].x, line.x);
+        assert_eq!(next[0].width, line.width);
+    }
+
+    #[test]
+    fn corners_join_their_lines() {
+        let boxes = boxes('┏', CELL);
+        let vertical = boxes.iter().find(|area| area.height > area.width).unwrap();
+        let horizontal = boxes.iter().find(|area| area.width > area.height).unwrap();
+        assert_eq!(vertical.y, horizontal.y);
+        assert_eq!(vertical.x, horizontal.x);
+
+        // Outer and inner corner of the double lines.
+        let boxes = self::boxes('╔', CELL);
+        assert_eq!(boxes.len(), 4);
+        let top = boxes.iter().map(|area| area.y).fold(f32::MAX, f32::min);
+        let left = boxes.iter().map(|area| area.x).fold(f32::MAX, f32::min);
+        assert!(covers(&boxes, left, top));
+    }
+
+    #[test]
+    fn blocks_and_braille_fill_their_part() {
+        let boxes = boxes('▄', CELL);
+        assert_eq!(boxes, vec![part(CELL, 0., 0.5, 1., 1.)]);
+
+        let boxes = self::boxes('▚', CELL);
+        assert_eq!(
+            boxes,
+            vec![part(CELL, 0., 0., 0.5, 0.5), part(CELL, 0.5, 0.5, 1., 1.)]
+        );
+
+        assert!(self::boxes('\u{2800}', CELL).is_empty());
+        assert_eq!(self::boxes('⣿', CELL).len(), 8);
+        assert_eq!(alpha('▒'), 0.5);
+    }
+
+    #[test]
+    fn shapes_take_one_rect_per_row() {
+        let boxes = boxes('\u{e0b0}', CELL);
+        assert_eq!(boxes.len(), 17);
+        assert!(boxes.iter().all(|area| area.height == 1.));
+        assert!(boxes.iter().all(|area| area.x == CELL.x));
+
+        let widest = boxes.iter().map(|area| area.width).fold(0., f32::max);
+        assert!(widest > CELL.width * 0.9);
+    }
+
+    #[test]
+    fn only_builtin_chars_are_drawn() {
+        assert!(is_builtin('─'));
+        assert!(is_builtin('█'));
+        assert!(is_builtin('⠿'));
+        assert!(is_builtin('\u{e0b0}'));
+        assert!(!is_builtin('a'));
+        assert!(!is_builtin('\u{e0a0}'));
+        assert!(rects('a', CELL, [1.; 4], 1.).is_empty());
+    }
+}
@@ -1,3 +1,5 @@
+pub mod builtin;
+
 use crate::components::core::orthographic_projection;
 use crate::context::Context;
 use crate::Renderable;
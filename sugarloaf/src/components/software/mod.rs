@@ -0,0 +1,191 @@
+// Software renderer, drawing frames into memory with tiny-skia where the
+// GPU is not available, like in CI. Shapes follow the shaders, but custom
+// shaders and background images are left out.
+
+use crate::components::rect::Rect;
+use crate::font::color::ColorImage;
+use crate::tools::screenshot::Screenshot;
+use tiny_skia::{
+    Color, IntSize, Paint, Pixmap, PixmapPaint, PremultipliedColorU8, Transform,
+};
+
+pub struct Canvas {
+    pixmap: Pixmap,
+    pub scale: f32,
+}
+
+impl Canvas {
+    pub fn new(width: u32, height: u32, scale: f32) -> Canvas {
+        Canvas {
+            pixmap: create_pixmap(width, height),
+            scale,
+        }
+    }
+
+    #[inline]
+    pub fn size(&self) -> (u32, u32) {
+        (self.pixmap.width(), self.pixmap.height())
+    }
+
+    pub fn resize(&mut self, width: u32, height: u32) {
+        if self.size() != (width, height) {
+            self.pixmap = create_pixmap(width, height);
+        }
+    }
+
+    pub fn clear(&mut self, color: wgpu::Color) {
+        self.pixmap.fill(to_color([
+            color.r as f32,
+            color.g as f32,
+            color.b as f32,
+            color.a as f32,
+        ]));
+    }
+
+    /// Rects are in logical pixels, with the size and the half pixel of
+    /// overlap rect.wgsl gives them.
+    pub fn fill_rects(&mut self, rects: &[Rect]) {
+        let mut paint = Paint {
+            anti_alias: false,
+            ..Paint::default()
+        };
+
+        for rect in rects {
+            let x = rect.position[0] * self.scale - 0.5;
+            let y = rect.position[1] * self.scale - 0.5;
+            let width = (rect.size[0] * self.scale + 1.) / 2.;
+            let height = rect.size[1] * self.scale + 1.;
+
+            if let Some(bounds) = tiny_skia::Rect::from_xywh(x, y, width, height) {
+                paint.set_color(to_color(rect.color));
+                self.pixmap
+                    .fill_rect(bounds, &paint, Transform::identity(), None);
+            }
+        }
+    }
+
+    /// Blend `color` over the pixels with the given coverage, a row after
+    /// the other.
+    pub fn draw_coverage(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        coverage: &[u8],
+        color: [f32; 4],
+    ) {
+        let color = to_color(color);
+        let data = coverage
+            .iter()
+            .flat_map(|alpha| {
+                let alpha = *alpha as f32 / 255.;
+                [
+                    color.red() * color.alpha() * alpha,
+                    color.green() * color.alpha() * alpha,
+                    color.blue() * color.alpha() * alpha,
+                    color.alpha() * alpha,
+                ]
+                .map(|channel| (channel * 255.).round() as u8)
+            })
+            .collect();
+
+        self.draw_premultiplied(x, y, width, height, data);
+    }
+
+    /// Blend a straight alpha image over the frame.
+    pub fn draw_image(&mut self, x: i32, y: i32, image: &ColorImage) {
+        let data = image
+            .data
+            .chunks_exact(4)
+            .flat_map(|pixel| {
+                let alpha = pixel[3] as u16;
+                [
+                    (pixel[0] as u16 * alpha / 255) as u8,
+                    (pixel[1] as u16 * alpha / 255) as u8,
+                    (pixel[2] as u16 * alpha / 255) as u8,
+                    pixel[3],
+                ]
+            })
+            .collect();
+
+        self.draw_premultiplied(x, y, image.width, image.height, data);
+    }
+
+    fn draw_premultiplied(
+        &mut self,
+        x: i32,
+        y: i32,
+        width: u32,
+        height: u32,
+        data: Vec<u8>,
+    ) {
+        let source =
+            IntSize::from_wh(width, height).and_then(|size| Pixmap::from_vec(data, size));
+        if let Some(source) = source {
+            self.pixmap.draw_pixmap(
+                x,
+                y,
+                source.as_ref(),
+                &PixmapPaint::default(),
+                Transform::identity(),
+                None,
+            );
+        }
+    }
+
+    /// The frame as straight alpha RGBA.
+    pub fn frame(&self) -> Screenshot {
+        let data = self
+            .pixmap
+            .pixels()
+            .iter()
+            .flat_map(|pixel: &PremultipliedColorU8| {
+                let pixel = pixel.demultiply();
+                [pixel.red(), pixel.green(), pixel.blue(), pixel.alpha()]
+            })
+            .collect();
+
+        Screenshot {
+            width: self.pixmap.width(),
+            height: self.pixmap.height(),
+            data,
+        }
+    }
+}
+
+#[inline]
+fn create_pixmap(width: u32, height: u32) -> Pixmap {
+    Pixmap::new(width.max(1), height.max(1)).unwrap()
+}
+
+/// Colors are stored as they are, like on a surface without sRGB encoding.
+#[inline]
+fn to_color(color: [f32; 4]) -> Color {
+    let [r, g, b, a] = color.map(|channel| channel.clamp(0., 1.));
+    Color::from_rgba(r, g, b, a).unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rects_and_coverage_blend_over_the_background() {
+        let mut canvas = Canvas::new(4, 2, 1.);
+        canvas.clear(wgpu::Color::BLACK);
+        canvas.fill_rects(&[Rect {
+            position: [0.5, 0.5],
+            color: [1., 0., 0., 1.],
+            size: [3., 1.],
+        }]);
+        canvas.draw_coverage(2, 1, 2, 1, &[255, 0], [0., 0., 1., 1.]);
+
+        let frame = canvas.frame();
+        let pixels: Vec<&[u8]> = frame.data.chunks_exact(4).collect();
+        assert_eq!(pixels[0], [255, 0, 0, 255]);
+        assert_eq!(pixels[3], [0, 0, 0, 255]);
+        assert_eq!(pixels[6], [0, 0, 255, 255]);
+        assert_eq!(pixels[7], [0, 0, 0, 255]);
+    }
+}
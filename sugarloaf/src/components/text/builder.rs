@@ -134,6 +134,13 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrushBuilder<(), F, H> {
             self.inner,
         )
     }
+
+    /// Builds a `GlyphBrush` drawing into a
+    /// [`Canvas`](crate::components::software::Canvas) rather than a GPU.
+    #[cfg(feature = "software")]
+    pub fn build_software(self) -> GlyphBrush<(), F, H> {
+        GlyphBrush::<(), F, H>::new_software(self.inner)
+    }
 }
 
 impl<F: Font + Sync, H: BuildHasher> GlyphBrushBuilder<wgpu::DepthStencilState, F, H> {
@@ -0,0 +1,411 @@
+// Color glyphs (emoji) can't go through glyph_brush, its cache only keeps
+// coverage. They are rasterized by `crate::font::color` into an RGBA atlas
+// and drawn on top of the text of the same frame.
+
+use crate::components::layer::atlas::{self, Atlas};
+use crate::components::text::pipeline::BLEND;
+use crate::components::text::Region;
+use crate::font::color::{ColorFace, ColorImage};
+
+use bytemuck::{Pod, Zeroable};
+use core::num::NonZeroU64;
+use glyph_brush::ab_glyph::{Font, Rect};
+use glyph_brush::FontId;
+use std::borrow::Cow;
+use std::collections::hash_map::Entry;
+use std::collections::HashMap;
+use std::mem;
+
+/// Rasterized glyphs kept before the atlas is emptied.
+pub const CACHE_CAPACITY: usize = 1024;
+
+const TRANSFORM_SIZE: u64 = mem::size_of::<[f32; 16]>() as u64;
+
+/// A color glyph, drawn centered in `bounds`, which are usually the cells it
+/// takes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ColorGlyph {
+    pub font_id: FontId,
+    pub glyph_id: u16,
+    pub bounds: Rect,
+}
+
+/// Size a glyph is rasterized at, the image is the same for every glyph
+/// with the same key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GlyphKey {
+    pub font_id: usize,
+    pub glyph_id: u16,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl From<&ColorGlyph> for GlyphKey {
+    #[inline]
+    fn from(glyph: &ColorGlyph) -> GlyphKey {
+        GlyphKey {
+            font_id: glyph.font_id.0,
+            glyph_id: glyph.glyph_id,
+            width: glyph.bounds.width().round() as u32,
+            height: glyph.bounds.height().round() as u32,
+        }
+    }
+}
+
+/// Rasterize the glyph of `key` with its face, if the font has one.
+pub fn rasterize<F: Font>(
+    faces: &HashMap<usize, ColorFace>,
+    fonts: &[F],
+    key: GlyphKey,
+) -> Option<ColorImage> {
+    match (faces.get(&key.font_id), fonts.get(key.font_id)) {
+        (Some(face), Some(font)) => {
+            face.rasterize(font, key.glyph_id, key.width, key.height)
+        }
+        _ => None,
+    }
+}
+
+pub struct ColorPipeline {
+    glyphs: HashMap<GlyphKey, Option<atlas::Entry>>,
+    atlas: Atlas,
+    transform: wgpu::Buffer,
+    current_transform: [f32; 16],
+    constants: wgpu::BindGroup,
+    texture_layout: wgpu::BindGroupLayout,
+    texture: wgpu::BindGroup,
+    texture_version: usize,
+    raw: wgpu::RenderPipeline,
+    instances: wgpu::Buffer,
+    current_instances: usize,
+    supported_instances: usize,
+}
+
+impl ColorPipeline {
+    pub fn new(device: &wgpu::Device, render_format: wgpu::TextureFormat) -> Self {
+        let transform = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("text::ColorPipeline transform"),
+            size: TRANSFORM_SIZE,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            address_mode_w: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            mipmap_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let constant_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("text::ColorPipeline constants"),
+                entries: &[
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 0,
+                        visibility: wgpu::ShaderStages::VERTEX,
+                        ty: wgpu::BindingType::Buffer {
+                            ty: wgpu::BufferBindingType::Uniform,
+                            has_dynamic_offset: false,
+                            min_binding_size: wgpu::BufferSize::new(TRANSFORM_SIZE),
+                        },
+                        count: None,
+                    },
+                    wgpu::BindGroupLayoutEntry {
+                        binding: 1,
+                        visibility: wgpu::ShaderStages::FRAGMENT,
+                        ty: wgpu::BindingType::Sampler(
+                            wgpu::SamplerBindingType::Filtering,
+                        ),
+                        count: None,
+                    },
+                ],
+            });
+
+        let constants = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("text::ColorPipeline constants"),
+            layout: &constant_layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+                        buffer: &transform,
+                        offset: 0,
+                        size: None,
+                    }),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        let texture_layout =
+            device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                label: Some("text::ColorPipeline atlas"),
+                entries: &[wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                }],
+            });
+
+        let atlas = Atlas::new(device);
+        let texture = create_texture(device, &texture_layout, &atlas);
+
+        let layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("text::ColorPipeline layout"),
+            push_constant_ranges: &[],
+            bind_group_layouts: &[&constant_layout, &texture_layout],
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Color glyph shader"),
+            source: wgpu::ShaderSource::Wgsl(Cow::Borrowed(include_str!("color.wgsl"))),
+        });
+
+        let raw = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("text::ColorPipeline"),
+            layout: Some(&layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: mem::size_of::<Instance>() as u64,
+                    step_mode: wgpu::VertexStepMode::Instance,
+                    attributes: &wgpu::vertex_attr_array![
+                        0 => Float32x2,
+                        1 => Float32x2,
+                        2 => Float32x2,
+                        3 => Float32x2,
+                        4 => Sint32,
+                    ],
+                }],
+            },
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleStrip,
+                front_face: wgpu::FrontFace::Cw,
+                strip_index_format: Some(wgpu::IndexFormat::Uint16),
+                ..Default::default()
+            },
+            multisample: wgpu::MultisampleState::default(),
+            depth_stencil: None,
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: render_format,
+                    blend: BLEND,
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+            }),
+            multiview: None,
+        });
+
+        let instances = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("text::ColorPipeline instances"),
+            size: mem::size_of::<Instance>() as u64 * Instance::INITIAL_AMOUNT as u64,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        ColorPipeline {
+            glyphs: HashMap::new(),
+            texture_version: atlas.layer_count(),
+            atlas,
+            transform,
+            current_transform: [0.0; 16],
+            constants,
+            texture_layout,
+            texture,
+            raw,
+            instances,
+            current_instances: 0,
+            supported_instances: Instance::INITIAL_AMOUNT,
+        }
+    }
+
+    /// Draws the glyphs, rasterizing the ones that aren't in the atlas yet.
+    #[allow(clippy::too_many_arguments)]
+    pub fn draw<F: Font>(
+        &mut self,
+        fonts: &[F],
+        faces: &HashMap<usize, ColorFace>,
+        queue: Vec<ColorGlyph>,
+        device: &wgpu::Device,
+        staging_belt: &mut wgpu::util::StagingBelt,
+        encoder: &mut wgpu::CommandEncoder,
+        target: &wgpu::TextureView,
+        transform: [f32; 16],
+        region: Option<Region>,
+    ) {
+        if queue.is_empty() {
+            return;
+        }
+
+        if self.glyphs.len() >= CACHE_CAPACITY {
+            for entry in self.glyphs.values().flatten() {
+                self.atlas.remove(entry);
+            }
+            self.glyphs.clear();
+        }
+
+        let mut instances = Vec::with_capacity(queue.len());
+        for glyph in queue {
+            let key = GlyphKey::from(&glyph);
+
+            if let Entry::Vacant(vacant) = self.glyphs.entry(key) {
+                let entry = rasterize(faces, fonts, key).and_then(|image| {
+                    self.atlas.upload(
+                        device,
+                        encoder,
+                        image.width,
+                        image.height,
+                        &image.data,
+                    )
+                });
+                vacant.insert(entry);
+            }
+
+            if let Some(Some(atlas::Entry::Contiguous(allocation))) =
+                self.glyphs.get(&key)
+            {
+                instances.push(Instance::new(glyph.bounds, allocation));
+            }
+        }
+
+        if instances.is_empty() {
+            return;
+        }
+
+        let texture_version = self.atlas.layer_count();
+        if self.texture_version != texture_version {
+            self.texture = create_texture(device, &self.texture_layout, &self.atlas);
+            self.texture_version = texture_version;
+        }
+
+        if transform != self.current_transform {
+            let mut transform_view = staging_belt.write_buffer(
+                encoder,
+                &self.transform,
+                0,
+                unsafe { NonZeroU64::new_unchecked(16 * 4) },
+                device,
+            );
+            transform_view.copy_from_slice(bytemuck::cast_slice(&transform));
+            self.current_transform = transform;
+        }
+
+        self.upload(device, staging_belt, encoder, &instances);
+
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("text::ColorPipeline render pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view: target,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        });
+
+        render_pass.set_pipeline(&self.raw);
+        render_pass.set_bind_group(0, &self.constants, &[]);
+        render_pass.set_bind_group(1, &self.texture, &[]);
+        render_pass.set_vertex_buffer(0, self.instances.slice(..));
+
+        if let Some(region) = region {
+            render_pass.set_scissor_rect(region.x, region.y, region.width, region.height);
+        }
+
+        render_pass.draw(0..4, 0..self.current_instances as u32);
+    }
+
+    fn upload(
+        &mut self,
+        device: &wgpu::Device,
+        staging_belt: &mut wgpu::util::StagingBelt,
+        encoder: &mut wgpu::CommandEncoder,
+        instances: &[Instance],
+    ) {
+        if instances.len() > self.supported_instances {
+            self.instances = device.create_buffer(&wgpu::BufferDescriptor {
+                label: Some("text::ColorPipeline instances"),
+                size: mem::size_of::<Instance>() as u64 * instances.len() as u64,
+                usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            self.supported_instances = instances.len();
+        }
+
+        let instances_bytes = bytemuck::cast_slice(instances);
+        if let Some(size) = NonZeroU64::new(instances_bytes.len() as u64) {
+            let mut instances_view =
+                staging_belt.write_buffer(encoder, &self.instances, 0, size, device);
+            instances_view.copy_from_slice(instances_bytes);
+        }
+
+        self.current_instances = instances.len();
+    }
+}
+
+fn create_texture(
+    device: &wgpu::Device,
+    layout: &wgpu::BindGroupLayout,
+    atlas: &Atlas,
+) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+        label: Some("text::ColorPipeline atlas"),
+        layout,
+        entries: &[wgpu::BindGroupEntry {
+            binding: 0,
+            resource: wgpu::BindingResource::TextureView(atlas.view()),
+        }],
+    })
+}
+
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Zeroable, Pod)]
+struct Instance {
+    left_top: [f32; 2],
+    right_bottom: [f32; 2],
+    tex_left_top: [f32; 2],
+    tex_right_bottom: [f32; 2],
+    layer: i32,
+}
+
+impl Instance {
+    const INITIAL_AMOUNT: usize = 1_000;
+
+    /// The image is centered in `bounds`.
+    fn new(bounds: Rect, allocation: &atlas::Allocation) -> Instance {
+        let (x, y) = allocation.position();
+        let size = allocation.size();
+        let left = bounds.min.x + (bounds.width() - size.width as f32) / 2.;
+        let top = bounds.min.y + (bounds.height() - size.height as f32) / 2.;
+        let atlas_size = atlas::SIZE as f32;
+
+        Instance {
+            left_top: [left, top],
+            right_bottom: [left + size.width as f32, top + size.height as f32],
+            tex_left_top: [x as f32 / atlas_size, y as f32 / atlas_size],
+            tex_right_bottom: [
+                (x + size.width) as f32 / atlas_size,
+                (y + size.height) as f32 / atlas_size,
+            ],
+            layer: allocation.layer() as i32,
+        }
+    }
+}
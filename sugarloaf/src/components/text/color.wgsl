@@ -0,0 +1,69 @@
+struct Globals {
+    transform: mat4x4<f32>,
+}
+
+@group(0) @binding(0) var<uniform> globals: Globals;
+@group(0) @binding(1) var color_sampler: sampler;
+@group(1) @binding(0) var color_tex: texture_2d_array<f32>;
+
+struct VertexInput {
+    @builtin(vertex_index) vertex_index: u32,
+    @location(0) left_top: vec2<f32>,
+    @location(1) right_bottom: vec2<f32>,
+    @location(2) tex_left_top: vec2<f32>,
+    @location(3) tex_right_bottom: vec2<f32>,
+    @location(4) layer: i32,
+}
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) f_tex_pos: vec2<f32>,
+    @location(1) layer: f32, // this should be an i32, but naga currently reads that as requiring interpolation.
+}
+
+@vertex
+fn vs_main(input: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    var pos: vec2<f32> = vec2<f32>(0.0, 0.0);
+    var left: f32 = input.left_top.x;
+    var right: f32 = input.right_bottom.x;
+    var top: f32 = input.left_top.y;
+    var bottom: f32 = input.right_bottom.y;
+
+    switch (i32(input.vertex_index)) {
+        case 0: {
+            pos = vec2<f32>(left, top);
+            out.f_tex_pos = input.tex_left_top;
+        }
+        case 1: {
+            pos = vec2<f32>(right, top);
+            out.f_tex_pos = vec2<f32>(input.tex_right_bottom.x, input.tex_left_top.y);
+        }
+        case 2: {
+            pos = vec2<f32>(left, bottom);
+            out.f_tex_pos = vec2<f32>(input.tex_left_top.x, input.tex_right_bottom.y);
+        }
+        case 3: {
+            pos = vec2<f32>(right, bottom);
+            out.f_tex_pos = input.tex_right_bottom;
+        }
+        default: {}
+    }
+
+    out.layer = f32(input.layer);
+    out.position = globals.transform * vec4<f32>(pos, 0.0, 1.0);
+
+    return out;
+}
+
+@fragment
+fn fs_main(input: VertexOutput) -> @location(0) vec4<f32> {
+    var color: vec4<f32> = textureSample(color_tex, color_sampler, input.f_tex_pos, i32(input.layer));
+
+    if (color.a <= 0.0) {
+        discard;
+    }
+
+    return color;
+}
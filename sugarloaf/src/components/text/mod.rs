@@ -2,9 +2,13 @@
 // From https://github.com/hecrj/wgpu_glyph
 // #[deny(unused_results)]
 mod builder;
+mod color;
 mod pipeline;
+#[cfg(feature = "software")]
+mod software;
 
 /// A region of the screen.
+#[derive(Clone, Copy)]
 pub struct Region {
     pub x: u32,
     pub y: u32,
@@ -12,9 +16,13 @@ pub struct Region {
     pub height: u32,
 }
 
+use color::ColorPipeline;
 use pipeline::{Instance, Pipeline};
+#[cfg(feature = "software")]
+use software::SoftwarePipeline;
 
 pub use builder::GlyphBrushBuilder;
+pub use color::ColorGlyph;
 pub use glyph_brush::ab_glyph;
 pub use glyph_brush::{
     BuiltInLineBreaker, Extra, FontId, GlyphCruncher, GlyphPositioner, HorizontalAlign,
@@ -25,7 +33,9 @@ pub use glyph_brush::{
 use ab_glyph::{Font, Rect};
 use core::hash::BuildHasher;
 use std::borrow::Cow;
+use std::collections::HashMap;
 
+use crate::font::color::ColorFace;
 use glyph_brush::{BrushAction, BrushError, DefaultSectionHasher};
 
 /// Object allowing glyph drawing, containing cache state. Manages glyph positioning cacheing,
@@ -33,10 +43,26 @@ use glyph_brush::{BrushAction, BrushError, DefaultSectionHasher};
 ///
 /// Build using a [`GlyphBrushBuilder`](struct.GlyphBrushBuilder.html).
 pub struct GlyphBrush<Depth, F = ab_glyph::FontArc, H = DefaultSectionHasher> {
-    pipeline: Pipeline<Depth>,
+    renderer: Renderer<Depth>,
+    color_faces: HashMap<usize, ColorFace>,
+    color_queue: Vec<ColorGlyph>,
     glyph_brush: glyph_brush::GlyphBrush<Instance, Extra, F, H>,
 }
 
+/// Where the glyphs are drawn, the software one can only be built by
+/// [`GlyphBrushBuilder::build_software`].
+enum Renderer<Depth> {
+    Gpu {
+        pipeline: Pipeline<Depth>,
+        color_pipeline: ColorPipeline,
+    },
+    #[cfg(feature = "software")]
+    Software(SoftwarePipeline),
+}
+
+#[cfg(feature = "software")]
+const NOT_GPU: &str = "the glyph brush was built for the software renderer";
+
 impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
     /// Queues a section/layout to be drawn by the next call of
     /// [`draw_queued`](struct.GlyphBrush.html#method.draw_queued). Can be
@@ -72,15 +98,15 @@ impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
     /// Queues pre-positioned glyphs to be processed by the next call of
     /// [`draw_queued`](struct.GlyphBrush.html#method.draw_queued). Can be
     /// called multiple times.
-    // #[inline]
-    // pub fn queue_pre_positioned(
-    //     &mut self,
-    //     glyphs: Vec<SectionGlyph>,
-    //     extra: Vec<Extra>,
-    //     bounds: Rect,
-    // ) {
-    //     self.glyph_brush.queue_pre_positioned(glyphs, extra, bounds)
-    // }
+    #[inline]
+    pub fn queue_pre_positioned(
+        &mut self,
+        glyphs: Vec<SectionGlyph>,
+        extra: Vec<Extra>,
+        bounds: Rect,
+    ) {
+        self.glyph_brush.queue_pre_positioned(glyphs, extra, bounds)
+    }
 
     /// Retains the section in the cache as if it had been used in the last
     /// draw-frame.
@@ -124,6 +150,26 @@ impl<Depth, F: Font, H: BuildHasher> GlyphBrush<Depth, F, H> {
     pub fn add_font(&mut self, font: F) -> FontId {
         self.glyph_brush.add_font(font)
     }
+
+    /// Marks the font as a color one, its glyphs should be queued with
+    /// [`queue_color_glyph`](struct.GlyphBrush.html#method.queue_color_glyph).
+    #[inline]
+    pub fn add_color_font(&mut self, font_id: FontId, face: ColorFace) {
+        self.color_faces.insert(font_id.0, face);
+    }
+
+    #[inline]
+    pub fn is_color_font(&self, font_id: FontId) -> bool {
+        self.color_faces.contains_key(&font_id.0)
+    }
+
+    /// Queues a glyph of a color font to be drawn, after the sections, by
+    /// the next call of
+    /// [`draw_queued`](struct.GlyphBrush.html#method.draw_queued).
+    #[inline]
+    pub fn queue_color_glyph(&mut self, glyph: ColorGlyph) {
+        self.color_queue.push(glyph)
+    }
 }
 
 impl<D, F, H> GlyphBrush<D, F, H>
@@ -136,8 +182,12 @@ where
         device: &wgpu::Device,
         staging_belt: &mut wgpu::util::StagingBelt,
         encoder: &mut wgpu::CommandEncoder,
-    ) {
-        let pipeline = &mut self.pipeline;
+    ) -> Result<(), String> {
+        let pipeline = match &mut self.renderer {
+            Renderer::Gpu { pipeline, .. } => pipeline,
+            #[cfg(feature = "software")]
+            Renderer::Software(_) => return Err(String::from(NOT_GPU)),
+        };
 
         let mut brush_action;
 
@@ -186,10 +236,65 @@ where
 
         match brush_action.unwrap() {
             BrushAction::Draw(verts) => {
-                self.pipeline.upload(device, staging_belt, encoder, &verts);
+                pipeline.upload(device, staging_belt, encoder, &verts);
             }
             BrushAction::ReDraw => {}
         };
+
+        Ok(())
+    }
+
+    /// Draws all queued sections and color glyphs into `canvas`, same as
+    /// `draw_queued` does for the GPU.
+    #[cfg(feature = "software")]
+    pub fn draw_queued_software(
+        &mut self,
+        canvas: &mut crate::components::software::Canvas,
+    ) -> Result<(), String> {
+        let pipeline = match &mut self.renderer {
+            Renderer::Software(pipeline) => pipeline,
+            Renderer::Gpu { .. } => {
+                return Err(String::from(
+                    "the glyph brush was built for the GPU renderer",
+                ))
+            }
+        };
+
+        let mut brush_action;
+        loop {
+            brush_action = self.glyph_brush.process_queued(
+                |rect, tex_data| {
+                    pipeline.update_cache(
+                        [rect.min[0], rect.min[1]],
+                        [rect.width(), rect.height()],
+                        tex_data,
+                    );
+                },
+                Instance::from_vertex,
+            );
+
+            match brush_action {
+                Ok(_) => break,
+                Err(BrushError::TextureTooSmall { suggested }) => {
+                    pipeline.increase_cache_size(suggested.0, suggested.1);
+                    self.glyph_brush.resize_texture(suggested.0, suggested.1);
+                }
+            }
+        }
+
+        if let Ok(BrushAction::Draw(verts)) = brush_action {
+            pipeline.upload(verts);
+        }
+
+        pipeline.draw(canvas);
+        pipeline.draw_color(
+            self.glyph_brush.fonts(),
+            &self.color_faces,
+            std::mem::take(&mut self.color_queue),
+            canvas,
+        );
+
+        Ok(())
     }
 }
 
@@ -204,14 +309,36 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
         let glyph_brush = raw_builder.build();
         let (cache_width, cache_height) = glyph_brush.texture_dimensions();
         GlyphBrush {
-            pipeline: Pipeline::<()>::new(
-                device,
-                filter_mode,
-                multisample,
-                render_format,
+            renderer: Renderer::Gpu {
+                pipeline: Pipeline::<()>::new(
+                    device,
+                    filter_mode,
+                    multisample,
+                    render_format,
+                    cache_width,
+                    cache_height,
+                ),
+                color_pipeline: ColorPipeline::new(device, render_format),
+            },
+            color_faces: HashMap::new(),
+            color_queue: vec![],
+            glyph_brush,
+        }
+    }
+
+    #[cfg(feature = "software")]
+    fn new_software(raw_builder: glyph_brush::GlyphBrushBuilder<F, H>) -> Self {
+        // Glyphs close enough share their rasterization, which one is kept
+        // varies between runs. Frames have to be the same every time.
+        let glyph_brush = raw_builder.draw_cache_position_tolerance(0.).build();
+        let (cache_width, cache_height) = glyph_brush.texture_dimensions();
+        GlyphBrush {
+            renderer: Renderer::Software(SoftwarePipeline::new(
                 cache_width,
                 cache_height,
-            ),
+            )),
+            color_faces: HashMap::new(),
+            color_queue: vec![],
             glyph_brush,
         }
     }
@@ -264,9 +391,20 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
         target: &wgpu::TextureView,
         transform: [f32; 16],
     ) -> Result<(), String> {
-        self.process_queued(device, staging_belt, encoder);
-        self.pipeline
-            .draw(device, staging_belt, encoder, target, transform, None);
+        self.process_queued(device, staging_belt, encoder)?;
+        let (pipeline, color_pipeline) = gpu_pipelines(&mut self.renderer)?;
+        pipeline.draw(device, staging_belt, encoder, target, transform, None);
+        color_pipeline.draw(
+            self.glyph_brush.fonts(),
+            &self.color_faces,
+            std::mem::take(&mut self.color_queue),
+            device,
+            staging_belt,
+            encoder,
+            target,
+            transform,
+            None,
+        );
 
         Ok(())
     }
@@ -292,8 +430,20 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<(), F, H> {
         transform: [f32; 16],
         region: Region,
     ) -> Result<(), String> {
-        self.process_queued(device, staging_belt, encoder);
-        self.pipeline.draw(
+        self.process_queued(device, staging_belt, encoder)?;
+        let (pipeline, color_pipeline) = gpu_pipelines(&mut self.renderer)?;
+        pipeline.draw(
+            device,
+            staging_belt,
+            encoder,
+            target,
+            transform,
+            Some(region),
+        );
+        color_pipeline.draw(
+            self.glyph_brush.fonts(),
+            &self.color_faces,
+            std::mem::take(&mut self.color_queue),
             device,
             staging_belt,
             encoder,
@@ -318,15 +468,20 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
         let glyph_brush = raw_builder.build();
         let (cache_width, cache_height) = glyph_brush.texture_dimensions();
         GlyphBrush {
-            pipeline: Pipeline::<wgpu::DepthStencilState>::new(
-                device,
-                filter_mode,
-                multisample,
-                render_format,
-                depth_stencil_state,
-                cache_width,
-                cache_height,
-            ),
+            renderer: Renderer::Gpu {
+                pipeline: Pipeline::<wgpu::DepthStencilState>::new(
+                    device,
+                    filter_mode,
+                    multisample,
+                    render_format,
+                    depth_stencil_state,
+                    cache_width,
+                    cache_height,
+                ),
+                color_pipeline: ColorPipeline::new(device, render_format),
+            },
+            color_faces: HashMap::new(),
+            color_queue: vec![],
             glyph_brush,
         }
     }
@@ -383,13 +538,25 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
         depth_stencil_attachment: wgpu::RenderPassDepthStencilAttachment,
         transform: [f32; 16],
     ) -> Result<(), String> {
-        self.process_queued(device, staging_belt, encoder);
-        self.pipeline.draw(
+        self.process_queued(device, staging_belt, encoder)?;
+        let (pipeline, color_pipeline) = gpu_pipelines(&mut self.renderer)?;
+        pipeline.draw(
             (device, staging_belt, encoder, target),
             depth_stencil_attachment,
             transform,
             None,
         );
+        color_pipeline.draw(
+            self.glyph_brush.fonts(),
+            &self.color_faces,
+            std::mem::take(&mut self.color_queue),
+            device,
+            staging_belt,
+            encoder,
+            target,
+            transform,
+            None,
+        );
 
         Ok(())
     }
@@ -421,19 +588,44 @@ impl<F: Font + Sync, H: BuildHasher> GlyphBrush<wgpu::DepthStencilState, F, H> {
     ) -> Result<(), String> {
         let (device, staging_belt, encoder, target) = config;
 
-        self.process_queued(device, staging_belt, encoder);
+        self.process_queued(device, staging_belt, encoder)?;
+        let (pipeline, color_pipeline) = gpu_pipelines(&mut self.renderer)?;
 
-        self.pipeline.draw(
+        pipeline.draw(
             (device, staging_belt, encoder, target),
             depth_stencil_attachment,
             transform,
             Some(region),
         );
+        color_pipeline.draw(
+            self.glyph_brush.fonts(),
+            &self.color_faces,
+            std::mem::take(&mut self.color_queue),
+            device,
+            staging_belt,
+            encoder,
+            target,
+            transform,
+            Some(region),
+        );
 
         Ok(())
     }
 }
 
+fn gpu_pipelines<D>(
+    renderer: &mut Renderer<D>,
+) -> Result<(&mut Pipeline<D>, &mut ColorPipeline), String> {
+    match renderer {
+        Renderer::Gpu {
+            pipeline,
+            color_pipeline,
+        } => Ok((pipeline, color_pipeline)),
+        #[cfg(feature = "software")]
+        Renderer::Software(_) => Err(String::from(NOT_GPU)),
+    }
+}
+
 /// Helper function to generate a generate a transform matrix.
 pub fn orthographic_projection(width: u32, height: u32) -> [f32; 16] {
     [
@@ -415,11 +415,11 @@ fn create_uniforms(
 #[repr(C)]
 #[derive(Debug, Clone, Copy, Zeroable, Pod)]
 pub struct Instance {
-    left_top: [f32; 3],
-    right_bottom: [f32; 2],
-    tex_left_top: [f32; 2],
-    tex_right_bottom: [f32; 2],
-    color: [f32; 4],
+    pub(super) left_top: [f32; 3],
+    pub(super) right_bottom: [f32; 2],
+    pub(super) tex_left_top: [f32; 2],
+    pub(super) tex_right_bottom: [f32; 2],
+    pub(super) color: [f32; 4],
 }
 
 impl Instance {
@@ -0,0 +1,126 @@
+// The software renderer keeps the glyph cache of glyph_brush in memory and
+// reads it back for every glyph, where the GPU one samples it in text.wgsl.
+
+use crate::components::software::Canvas;
+use crate::components::text::color::{self, ColorGlyph, GlyphKey};
+use crate::components::text::pipeline::Instance;
+use crate::font::color::{ColorFace, ColorImage};
+
+use glyph_brush::ab_glyph::Font;
+use std::collections::HashMap;
+
+pub struct SoftwarePipeline {
+    cache: Vec<u8>,
+    cache_size: (u32, u32),
+    instances: Vec<Instance>,
+    color_glyphs: HashMap<GlyphKey, Option<ColorImage>>,
+}
+
+impl SoftwarePipeline {
+    pub fn new(cache_width: u32, cache_height: u32) -> SoftwarePipeline {
+        SoftwarePipeline {
+            cache: vec![0; (cache_width * cache_height) as usize],
+            cache_size: (cache_width, cache_height),
+            instances: vec![],
+            color_glyphs: HashMap::new(),
+        }
+    }
+
+    pub fn update_cache(&mut self, offset: [u32; 2], size: [u32; 2], data: &[u8]) {
+        let cache_width = self.cache_size.0 as usize;
+        let width = size[0] as usize;
+        for (row, line) in data.chunks_exact(width).take(size[1] as usize).enumerate() {
+            let start = (offset[1] as usize + row) * cache_width + offset[0] as usize;
+            self.cache[start..start + width].copy_from_slice(line);
+        }
+    }
+
+    /// glyph_brush rasterizes every glyph again after a resize.
+    pub fn increase_cache_size(&mut self, width: u32, height: u32) {
+        self.cache = vec![0; (width * height) as usize];
+        self.cache_size = (width, height);
+    }
+
+    #[inline]
+    pub fn upload(&mut self, instances: Vec<Instance>) {
+        self.instances = instances;
+    }
+
+    /// Draws each glyph with the coverage of the cache texels under its
+    /// pixels.
+    pub fn draw(&self, canvas: &mut Canvas) {
+        let (cache_width, cache_height) =
+            (self.cache_size.0 as f32, self.cache_size.1 as f32);
+
+        for instance in &self.instances {
+            // Instances are flipped vertically for the GPU projection.
+            let (left, right) = (instance.left_top[0], instance.right_bottom[0]);
+            let (top, bottom) = (instance.right_bottom[1], instance.left_top[1]);
+            let (tex_left, tex_right) =
+                (instance.tex_left_top[0], instance.tex_right_bottom[0]);
+            let (tex_top, tex_bottom) =
+                (instance.tex_right_bottom[1], instance.tex_left_top[1]);
+
+            let width = (right - left).round() as u32;
+            let height = (bottom - top).round() as u32;
+            if width == 0 || height == 0 {
+                continue;
+            }
+
+            let mut coverage = Vec::with_capacity((width * height) as usize);
+            for y in 0..height {
+                let v =
+                    tex_top + (tex_bottom - tex_top) * (y as f32 + 0.5) / height as f32;
+                let texel_y =
+                    ((v * cache_height) as usize).min(self.cache_size.1 as usize - 1);
+                for x in 0..width {
+                    let u = tex_left
+                        + (tex_right - tex_left) * (x as f32 + 0.5) / width as f32;
+                    let texel_x =
+                        ((u * cache_width) as usize).min(self.cache_size.0 as usize - 1);
+                    coverage
+                        .push(self.cache[texel_y * self.cache_size.0 as usize + texel_x]);
+                }
+            }
+
+            canvas.draw_coverage(
+                left.round() as i32,
+                top.round() as i32,
+                width,
+                height,
+                &coverage,
+                instance.color,
+            );
+        }
+    }
+
+    /// Draws the color glyphs centered in their bounds, like the GPU
+    /// pipeline of `color.rs`.
+    pub fn draw_color<F: Font>(
+        &mut self,
+        fonts: &[F],
+        faces: &HashMap<usize, ColorFace>,
+        queue: Vec<ColorGlyph>,
+        canvas: &mut Canvas,
+    ) {
+        if self.color_glyphs.len() >= color::CACHE_CAPACITY {
+            self.color_glyphs.clear();
+        }
+
+        for glyph in queue {
+            let key = GlyphKey::from(&glyph);
+            let image = self
+                .color_glyphs
+                .entry(key)
+                .or_insert_with(|| color::rasterize(faces, fonts, key));
+
+            if let Some(image) = image {
+                let left =
+                    glyph.bounds.min.x + (glyph.bounds.width() - image.width as f32) / 2.;
+                let top = glyph.bounds.min.y
+                    + (glyph.bounds.height() - image.height as f32) / 2.;
+                canvas.draw_image(left.round() as i32, top.round() as i32, image);
+            }
+        }
+    }
+}
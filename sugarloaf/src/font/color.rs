@@ -0,0 +1,246 @@
+// Emoji fonts paint their glyphs in color, either with layers of outlines
+// filled from a palette (COLR/CPAL) or with embedded images (CBDT, sbix).
+// glyph_brush only knows about coverage, so those glyphs are rasterized
+// here into RGBA images instead and drawn from an atlas of their own.
+
+use glyph_brush::ab_glyph::{self, point, Font, PxScale, ScaleFont};
+use rustybuzz::ttf_parser::{self, colr, GlyphId, RasterImageFormat, RgbaColor};
+use std::sync::Arc;
+
+/// Font data shared with the font database, file backed faces are mapped
+/// rather than copied since emoji fonts tend to be large.
+pub type FaceData = Arc<dyn AsRef<[u8]> + Send + Sync>;
+
+/// Layers meant to take the text color are painted white, the color of
+/// the cell isn't known when glyphs are rasterized.
+const FOREGROUND: RgbaColor = RgbaColor {
+    red: 255,
+    green: 255,
+    blue: 255,
+    alpha: 255,
+};
+
+/// Straight alpha RGBA pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+#[derive(Clone)]
+pub struct ColorFace {
+    data: FaceData,
+    index: u32,
+}
+
+impl ColorFace {
+    /// Returns `None` when the face has no color glyphs.
+    #[inline]
+    pub fn new(data: FaceData, index: u32) -> Option<ColorFace> {
+        has_color_glyphs((*data).as_ref(), index).then_some(ColorFace { data, index })
+    }
+
+    /// Rasterize the glyph to fit a `width` x `height` box, keeping its
+    /// aspect ratio. `font` is the same face, used for the outlines of
+    /// COLR layers.
+    pub fn rasterize<F: Font>(
+        &self,
+        font: &F,
+        glyph_id: u16,
+        width: u32,
+        height: u32,
+    ) -> Option<ColorImage> {
+        let face = ttf_parser::Face::parse((*self.data).as_ref(), self.index).ok()?;
+        let glyph_id = GlyphId(glyph_id);
+
+        let image = if face.is_color_glyph(glyph_id) {
+            paint_layers(&face, font, glyph_id, height)?
+        } else {
+            let raster = face.glyph_raster_image(glyph_id, height as u16)?;
+            if raster.format != RasterImageFormat::PNG {
+                return None;
+            }
+
+            let image =
+                image::load_from_memory_with_format(raster.data, image::ImageFormat::Png)
+                    .ok()?
+                    .to_rgba8();
+            ColorImage {
+                width: image.width(),
+                height: image.height(),
+                data: image.into_raw(),
+            }
+        };
+
+        if image.width == 0 || image.height == 0 {
+            return None;
+        }
+
+        resize(image, width, height)
+    }
+}
+
+pub fn has_color_glyphs(data: &[u8], index: u32) -> bool {
+    ttf_parser::Face::parse(data, index).map_or(false, |face| {
+        let tables = face.tables();
+        tables.colr.is_some() || tables.cbdt.is_some() || tables.sbix.is_some()
+    })
+}
+
+#[derive(Default)]
+struct Layers(Vec<(GlyphId, RgbaColor)>);
+
+impl colr::Painter for Layers {
+    fn outline(&mut self, glyph_id: GlyphId) {
+        self.0.push((glyph_id, FOREGROUND));
+    }
+
+    fn paint_foreground(&mut self) {}
+
+    fn paint_color(&mut self, color: RgbaColor) {
+        if let Some(layer) = self.0.last_mut() {
+            layer.1 = color;
+        }
+    }
+}
+
+fn paint_layers<F: Font>(
+    face: &ttf_parser::Face,
+    font: &F,
+    glyph_id: GlyphId,
+    px: u32,
+) -> Option<ColorImage> {
+    let mut layers = Layers::default();
+    face.paint_color_glyph(glyph_id, 0, &mut layers)?;
+
+    let scale = PxScale::from(px as f32);
+    let ascent = font.as_scaled(scale).ascent();
+    let outlines: Vec<_> = layers
+        .0
+        .into_iter()
+        .filter_map(|(id, color)| {
+            let glyph =
+                ab_glyph::GlyphId(id.0).with_scale_and_position(scale, point(0., ascent));
+            font.outline_glyph(glyph).map(|outline| (outline, color))
+        })
+        .collect();
+
+    let bounds = outlines
+        .iter()
+        .map(|(outline, _)| outline.px_bounds())
+        .reduce(|a, b| ab_glyph::Rect {
+            min: point(a.min.x.min(b.min.x), a.min.y.min(b.min.y)),
+            max: point(a.max.x.max(b.max.x), a.max.y.max(b.max.y)),
+        })?;
+
+    let width = bounds.width() as u32;
+    let height = bounds.height() as u32;
+    let mut data = vec![0; (width * height * 4) as usize];
+    for (outline, color) in outlines {
+        let offset = outline.px_bounds().min - bounds.min;
+        outline.draw(|x, y, coverage| {
+            let x = x + offset.x as u32;
+            let y = y + offset.y as u32;
+            if x < width && y < height {
+                let index = ((y * width + x) * 4) as usize;
+                blend(&mut data[index..index + 4], color, coverage);
+            }
+        });
+    }
+
+    Some(ColorImage {
+        width,
+        height,
+        data,
+    })
+}
+
+/// Paints `color` with `coverage` over a straight alpha pixel.
+fn blend(pixel: &mut [u8], color: RgbaColor, coverage: f32) {
+    let alpha = coverage.clamp(0., 1.) * color.alpha as f32 / 255.;
+    let dst_alpha = pixel[3] as f32 / 255.;
+    let out_alpha = alpha + dst_alpha * (1. - alpha);
+    if out_alpha <= 0. {
+        return;
+    }
+
+    for (dst, src) in pixel.iter_mut().zip([color.red, color.green, color.blue]) {
+        let value =
+            (src as f32 * alpha + *dst as f32 * dst_alpha * (1. - alpha)) / out_alpha;
+        *dst = value.round() as u8;
+    }
+    pixel[3] = (out_alpha * 255.).round() as u8;
+}
+
+/// Size of a `width` x `height` image scaled to fit the box.
+fn fit(width: u32, height: u32, box_width: u32, box_height: u32) -> (u32, u32) {
+    let scale = (box_width as f32 / width as f32).min(box_height as f32 / height as f32);
+    (
+        ((width as f32 * scale).round() as u32).max(1),
+        ((height as f32 * scale).round() as u32).max(1),
+    )
+}
+
+fn resize(image: ColorImage, box_width: u32, box_height: u32) -> Option<ColorImage> {
+    let (width, height) = fit(image.width, image.height, box_width, box_height);
+    if (width, height) == (image.width, image.height) {
+        return Some(image);
+    }
+
+    let buffer = image::RgbaImage::from_raw(image.width, image.height, image.data)?;
+    let resized = image::imageops::resize(
+        &buffer,
+        width,
+        height,
+        image::imageops::FilterType::Triangle,
+    );
+    Some(ColorImage {
+        width,
+        height,
+        data: resized.into_raw(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::constants::FONT_EMOJI;
+
+    #[test]
+    fn monochrome_faces_are_not_color() {
+        assert!(ColorFace::new(Arc::new(FONT_EMOJI), 0).is_none());
+    }
+
+    #[test]
+    fn images_fit_the_box_keeping_aspect_ratio() {
+        assert_eq!(fit(136, 128, 32, 32), (32, 30));
+        assert_eq!(fit(100, 100, 40, 20), (20, 20));
+        assert_eq!(fit(1, 1000, 10, 10), (1, 10));
+
+        let image = ColorImage {
+            width: 4,
+            height: 4,
+            data: vec![255; 64],
+        };
+        let resized = resize(image, 2, 8).unwrap();
+        assert_eq!((resized.width, resized.height), (2, 2));
+        assert_eq!(resized.data, vec![255; 16]);
+    }
+
+    #[test]
+    fn layers_blend_over_each_other() {
+        let mut pixel = [0, 0, 0, 0];
+        let red = RgbaColor::new(255, 0, 0, 255);
+        blend(&mut pixel, red, 1.);
+        assert_eq!(pixel, [255, 0, 0, 255]);
+
+        let blue = RgbaColor::new(0, 0, 255, 255);
+        blend(&mut pixel, blue, 0.5);
+        assert_eq!(pixel, [128, 0, 128, 255]);
+
+        let mut pixel = [0, 0, 0, 0];
+        blend(&mut pixel, blue, 0.5);
+        assert_eq!(pixel, [0, 0, 255, 128]);
+    }
+}
@@ -0,0 +1,184 @@
+// Last resort for chars that none of the configured fonts cover, like CJK,
+// Devanagari or math symbols. The system faces are looked up once per char
+// and the answer is kept, including when no face has it. The cmap of a face
+// is read the first time the face is tried and kept as ranges, so a face is
+// parsed at most once.
+
+use crate::font::color::ColorFace;
+use crate::font::loader::{Database, Family, Style, ID};
+use glyph_brush::ab_glyph::FontArc;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+pub struct SystemFallback {
+    db: Arc<Database>,
+    /// One face per family, the preferred families first.
+    candidates: Vec<ID>,
+    /// Coverage of each candidate, in the same order, read on demand.
+    coverage: Vec<Option<Coverage>>,
+    cache: HashMap<char, Option<ID>>,
+}
+
+impl SystemFallback {
+    pub fn new(db: Arc<Database>) -> SystemFallback {
+        let candidates = candidates(&db);
+        SystemFallback {
+            coverage: (0..candidates.len()).map(|_| None).collect(),
+            db,
+            candidates,
+            cache: HashMap::new(),
+        }
+    }
+
+    /// Face covering `content`, if the system has any.
+    pub fn find(&mut self, content: char) -> Option<ID> {
+        if let Some(id) = self.cache.get(&content) {
+            return *id;
+        }
+
+        let db = &self.db;
+        let id = self
+            .candidates
+            .iter()
+            .zip(self.coverage.iter_mut())
+            .find_map(|(id, coverage)| {
+                coverage
+                    .get_or_insert_with(|| Coverage::read(db, *id))
+                    .contains(content)
+                    .then_some(*id)
+            });
+
+        if let Some(face) = id.and_then(|id| db.face(id)) {
+            log::info!(
+                "Font fallback: {:?} found in '{}'",
+                content,
+                face.families
+                    .get(0)
+                    .map(|family| family.0.as_str())
+                    .unwrap_or_default()
+            );
+        }
+
+        self.cache.insert(content, id);
+        id
+    }
+
+    #[inline]
+    pub fn load(&self, id: ID) -> Option<(FontArc, Option<ColorFace>)> {
+        crate::font::load_face(&self.db, id)
+    }
+}
+
+/// Sorted and disjoint ranges of the chars a face has glyphs for.
+struct Coverage(Vec<(u32, u32)>);
+
+impl Coverage {
+    fn read(db: &Database, id: ID) -> Coverage {
+        let mut codepoints = Vec::new();
+        db.with_face_data(id, |data, index| {
+            let Some(cmap) = ttf_parser::Face::parse(data, index)
+                .ok()
+                .and_then(|face| face.tables().cmap)
+            else {
+                return;
+            };
+            for subtable in cmap.subtables.into_iter().filter(|s| s.is_unicode()) {
+                subtable.codepoints(|codepoint| {
+                    if subtable.glyph_index(codepoint).is_some() {
+                        codepoints.push(codepoint);
+                    }
+                });
+            }
+        });
+        codepoints.sort_unstable();
+        codepoints.dedup();
+
+        let mut ranges: Vec<(u32, u32)> = Vec::new();
+        for codepoint in codepoints {
+            match ranges.last_mut() {
+                Some((_, end)) if *end + 1 == codepoint => *end = codepoint,
+                _ => ranges.push((codepoint, codepoint)),
+            }
+        }
+        Coverage(ranges)
+    }
+
+    #[inline]
+    fn contains(&self, content: char) -> bool {
+        let codepoint = content as u32;
+        self.0
+            .binary_search_by(|(start, end)| {
+                if *end < codepoint {
+                    Ordering::Less
+                } else if *start > codepoint {
+                    Ordering::Greater
+                } else {
+                    Ordering::Equal
+                }
+            })
+            .is_ok()
+    }
+}
+
+fn candidates(db: &Database) -> Vec<ID> {
+    let mut families: Vec<String> = db
+        .fallback_families()
+        .iter()
+        .map(|family| family.to_lowercase())
+        .collect();
+    for family in [Family::Monospace, Family::SansSerif, Family::Serif] {
+        let family = db.family_name(&family).to_lowercase();
+        if !families.contains(&family) {
+            families.push(family);
+        }
+    }
+
+    // Regular faces are picked over the other styles of a family.
+    let mut faces: Vec<(usize, &str, bool, u16, ID)> = db
+        .faces()
+        .filter_map(|face| {
+            let family = face.families.get(0)?.0.as_str();
+            let rank = families
+                .iter()
+                .position(|preferred| *preferred == family.to_lowercase())
+                .unwrap_or(families.len());
+            Some((
+                rank,
+                family,
+                face.style != Style::Normal,
+                face.weight.0.abs_diff(400),
+                face.id,
+            ))
+        })
+        .collect();
+    faces.sort();
+    faces.dedup_by(|a, b| a.1 == b.1);
+
+    faces.into_iter().map(|(.., id)| id).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::constants::{FONT_CASCADIAMONO_REGULAR, FONT_EMOJI};
+
+    #[test]
+    fn finds_and_caches_faces_covering_a_char() {
+        let mut db = Database::new();
+        db.load_font_data(FONT_CASCADIAMONO_REGULAR.to_vec());
+        db.load_font_data(FONT_EMOJI.to_vec());
+
+        let mut fallback = SystemFallback::new(Arc::new(db));
+        assert_eq!(fallback.candidates.len(), 2);
+
+        let id = fallback.find('🦀').unwrap();
+        assert_ne!(fallback.find('a'), Some(id));
+        assert!(fallback.load(id).is_some());
+        assert_eq!(fallback.cache.get(&'🦀'), Some(&Some(id)));
+        assert!(fallback.coverage.iter().all(Option::is_some));
+
+        assert_eq!(fallback.find('\u{10FFFD}'), None);
+        assert_eq!(fallback.cache.get(&'\u{10FFFD}'), Some(&None));
+    }
+}
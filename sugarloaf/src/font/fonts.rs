@@ -1,5 +1,6 @@
 use crate::font::{DEFAULT_FONT_FAMILY, DEFAULT_FONT_FAMILY_VARIANT};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /* Example:
 
@@ -7,11 +8,18 @@ use serde::{Deserialize, Serialize};
 size = 18
 # You can also set family on root to overwritte all fonts
 # family = "cascadiamono"
+# Draw box drawing, block, braille and Powerline chars instead of
+# taking them from the fonts
+builtin-box-drawing = true
 
 [fonts.regular]
 family = "cascadiamono"
 style = "normal"
 weight = 400
+# OpenType features, a leading "-" turns a feature off
+features = ["ss01", "zero", "-calt"]
+# Axes of variable fonts
+variations = { wght = 450 }
 
 [fonts.bold]
 family = "cascadiamono"
@@ -27,6 +35,11 @@ weight = 400
 family = "cascadiamono"
 style = "italic"
 weight = 800
+
+# Looked up in order for chars the fonts above don't have,
+# before any system font
+[[fonts.extras]]
+family = "Noto Sans CJK JP"
 */
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Clone)]
@@ -35,6 +48,10 @@ pub struct SugarloafFont {
     pub family: String,
     pub weight: Option<u16>,
     pub style: Option<String>,
+    #[serde(default = "Vec::default")]
+    pub features: Vec<String>,
+    #[serde(default = "BTreeMap::default")]
+    pub variations: BTreeMap<String, f32>,
 }
 
 impl SugarloafFont {
@@ -49,6 +66,10 @@ pub fn default_font_size() -> f32 {
     18.
 }
 
+fn default_builtin_box_drawing() -> bool {
+    true
+}
+
 fn default_font_family() -> String {
     DEFAULT_FONT_FAMILY.to_string()
 }
@@ -61,6 +82,8 @@ pub fn default_font_regular() -> SugarloafFont {
         family: default_font_family(),
         weight: Some(400),
         style: Some(String::from("normal")),
+        features: vec![],
+        variations: BTreeMap::new(),
     }
 }
 
@@ -69,6 +92,8 @@ pub fn default_font_bold() -> SugarloafFont {
         family: default_font_family(),
         weight: Some(800),
         style: Some(String::from("normal")),
+        features: vec![],
+        variations: BTreeMap::new(),
     }
 }
 
@@ -77,6 +102,8 @@ pub fn default_font_italic() -> SugarloafFont {
         family: default_font_family(),
         weight: Some(400),
         style: Some(String::from("italic")),
+        features: vec![],
+        variations: BTreeMap::new(),
     }
 }
 
@@ -85,6 +112,8 @@ pub fn default_font_bold_italic() -> SugarloafFont {
         family: default_font_family(),
         weight: Some(800),
         style: Some(String::from("italic")),
+        features: vec![],
+        variations: BTreeMap::new(),
     }
 }
 
@@ -102,6 +131,13 @@ pub struct SugarloafFonts {
     pub bold_italic: SugarloafFont,
     #[serde(default = "default_font_italic")]
     pub italic: SugarloafFont,
+    #[serde(default = "Vec::default")]
+    pub extras: Vec<SugarloafFont>,
+    #[serde(
+        default = "default_builtin_box_drawing",
+        rename = "builtin-box-drawing"
+    )]
+    pub builtin_box_drawing: bool,
 }
 
 impl Default for SugarloafFonts {
@@ -113,6 +149,8 @@ impl Default for SugarloafFonts {
             bold: default_font_bold(),
             bold_italic: default_font_bold_italic(),
             italic: default_font_italic(),
+            extras: vec![],
+            builtin_box_drawing: default_builtin_box_drawing(),
         }
     }
 }
@@ -73,6 +73,7 @@ pub struct Database {
     family_cursive: String,
     family_fantasy: String,
     family_monospace: String,
+    fallback_families: Vec<String>,
 }
 
 impl Default for Database {
@@ -103,6 +104,7 @@ impl Database {
             #[cfg(target_os = "macos")]
             family_fantasy: "Papyrus".to_string(),
             family_monospace: "Courier New".to_string(),
+            fallback_families: Vec::new(),
         }
     }
 
@@ -344,8 +346,9 @@ impl Database {
                 .or_else(|| accept.get(0))
                 .or_else(|| default.get(0));
 
+            let alias = alias.to_lowercase();
             if let Some(name) = name {
-                match alias.to_lowercase().as_str() {
+                match alias.as_str() {
                     "serif" => self.set_serif_family(name),
                     "sans-serif" => self.set_sans_serif_family(name),
                     "sans serif" => self.set_sans_serif_family(name),
@@ -355,6 +358,19 @@ impl Database {
                     _ => {}
                 }
             }
+
+            // Every family of the aliases is a candidate to fallback, that is
+            // where distributions list their CJK and other script faces.
+            if matches!(
+                alias.as_str(),
+                "monospace" | "sans-serif" | "sans serif" | "serif"
+            ) {
+                for name in prefer.iter().chain(&accept).chain(&default) {
+                    if !self.fallback_families.contains(name) {
+                        self.fallback_families.push(name.to_owned());
+                    }
+                }
+            }
         }
 
         for dir in fontconfig.dirs {
@@ -450,6 +466,15 @@ impl Database {
         }
     }
 
+    /// Families to look first for glyphs missing in the configured fonts,
+    /// in order of preference.
+    ///
+    /// Filled from fontconfig aliases, so it's empty on other platforms.
+    #[inline]
+    pub fn fallback_families(&self) -> &[String] {
+        &self.fallback_families
+    }
+
     /// Performs a CSS-like query and returns the best matched font face.
     pub fn query(&self, query: &Query) -> Option<ID> {
         for family in query.families {
@@ -1,8 +1,11 @@
+pub mod color;
 pub mod constants;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod fallback;
 pub mod fonts;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod loader;
-// pub mod ligatures;
+pub mod shaping;
 
 pub const FONT_ID_REGULAR: usize = 0;
 pub const FONT_ID_ITALIC: usize = 1;
@@ -13,13 +16,87 @@ pub const FONT_ID_EMOJIS: usize = 5;
 pub const FONT_ID_UNICODE: usize = 6;
 pub const FONT_ID_ICONS: usize = 7;
 pub const FONT_ID_BUILTIN: usize = 8;
+/// First of the `fonts.extras`, faces from the system fallback come after
+/// them.
+pub const FONT_ID_EXTRAS: usize = 9;
 
+use crate::font::color::ColorFace;
 use crate::font::constants::*;
-use glyph_brush::ab_glyph::FontArc;
+use glyph_brush::ab_glyph::{FontArc, FontRef, FontVec, VariableFont};
+use std::borrow::Cow;
+#[cfg(not(target_arch = "wasm32"))]
+use std::sync::Arc;
+
+/// Color emoji fonts that come with macOS, most Linux distributions and
+/// Windows, the first one found takes the emoji slot.
+#[cfg(not(target_arch = "wasm32"))]
+const COLOR_EMOJI_FAMILIES: [&str; 4] = [
+    "Apple Color Emoji",
+    "Noto Color Emoji",
+    "Segoe UI Emoji",
+    "Twemoji",
+];
 
 pub type SugarloafFont = fonts::SugarloafFont;
 pub type SugarloafFonts = fonts::SugarloafFonts;
 
+/// Raw data of a loaded face and the settings it was loaded with, kept
+/// around for shaping.
+#[derive(Debug, Clone)]
+pub struct FontData {
+    pub data: Cow<'static, [u8]>,
+    pub features: Vec<String>,
+    pub variations: Vec<([u8; 4], f32)>,
+}
+
+impl FontData {
+    #[inline]
+    pub fn new(data: Cow<'static, [u8]>, font_spec: &SugarloafFont) -> FontData {
+        let variations = font_spec
+            .variations
+            .iter()
+            .filter_map(|(axis, value)| match <[u8; 4]>::try_from(axis.as_bytes()) {
+                Ok(tag) => Some((tag, *value)),
+                Err(_) => {
+                    log::warn!(
+                        "Ignoring font variation '{axis}', axes are four letter tags"
+                    );
+                    None
+                }
+            })
+            .collect();
+
+        FontData {
+            data,
+            features: font_spec.features.to_owned(),
+            variations,
+        }
+    }
+
+    /// Face to rasterize with, the variations are applied on it.
+    #[inline]
+    fn font_arc(&self) -> Result<FontArc, glyph_brush::ab_glyph::InvalidFont> {
+        fn vary<F: VariableFont>(mut font: F, variations: &[([u8; 4], f32)]) -> F {
+            for (axis, value) in variations {
+                if !font.set_variation(axis, *value) {
+                    log::warn!("Font has no '{}' axis", String::from_utf8_lossy(axis));
+                }
+            }
+            font
+        }
+
+        Ok(match &self.data {
+            Cow::Borrowed(data) => {
+                FontArc::new(vary(FontRef::try_from_slice(data)?, &self.variations))
+            }
+            Cow::Owned(data) => FontArc::new(vary(
+                FontVec::try_from_vec(data.to_owned())?,
+                &self.variations,
+            )),
+        })
+    }
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 use log::{info, warn};
 
@@ -30,6 +107,8 @@ pub struct ComposedFontArc {
     pub bold: FontArc,
     pub italic: FontArc,
     pub bold_italic: FontArc,
+    /// Data of the faces above, indexed by `FONT_ID_*`.
+    pub data: [FontData; 4],
 }
 
 pub struct Font {
@@ -39,6 +118,67 @@ pub struct Font {
     pub unicode: FontArc,
     pub icons: FontArc,
     pub breadcrumbs: FontArc,
+    pub extras: Vec<FontArc>,
+    /// Faces with color glyphs, by font id.
+    pub color_faces: Vec<(usize, ColorFace)>,
+}
+
+/// Data of a face without copying it, font files get memory mapped.
+#[cfg(not(target_arch = "wasm32"))]
+fn shared_face_data(db: &loader::Database, id: loader::ID) -> Option<ColorFace> {
+    let (source, index) = db.face_source(id)?;
+    let data: color::FaceData = match source {
+        loader::Source::Binary(data) | loader::Source::SharedFile(_, data) => data,
+        loader::Source::File(path) => {
+            let file = std::fs::File::open(path).ok()?;
+            // Same as the database does when mapping font files.
+            Arc::new(unsafe { memmap2::MmapOptions::new().map(&file).ok()? })
+        }
+    };
+    ColorFace::new(data, index)
+}
+
+/// Loads a face of the database, along with its color glyphs if it has any.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn load_face(
+    db: &loader::Database,
+    id: loader::ID,
+) -> Option<(FontArc, Option<ColorFace>)> {
+    let font = db.with_face_data(id, |data, index| {
+        let font = FontVec::try_from_vec_and_index(data.to_vec(), index);
+        (font, color::has_color_glyphs(data, index))
+    })?;
+    let font = match font {
+        (font, true) => (font, shared_face_data(db, id)),
+        (font, false) => (font, None),
+    };
+
+    match font {
+        (Ok(font), color_face) => Some((FontArc::new(font), color_face)),
+        (Err(err_message), _) => {
+            warn!("Failed to load font, {err_message}");
+            None
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn find_color_emoji(db: &loader::Database) -> Option<(FontArc, ColorFace)> {
+    for family in COLOR_EMOJI_FAMILIES {
+        let query = loader::Query {
+            families: &[loader::Family::Name(family)],
+            ..loader::Query::default()
+        };
+
+        if let Some((font, Some(color_face))) =
+            db.query(&query).and_then(|id| load_face(db, id))
+        {
+            info!("Color emojis from '{family}'");
+            return Some((font, color_face));
+        }
+    }
+
+    None
 }
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -46,7 +186,7 @@ pub struct Font {
 fn find_font(
     db: &crate::font::loader::Database,
     font_spec: SugarloafFont,
-) -> (FontArc, bool, Option<SugarloafFont>) {
+) -> (FontArc, FontData, bool, Option<SugarloafFont>) {
     use std::io::Read;
 
     let weight = font_spec.weight.unwrap_or(400);
@@ -84,22 +224,28 @@ fn find_font(
                     if let Ok(mut file) = std::fs::File::open(path) {
                         let mut font_data = vec![];
                         if file.read_to_end(&mut font_data).is_ok() {
-                            match FontArc::try_from_vec(font_data) {
+                            let font_data =
+                                FontData::new(Cow::Owned(font_data), &font_spec);
+                            match font_data.font_arc() {
                                 Ok(arc) => {
                                     warn!(
                                         "Font '{}' found in {}",
                                         family,
                                         path.display()
                                     );
-                                    return (arc, false, None);
+                                    return (arc, font_data, false, None);
                                 }
                                 Err(err_message) => {
                                     warn!("Failed to load font '{family}' with style '{style}' and weight '{weight}', {err_message}");
-                                    return (
-                                        FontArc::try_from_slice(
+                                    let font_data = FontData::new(
+                                        Cow::Borrowed(
                                             constants::FONT_CASCADIAMONO_REGULAR,
-                                        )
-                                        .unwrap(),
+                                        ),
+                                        &font_spec,
+                                    );
+                                    return (
+                                        font_data.font_arc().unwrap(),
+                                        font_data,
                                         true,
                                         Some(font_spec),
                                     );
@@ -110,7 +256,7 @@ fn find_font(
                 }
             }
             None => {
-                not_found = Some(font_spec);
+                not_found = Some(font_spec.to_owned());
                 warn!("Failed to find font '{family}' with style '{style}' and weight '{weight}'");
             }
         }
@@ -143,11 +289,8 @@ fn find_font(
         },
     };
 
-    (
-        FontArc::try_from_slice(font_to_load).unwrap(),
-        true,
-        not_found,
-    )
+    let font_data = FontData::new(Cow::Borrowed(font_to_load), &font_spec);
+    (font_data.font_arc().unwrap(), font_data, true, not_found)
 }
 
 impl Font {
@@ -190,6 +333,8 @@ impl Font {
                     family: String::from("Apple Symbols"),
                     style: None,
                     weight: None,
+                    features: vec![],
+                    variations: Default::default(),
                 },
             )
             .0;
@@ -200,6 +345,8 @@ impl Font {
                     family: String::from("Arial Unicode MS"),
                     style: None,
                     weight: None,
+                    features: vec![],
+                    variations: Default::default(),
                 },
             )
             .0;
@@ -212,64 +359,103 @@ impl Font {
         }
 
         let regular = find_font(db, font_spec.regular);
-        if let Some(err) = regular.2 {
+        if let Some(err) = regular.3 {
             fonts_not_fount.push(err);
         }
 
         let bold = find_font(db, font_spec.bold);
-        if let Some(err) = bold.2 {
+        if let Some(err) = bold.3 {
             fonts_not_fount.push(err);
         }
 
         let bold_italic = find_font(db, font_spec.bold_italic);
-        if let Some(err) = bold_italic.2 {
+        if let Some(err) = bold_italic.3 {
             fonts_not_fount.push(err);
         }
 
         let italic = find_font(db, font_spec.italic);
-        if let Some(err) = italic.2 {
+        if let Some(err) = italic.3 {
             fonts_not_fount.push(err);
         }
 
+        let mut color_faces = vec![];
+        let emojis = match find_color_emoji(db) {
+            Some((font, color_face)) => {
+                color_faces.push((FONT_ID_EMOJIS, color_face));
+                font
+            }
+            None => FontArc::try_from_slice(FONT_EMOJI).unwrap(),
+        };
+
+        // Extras are only useful when found, otherwise it would be
+        // the regular font again.
+        let mut extras = vec![];
+        for extra in font_spec.extras {
+            let extra = find_font(db, extra);
+            if let Some(err) = extra.3 {
+                fonts_not_fount.push(err);
+            } else if !extra.2 {
+                if let Some(color_face) = ColorFace::new(Arc::new(extra.1.data), 0) {
+                    color_faces.push((FONT_ID_EXTRAS + extras.len(), color_face));
+                }
+                extras.push(extra.0);
+            }
+        }
+
         (
             Font {
                 text: ComposedFontArc {
-                    is_monospace: regular.1,
+                    is_monospace: regular.2,
                     regular: regular.0,
                     bold: bold.0,
                     bold_italic: bold_italic.0,
                     italic: italic.0,
+                    data: [regular.1, italic.1, bold.1, bold_italic.1],
                 },
                 symbol: font_arc_symbol,
-                emojis: FontArc::try_from_slice(FONT_EMOJI).unwrap(),
+                emojis,
                 unicode: font_arc_unicode,
                 icons: FontArc::try_from_slice(FONT_SYMBOLS_NERD_FONT_MONO).unwrap(),
                 breadcrumbs: FontArc::try_from_slice(FONT_CASCADIAMONO_REGULAR).unwrap(),
+                extras,
+                color_faces,
             },
             fonts_not_fount,
         )
     }
 
     #[cfg(target_arch = "wasm32")]
-    pub fn new(_font_spec: SugarloafFonts) -> (Font, Vec<SugarloafFont>) {
+    pub fn new(font_spec: SugarloafFonts) -> (Font, Vec<SugarloafFont>) {
         let font_arc_unicode = FontArc::try_from_slice(FONT_UNICODE_FALLBACK).unwrap();
         let font_arc_symbol = FontArc::try_from_slice(FONT_DEJAVU_SANS).unwrap();
 
+        let regular =
+            FontData::new(Cow::Borrowed(FONT_CASCADIAMONO_REGULAR), &font_spec.regular);
+        let italic =
+            FontData::new(Cow::Borrowed(FONT_CASCADIAMONO_ITALIC), &font_spec.italic);
+        let bold = FontData::new(Cow::Borrowed(FONT_CASCADIAMONO_BOLD), &font_spec.bold);
+        let bold_italic = FontData::new(
+            Cow::Borrowed(FONT_CASCADIAMONO_BOLD_ITALIC),
+            &font_spec.bold_italic,
+        );
+
         (
             Font {
                 text: ComposedFontArc {
                     is_monospace: true,
-                    bold: FontArc::try_from_slice(FONT_CASCADIAMONO_BOLD).unwrap(),
-                    bold_italic: FontArc::try_from_slice(FONT_CASCADIAMONO_BOLD_ITALIC)
-                        .unwrap(),
-                    italic: FontArc::try_from_slice(FONT_CASCADIAMONO_ITALIC).unwrap(),
-                    regular: FontArc::try_from_slice(FONT_CASCADIAMONO_REGULAR).unwrap(),
+                    bold: bold.font_arc().unwrap(),
+                    bold_italic: bold_italic.font_arc().unwrap(),
+                    italic: italic.font_arc().unwrap(),
+                    regular: regular.font_arc().unwrap(),
+                    data: [regular, italic, bold, bold_italic],
                 },
                 symbol: font_arc_symbol,
                 emojis: FontArc::try_from_slice(FONT_EMOJI).unwrap(),
                 unicode: font_arc_unicode,
                 icons: FontArc::try_from_slice(FONT_SYMBOLS_NERD_FONT_MONO).unwrap(),
                 breadcrumbs: FontArc::try_from_slice(FONT_CASCADIAMONO_REGULAR).unwrap(),
+                extras: vec![],
+                color_faces: vec![],
             },
             vec![],
         )
@@ -0,0 +1,243 @@
+// OpenType shaping for runs of cells, which is what brings in the `calt`
+// and `liga` programming ligatures of fonts like Fira Code or JetBrains Mono.
+//
+// Good to remember that programming ligatures they contradict unicode
+// so it should only work in the renderer level, any time an user copy
+// the sugarloaf content it should come as the original sequence of characters
+
+use crate::font::FontData;
+use rustybuzz::{Direction, Face, Feature, Tag, UnicodeBuffer, Variation};
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+/// Shaped runs of a face kept in each generation of its cache.
+const CACHE_CAPACITY: usize = 4096;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ShapedGlyph {
+    /// Cell of the run the glyph is drawn at.
+    pub cell: usize,
+    pub id: u16,
+    /// Offset from the cell, in font units.
+    pub x_offset: i32,
+    pub y_offset: i32,
+}
+
+type ShapedRun = Option<Vec<ShapedGlyph>>;
+
+struct ShapingFace {
+    /// Parsed once, it borrows `_data` so it's declared before it to be
+    /// dropped first.
+    face: Option<Face<'static>>,
+    features: Vec<Feature>,
+    /// Runs shaped since the last generation started, looked up by `&str`
+    /// so hits don't allocate.
+    current: HashMap<String, ShapedRun>,
+    /// The generation before, its runs move back to `current` when they are
+    /// used again and the rest is dropped with it.
+    previous: HashMap<String, ShapedRun>,
+    _data: Cow<'static, [u8]>,
+}
+
+impl From<FontData> for ShapingFace {
+    fn from(font_data: FontData) -> ShapingFace {
+        let features = font_data
+            .features
+            .iter()
+            .filter_map(|feature| match Feature::from_str(feature) {
+                Ok(feature) => Some(feature),
+                Err(_) => {
+                    log::warn!("Ignoring invalid font feature '{feature}'");
+                    None
+                }
+            })
+            .collect();
+
+        let variations: Vec<Variation> = font_data
+            .variations
+            .iter()
+            .map(|(axis, value)| Variation {
+                tag: Tag::from_bytes(axis),
+                value: *value,
+            })
+            .collect();
+
+        let data = font_data.data;
+        // SAFETY: The bytes are either static or owned by `data` on the heap,
+        // they don't move along with the `ShapingFace`, are never changed and
+        // outlive `face`, which never leaves it.
+        let bytes: &'static [u8] =
+            unsafe { std::slice::from_raw_parts(data.as_ptr(), data.len()) };
+        let face = Face::from_slice(bytes, 0).map(|mut face| {
+            face.set_variations(&variations);
+            face
+        });
+
+        ShapingFace {
+            face,
+            features,
+            current: HashMap::new(),
+            previous: HashMap::new(),
+            _data: data,
+        }
+    }
+}
+
+impl ShapingFace {
+    fn shape(&mut self, text: &str) -> Option<&[ShapedGlyph]> {
+        if !self.current.contains_key(text) {
+            let glyphs = match self.previous.remove(text) {
+                Some(glyphs) => glyphs,
+                None => self
+                    .face
+                    .as_ref()
+                    .and_then(|face| shape(face, &self.features, text)),
+            };
+
+            if self.current.len() >= CACHE_CAPACITY {
+                self.previous = std::mem::take(&mut self.current);
+            }
+            self.current.insert(text.to_owned(), glyphs);
+        }
+
+        self.current.get(text).and_then(Option::as_deref)
+    }
+}
+
+pub struct Shaper {
+    faces: Vec<ShapingFace>,
+}
+
+impl Shaper {
+    /// `faces` are indexed by font id.
+    pub fn new(faces: Vec<FontData>) -> Shaper {
+        Shaper {
+            faces: faces.into_iter().map(ShapingFace::from).collect(),
+        }
+    }
+
+    /// Whether the face has features set by the user. Those can replace
+    /// glyphs of single characters, like a slashed zero.
+    #[inline]
+    pub fn has_features(&self, font_id: usize) -> bool {
+        self.faces
+            .get(font_id)
+            .map_or(false, |face| !face.features.is_empty())
+    }
+
+    /// Shape `text`, which takes one cell per char, with the face `font_id`.
+    ///
+    /// Returns `None` when the face keeps every char as its regular glyph in
+    /// its own cell, in that case there is nothing to gain from shaping.
+    #[inline]
+    pub fn shape(&mut self, font_id: usize, text: &str) -> Option<&[ShapedGlyph]> {
+        self.faces.get_mut(font_id)?.shape(text)
+    }
+}
+
+fn shape(face: &Face, features: &[Feature], text: &str) -> Option<Vec<ShapedGlyph>> {
+    let mut buffer = UnicodeBuffer::new();
+    buffer.push_str(text);
+    buffer.guess_segment_properties();
+    // Cells are always laid out left to right.
+    buffer.set_direction(Direction::LeftToRight);
+    let output = rustybuzz::shape(face, features, buffer);
+
+    let cells: Vec<(usize, char)> = text.char_indices().collect();
+    let mut is_changed = output.len() != cells.len();
+    let glyphs: Vec<ShapedGlyph> = output
+        .glyph_infos()
+        .iter()
+        .zip(output.glyph_positions())
+        .enumerate()
+        .map(|(index, (info, position))| {
+            let cluster = info.cluster as usize;
+            let cell = cells
+                .partition_point(|(offset, _)| *offset <= cluster)
+                .saturating_sub(1);
+            let glyph = ShapedGlyph {
+                cell,
+                id: info.glyph_id as u16,
+                x_offset: position.x_offset,
+                y_offset: position.y_offset,
+            };
+
+            if !is_changed {
+                let regular = face.glyph_index(cells[cell].1).map(|id| id.0);
+                is_changed = cell != index
+                    || regular != Some(glyph.id)
+                    || glyph.x_offset != 0
+                    || glyph.y_offset != 0;
+            }
+
+            glyph
+        })
+        .collect();
+
+    is_changed.then_some(glyphs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::font::constants::FONT_CASCADIAMONO_REGULAR;
+    use crate::font::fonts::default_font_regular;
+
+    fn face(data: &'static [u8], features: &[&str]) -> FontData {
+        let mut font_spec = default_font_regular();
+        font_spec.features = features.iter().map(|feature| feature.to_string()).collect();
+        FontData::new(Cow::Borrowed(data), &font_spec)
+    }
+
+    #[test]
+    fn plain_text_is_not_shaped() {
+        let mut shaper = Shaper::new(vec![face(FONT_CASCADIAMONO_REGULAR, &[])]);
+        assert_eq!(shaper.shape(0, "hello"), None);
+        assert_eq!(shaper.shape(1, "hello"), None);
+    }
+
+    #[test]
+    #[cfg(not(target_os = "macos"))]
+    fn ligatures_keep_their_first_cell() {
+        use crate::font::constants::FONT_DEJAVU_SANS;
+
+        // DejaVu Sans has a regular `liga` for "fi".
+        let mut shaper = Shaper::new(vec![
+            face(FONT_DEJAVU_SANS, &[]),
+            face(FONT_DEJAVU_SANS, &["-liga"]),
+        ]);
+        let glyphs = shaper.shape(0, "afi").unwrap().to_vec();
+        assert_eq!(glyphs.len(), 2);
+        assert_eq!(glyphs[0].cell, 0);
+        assert_eq!(glyphs[1].cell, 1);
+
+        // Served from the cache the second time.
+        assert_eq!(shaper.shape(0, "afi"), Some(&glyphs[..]));
+
+        // Unless the feature is turned off.
+        assert!(shaper.has_features(1));
+        assert_eq!(shaper.shape(1, "afi"), None);
+    }
+
+    #[test]
+    fn runs_used_again_survive_a_generation() {
+        let mut shaper = Shaper::new(vec![face(FONT_CASCADIAMONO_REGULAR, &[])]);
+        shaper.shape(0, "kept");
+        shaper.shape(0, "dropped");
+        for index in 0..CACHE_CAPACITY {
+            shaper.shape(0, &index.to_string());
+        }
+        // "kept" and "dropped" are now in the previous generation.
+        shaper.shape(0, "kept");
+
+        for index in CACHE_CAPACITY..CACHE_CAPACITY * 2 {
+            shaper.shape(0, &index.to_string());
+        }
+        let face = &shaper.faces[0];
+        assert!(face.previous.contains_key("kept") || face.current.contains_key("kept"));
+        assert!(!face.previous.contains_key("dropped"));
+        assert!(!face.current.contains_key("dropped"));
+        assert!(face.current.len() + face.previous.len() <= CACHE_CAPACITY * 2);
+    }
+}
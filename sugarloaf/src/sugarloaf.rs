@@ -1,22 +1,30 @@
 use crate::components::core::{image::Handle, shapes::Rectangle};
 use crate::components::layer::{self, LayerBrush};
-use crate::components::rect::{Rect, RectBrush};
+use crate::components::post_processing::{self, PostProcessing};
+use crate::components::rect::{builtin, Rect, RectBrush};
+#[cfg(feature = "software")]
+use crate::components::software::Canvas;
 use crate::components::text;
 use crate::context::Context;
 use crate::core::{ImageProperties, RepeatedSugar, Sugar, SugarStack};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::font::fallback::SystemFallback;
 use crate::font::fonts::{SugarloafFont, SugarloafFonts};
 #[cfg(not(target_arch = "wasm32"))]
-use crate::font::loader::Database;
+use crate::font::loader::{self, Database};
+use crate::font::shaping::Shaper;
 use crate::font::Font;
 use crate::font::{
-    FONT_ID_BOLD, FONT_ID_BOLD_ITALIC, FONT_ID_EMOJIS, FONT_ID_ICONS, FONT_ID_ITALIC,
-    FONT_ID_REGULAR, FONT_ID_SYMBOL, FONT_ID_UNICODE,
+    FONT_ID_BOLD, FONT_ID_BOLD_ITALIC, FONT_ID_EMOJIS, FONT_ID_EXTRAS, FONT_ID_ICONS,
+    FONT_ID_ITALIC, FONT_ID_REGULAR, FONT_ID_SYMBOL, FONT_ID_UNICODE,
 };
 use crate::layout::SugarloafLayout;
+use crate::tools::screenshot::{PendingScreenshot, Screenshot};
 use core::fmt::{Debug, Formatter};
-use glyph_brush::ab_glyph::{self, Font as GFont, FontArc, PxScale};
-use glyph_brush::{FontId, GlyphCruncher};
+use glyph_brush::ab_glyph::{self, Font as GFont, FontArc, PxScale, ScaleFont};
+use glyph_brush::{FontId, GlyphCruncher, SectionGlyph};
 use std::collections::HashMap;
+use std::sync::Arc;
 use unicode_width::UnicodeWidthChar;
 
 #[cfg(target_arch = "wasm32")]
@@ -41,25 +49,121 @@ pub trait Renderable: 'static + Sized {
     );
 }
 
+/// Face of a text cell, taking its style into account.
+#[inline]
+fn text_font_id(cached_sugar: &CachedSugar, sugar: &Sugar) -> FontId {
+    if cached_sugar.font_id == FontId(FONT_ID_REGULAR) {
+        if let Some(style) = &sugar.style {
+            if style.is_bold_italic {
+                return FontId(FONT_ID_BOLD_ITALIC);
+            } else if style.is_bold {
+                return FontId(FONT_ID_BOLD);
+            } else if style.is_italic {
+                return FontId(FONT_ID_ITALIC);
+            }
+        }
+    }
+
+    cached_sugar.font_id
+}
+
 #[derive(Copy, Clone, PartialEq)]
 pub struct CachedSugar {
     font_id: FontId,
     char_width: f32,
     monospaced_font_scale: Option<f32>,
+    /// Drawn as a color glyph instead of text.
+    is_color: bool,
+    /// Drawn as rects sized to the cell instead of text.
+    is_builtin: bool,
+}
+
+/// Layout a cached row was stacked with, any difference invalidates it.
+#[derive(Copy, Clone, PartialEq)]
+struct RowKey {
+    text_y: f32,
+    font_bound: (f32, f32),
+    sugar_bound: (f32, f32),
+    scale: f32,
+    text_scale: f32,
+    line_height: f32,
+    screen_position: (f32, f32),
+}
+
+struct CachedRow {
+    key: RowKey,
+    sections: Vec<crate::components::text::OwnedSection>,
+    runs: Vec<ShapedRun>,
+    color_glyphs: Vec<crate::components::text::ColorGlyph>,
+    rects: Vec<Rect>,
+    glyph_rects: Vec<Rect>,
+}
+
+/// Glyphs of a shaped run, already positioned on its cells.
+#[derive(Clone)]
+struct ShapedRun {
+    glyphs: Vec<SectionGlyph>,
+    extra: crate::components::text::Extra,
+    bounds: ab_glyph::Rect,
 }
 
 pub struct Sugarloaf {
     sugar_cache: HashMap<char, CachedSugar>,
-    pub ctx: Context,
+    rows: Vec<Option<CachedRow>>,
+    shaper: Shaper,
+    #[cfg(not(target_arch = "wasm32"))]
+    fallback: SystemFallback,
+    /// Faces of the system fallback already added to the text brush.
+    #[cfg(not(target_arch = "wasm32"))]
+    fallback_fonts: HashMap<loader::ID, FontId>,
+    backend: Backend,
     pub layout: SugarloafLayout,
     text_brush: text::GlyphBrush<()>,
-    rect_brush: RectBrush,
-    layer_brush: LayerBrush,
     rects: Vec<Rect>,
+    /// Rects of builtin glyphs, drawn over every background.
+    glyph_rects: Vec<Rect>,
+    /// Drawn after everything else, covering the text under it.
+    overlay_rects: Vec<Rect>,
+    overlay_texts: Vec<OverlayText>,
     text_y: f32,
+    /// Whether `text_y` was set for the frame by the first row.
+    is_text_y_set: bool,
+    /// Lines between 0 and 1 the rows are moved down by.
+    scroll_offset: f32,
     font_bound: (f32, f32),
     fonts: SugarloafFonts,
     is_text_monospaced: bool,
+    /// Column and line of the cursor, given to custom shaders.
+    cursor_position: Option<(usize, usize)>,
+}
+
+/// Renderer of the frames, the software one is built with
+/// [`Sugarloaf::new_software`].
+enum Backend {
+    Gpu(Box<Gpu>),
+    #[cfg(feature = "software")]
+    Software(Canvas),
+}
+
+struct Gpu {
+    ctx: Context,
+    rect_brush: RectBrush,
+    layer_brush: LayerBrush,
+    post_processing: Option<PostProcessing>,
+    /// Paints frames drawn into a texture for screenshots, when there is
+    /// no custom shader.
+    passthrough: Option<PostProcessing>,
+}
+
+impl Backend {
+    fn build_text_brush(&self, fonts: Vec<FontArc>) -> text::GlyphBrush<()> {
+        let builder = text::GlyphBrushBuilder::using_fonts(fonts);
+        match self {
+            Backend::Gpu(gpu) => builder.build(&gpu.ctx.device, gpu.ctx.format),
+            #[cfg(feature = "software")]
+            Backend::Software(_) => builder.build_software(),
+        }
+    }
 }
 
 #[derive(Debug)]
@@ -84,13 +188,54 @@ impl Sugarloaf {
         power_preference: wgpu::PowerPreference,
         fonts: SugarloafFonts,
         layout: SugarloafLayout,
-        #[allow(unused)] db: Option<&Database>,
+        db: Option<&Arc<Database>>,
     ) -> Result<Sugarloaf, SugarloafWithErrors> {
         let ctx = Context::new(winit_window, power_preference).await;
+        let rect_brush = RectBrush::init(&ctx);
+        let layer_brush = LayerBrush::new(&ctx);
+        let backend = Backend::Gpu(Box::new(Gpu {
+            ctx,
+            rect_brush,
+            layer_brush,
+            post_processing: None,
+            passthrough: None,
+        }));
+
+        Sugarloaf::with_backend(backend, fonts, layout, db)
+    }
+
+    /// Sugarloaf drawing `width` x `height` frames in memory, without a
+    /// window or a GPU. They are read with [`Sugarloaf::render_screenshot`].
+    #[cfg(feature = "software")]
+    pub fn new_software(
+        width: u32,
+        height: u32,
+        scale: f32,
+        fonts: SugarloafFonts,
+        layout: SugarloafLayout,
+        db: Option<&Arc<Database>>,
+    ) -> Result<Sugarloaf, SugarloafWithErrors> {
+        let backend = Backend::Software(Canvas::new(width, height, scale));
+        Sugarloaf::with_backend(backend, fonts, layout, db)
+    }
+
+    fn with_backend(
+        backend: Backend,
+        fonts: SugarloafFonts,
+        layout: SugarloafLayout,
+        #[allow(unused)] db: Option<&Arc<Database>>,
+    ) -> Result<Sugarloaf, SugarloafWithErrors> {
         let mut sugarloaf_errors = None;
 
         #[cfg(not(target_arch = "wasm32"))]
-        let loaded_fonts = Font::new(fonts.to_owned(), db);
+        let db = db.cloned().unwrap_or_else(|| {
+            let mut db = Database::new();
+            db.load_system_fonts();
+            Arc::new(db)
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        let loaded_fonts = Font::new(fonts.to_owned(), Some(&db));
         #[cfg(target_arch = "wasm32")]
         let loaded_fonts = Font::new(fonts.to_owned());
 
@@ -102,34 +247,50 @@ impl Sugarloaf {
         }
 
         let is_monospace = loaded_fonts.text.is_monospace;
-
-        let text_brush = text::GlyphBrushBuilder::using_fonts(vec![
-            loaded_fonts.text.regular,
-            loaded_fonts.text.italic,
-            loaded_fonts.text.bold,
-            loaded_fonts.text.bold_italic,
-            loaded_fonts.symbol,
-            loaded_fonts.emojis,
-            loaded_fonts.unicode,
-            loaded_fonts.icons,
-            loaded_fonts.breadcrumbs,
-        ])
-        .build(&ctx.device, ctx.format);
-        let rect_brush = RectBrush::init(&ctx);
-        let layer_brush = LayerBrush::new(&ctx);
+        let shaper = Shaper::new(Vec::from(loaded_fonts.text.data));
+
+        let mut text_brush = backend.build_text_brush(
+            [
+                loaded_fonts.text.regular,
+                loaded_fonts.text.italic,
+                loaded_fonts.text.bold,
+                loaded_fonts.text.bold_italic,
+                loaded_fonts.symbol,
+                loaded_fonts.emojis,
+                loaded_fonts.unicode,
+                loaded_fonts.icons,
+                loaded_fonts.breadcrumbs,
+            ]
+            .into_iter()
+            .chain(loaded_fonts.extras)
+            .collect(),
+        );
+        for (font_id, color_face) in loaded_fonts.color_faces {
+            text_brush.add_color_font(FontId(font_id), color_face);
+        }
 
         let instance = Sugarloaf {
             sugar_cache: HashMap::new(),
-            layer_brush,
+            rows: vec![],
+            shaper,
+            #[cfg(not(target_arch = "wasm32"))]
+            fallback: SystemFallback::new(db),
+            #[cfg(not(target_arch = "wasm32"))]
+            fallback_fonts: HashMap::new(),
             fonts,
-            ctx,
-            rect_brush,
+            backend,
             rects: vec![],
+            glyph_rects: vec![],
+            overlay_rects: vec![],
+            overlay_texts: vec![],
             text_brush,
             text_y: 0.0,
+            is_text_y_set: false,
+            scroll_offset: 0.0,
             font_bound: (0.0, 0.0),
             layout,
             is_text_monospaced: is_monospace,
+            cursor_position: None,
         };
 
         if let Some(errors) = sugarloaf_errors {
@@ -141,9 +302,18 @@ impl Sugarloaf {
 
     #[allow(unused)]
     pub fn clear(&mut self) {
-        match self.ctx.surface.get_current_texture() {
+        let gpu = match &mut self.backend {
+            Backend::Gpu(gpu) => gpu,
+            #[cfg(feature = "software")]
+            Backend::Software(canvas) => {
+                canvas.clear(self.layout.background_color);
+                return;
+            }
+        };
+
+        match gpu.ctx.surface.get_current_texture() {
             Ok(frame) => {
-                let mut encoder = self.ctx.device.create_command_encoder(
+                let mut encoder = gpu.ctx.device.create_command_encoder(
                     &wgpu::CommandEncoderDescriptor { label: None },
                 );
 
@@ -163,10 +333,10 @@ impl Sugarloaf {
                     })],
                     depth_stencil_attachment: None,
                 });
-                self.ctx.staging_belt.finish();
-                self.ctx.queue.submit(Some(encoder.finish()));
+                gpu.ctx.staging_belt.finish();
+                gpu.ctx.queue.submit(Some(encoder.finish()));
                 frame.present();
-                self.ctx.staging_belt.recall();
+                gpu.ctx.staging_belt.recall();
             }
             Err(error) => {
                 if error == wgpu::SurfaceError::OutOfMemory {
@@ -197,22 +367,33 @@ impl Sugarloaf {
 
             let font = loaded_fonts.0;
             let is_monospace = font.text.is_monospace;
+            self.shaper = Shaper::new(Vec::from(font.text.data));
 
             // Clean font cache per instance
             self.sugar_cache = HashMap::new();
-
-            let text_brush = text::GlyphBrushBuilder::using_fonts(vec![
-                font.text.regular,
-                font.text.italic,
-                font.text.bold,
-                font.text.bold_italic,
-                font.symbol,
-                font.emojis,
-                font.unicode,
-                font.icons,
-                font.breadcrumbs,
-            ])
-            .build(&self.ctx.device, self.ctx.format);
+            self.rows.clear();
+            #[cfg(not(target_arch = "wasm32"))]
+            self.fallback_fonts.clear();
+
+            let mut text_brush = self.backend.build_text_brush(
+                [
+                    font.text.regular,
+                    font.text.italic,
+                    font.text.bold,
+                    font.text.bold_italic,
+                    font.symbol,
+                    font.emojis,
+                    font.unicode,
+                    font.icons,
+                    font.breadcrumbs,
+                ]
+                .into_iter()
+                .chain(font.extras)
+                .collect(),
+            );
+            for (font_id, color_face) in font.color_faces {
+                text_brush.add_color_font(FontId(font_id), color_face);
+            }
             self.text_brush = text_brush;
             self.fonts = fonts;
             self.is_text_monospaced = is_monospace;
@@ -223,15 +404,25 @@ impl Sugarloaf {
 
     #[inline]
     pub fn resize(&mut self, width: u32, height: u32) -> &mut Self {
-        self.ctx.resize(width, height);
+        match &mut self.backend {
+            Backend::Gpu(gpu) => gpu.ctx.resize(width, height),
+            #[cfg(feature = "software")]
+            Backend::Software(canvas) => canvas.resize(width, height),
+        }
         self.layout.resize(width, height).update();
+        self.rows.clear();
         self
     }
 
     #[inline]
     pub fn rescale(&mut self, scale: f32) -> &mut Self {
-        self.ctx.scale = scale;
+        match &mut self.backend {
+            Backend::Gpu(gpu) => gpu.ctx.scale = scale,
+            #[cfg(feature = "software")]
+            Backend::Software(canvas) => canvas.scale = scale,
+        }
         self.layout.rescale(scale).update();
+        self.rows.clear();
         self
     }
 
@@ -269,25 +460,58 @@ impl Sugarloaf {
         #[allow(clippy::unnecessary_to_owned)]
         let fonts: &[FontArc] = &self.text_brush.fonts().to_owned();
         let mut font_id = FontId(FONT_ID_REGULAR);
+        let char_width = sugar.content.width().unwrap_or(1) as f32;
+        let is_builtin =
+            self.fonts.builtin_box_drawing && builtin::is_builtin(sugar.content);
+
+        // Wide chars covered by a color emoji font are emojis, which other
+        // fonts may have as plain symbols.
+        let font_ids =
+            if char_width > 1. && self.text_brush.is_color_font(FontId(FONT_ID_EMOJIS)) {
+                [
+                    FONT_ID_EMOJIS,
+                    FONT_ID_REGULAR,
+                    FONT_ID_SYMBOL,
+                    FONT_ID_UNICODE,
+                    FONT_ID_ICONS,
+                ]
+            } else {
+                [
+                    FONT_ID_REGULAR,
+                    FONT_ID_SYMBOL,
+                    FONT_ID_UNICODE,
+                    FONT_ID_ICONS,
+                    FONT_ID_EMOJIS,
+                ]
+            };
 
-        let font_ids = [
-            FONT_ID_REGULAR,
-            FONT_ID_SYMBOL,
-            FONT_ID_UNICODE,
-            FONT_ID_ICONS,
-            FONT_ID_EMOJIS,
-        ];
-
-        for id in font_ids {
-            let found_glyph_id = fonts[id].glyph_id(sugar.content);
-            if found_glyph_id != ab_glyph::GlyphId(0) {
-                font_id = FontId(id);
-                break;
+        // The fixed fonts, then the extras and the system faces loaded so far.
+        let mut is_found = is_builtin;
+        if !is_found {
+            for id in font_ids.into_iter().chain(FONT_ID_EXTRAS..fonts.len()) {
+                let found_glyph_id = fonts[id].glyph_id(sugar.content);
+                if found_glyph_id != ab_glyph::GlyphId(0) {
+                    font_id = FontId(id);
+                    is_found = true;
+                    break;
+                }
             }
         }
 
+        #[cfg(not(target_arch = "wasm32"))]
+        if !is_found {
+            if let Some(fallback_font_id) = self.fallback_font_id(sugar.content) {
+                font_id = fallback_font_id;
+                is_found = true;
+            }
+        }
+
+        if !is_found {
+            log::warn!("no font has {:?}", sugar.content);
+        }
+
         let mut monospaced_font_scale = None;
-        let char_width = sugar.content.width().unwrap_or(1) as f32;
+        let is_color = self.text_brush.is_color_font(font_id);
 
         let mut scale_target: f32 = 0.;
 
@@ -297,7 +521,7 @@ impl Sugarloaf {
                 scale_target = self.layout.sugarwidth;
             }
 
-            FontId(FONT_ID_UNICODE) | FontId(FONT_ID_SYMBOL) => {
+            FontId(FONT_ID_UNICODE | FONT_ID_SYMBOL | FONT_ID_EXTRAS..) => {
                 scale_target = if char_width > 1. {
                     self.layout.sugarwidth * 2.0
                 } else {
@@ -333,36 +557,155 @@ impl Sugarloaf {
             font_id,
             char_width,
             monospaced_font_scale,
+            is_color,
+            is_builtin,
         };
 
-        self.sugar_cache.insert(
-            sugar.content,
-            CachedSugar {
-                font_id,
-                char_width,
-                monospaced_font_scale,
-            },
-        );
+        self.sugar_cache.insert(sugar.content, cached_sugar);
 
         cached_sugar
     }
 
+    /// Face of the system covering `content`, loaded into the text brush
+    /// the first time it's needed.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn fallback_font_id(&mut self, content: char) -> Option<FontId> {
+        let id = self.fallback.find(content)?;
+        if let Some(font_id) = self.fallback_fonts.get(&id) {
+            return Some(*font_id);
+        }
+
+        let (font, color_face) = self.fallback.load(id)?;
+        let font_id = self.text_brush.add_font(font);
+        if let Some(color_face) = color_face {
+            self.text_brush.add_color_font(font_id, color_face);
+        }
+        self.fallback_fonts.insert(id, font_id);
+        Some(font_id)
+    }
+
+    #[inline]
+    pub fn stack(&mut self, stack: SugarStack) {
+        let row = self.build_row(stack);
+        self.queue_row(&row);
+    }
+
+    /// Same as [`Sugarloaf::stack`], but keeps the result around so the
+    /// next frame can reuse it with [`Sugarloaf::stack_cached_row`].
+    #[inline]
+    pub fn stack_row(&mut self, line: usize, stack: SugarStack) {
+        let row = self.build_row(stack);
+        self.queue_row(&row);
+
+        if self.rows.len() <= line {
+            self.rows.resize_with(line + 1, || None);
+        }
+        self.rows[line] = Some(row);
+    }
+
+    /// Queue the row stacked for `line` in a previous frame.
+    ///
+    /// Returns false if there is nothing cached for `line` or the layout has
+    /// changed since, in that case the row has to be stacked again.
+    #[inline]
+    pub fn stack_cached_row(&mut self, line: usize) -> bool {
+        let key = self.row_key();
+        let row = match self.rows.get_mut(line).and_then(Option::take) {
+            Some(row) if row.key == key => row,
+            _ => return false,
+        };
+
+        self.queue_row(&row);
+        self.rows[line] = Some(row);
+        true
+    }
+
+    /// Drop every row kept by [`Sugarloaf::stack_row`].
+    #[inline]
+    pub fn clear_row_cache(&mut self) {
+        self.rows.clear();
+    }
+
+    /// Move the rows down by a fraction of a line, for scrolling pixel by
+    /// pixel. The row partly shown above them comes from
+    /// [`Sugarloaf::stack_above`].
+    #[inline]
+    pub fn set_scroll_offset(&mut self, offset: f32) {
+        self.scroll_offset = offset.clamp(0., 1.);
+    }
+
+    #[inline]
+    pub fn scroll_offset(&self) -> f32 {
+        self.scroll_offset
+    }
+
+    /// Queue the row right above the first one, has to be called before
+    /// any other row of the frame.
+    #[inline]
+    pub fn stack_above(&mut self, stack: SugarStack) {
+        self.row_key();
+        self.text_y -= self.font_bound.1;
+        self.stack(stack);
+    }
+
+    #[inline]
+    fn row_key(&mut self) -> RowKey {
+        if !self.is_text_y_set {
+            self.text_y = self.layout.style.screen_position.1
+                + self.scroll_offset * self.font_bound.1;
+            self.is_text_y_set = true;
+        }
+
+        RowKey {
+            text_y: self.text_y,
+            font_bound: self.font_bound,
+            sugar_bound: (self.layout.sugarwidth, self.layout.sugarheight),
+            scale: self.get_scale(),
+            text_scale: self.layout.style.text_scale,
+            line_height: self.layout.line_height,
+            screen_position: self.layout.style.screen_position,
+        }
+    }
+
+    #[inline]
+    fn queue_row(&mut self, row: &CachedRow) {
+        for section in &row.sections {
+            self.text_brush.queue(section);
+        }
+        for run in &row.runs {
+            self.text_brush.queue_pre_positioned(
+                run.glyphs.clone(),
+                vec![run.extra],
+                run.bounds,
+            );
+        }
+        for color_glyph in &row.color_glyphs {
+            self.text_brush.queue_color_glyph(*color_glyph);
+        }
+        self.rects.extend_from_slice(&row.rects);
+        self.glyph_rects.extend_from_slice(&row.glyph_rects);
+        self.text_y += self.font_bound.1;
+    }
+
     #[inline]
-    pub fn stack(&mut self, mut stack: SugarStack) {
+    fn build_row(&mut self, mut stack: SugarStack) -> CachedRow {
+        let key = self.row_key();
         let mut x = 0.;
         let mut sections = vec![];
+        let mut color_glyphs = vec![];
+        let mut rects = vec![];
+        let mut glyph_rects = vec![];
         let mod_pos_y = self.layout.style.screen_position.1;
-        let mod_text_y = self.layout.sugarheight * self.ctx.scale / 2.;
+        let mod_text_y = self.layout.sugarheight * self.get_scale() / 2.;
 
-        let sugar_x = self.layout.sugarwidth * self.ctx.scale;
+        let sugar_x = self.layout.sugarwidth * self.get_scale();
         let sugar_width = self.layout.sugarwidth * 2.;
 
         let mut repeated = RepeatedSugar::new(0);
 
-        let text_bound = self.layout.sugarheight * self.ctx.scale;
-        if self.text_y == 0.0 {
-            self.text_y = self.layout.style.screen_position.1;
-        }
+        let text_bound = self.layout.sugarheight * self.get_scale();
+        let (shaped, runs) =
+            self.shape_row(&mut stack, mod_text_y + self.text_y + mod_pos_y);
 
         let size = stack.len();
         for i in 0..size {
@@ -373,6 +716,10 @@ impl Sugarloaf {
             let cached_sugar: CachedSugar = self.get_font_id(&mut stack[i]);
             if i < size - 1
                 && cached_sugar.char_width <= 1.
+                && !cached_sugar.is_color
+                && !cached_sugar.is_builtin
+                && !shaped[i]
+                && !shaped[i + 1]
                 && stack[i].content == stack[i + 1].content
                 && stack[i].foreground_color == stack[i + 1].foreground_color
                 && stack[i].background_color == stack[i + 1].background_color
@@ -386,18 +733,7 @@ impl Sugarloaf {
 
             repeated.set_reset_on_next();
 
-            let mut font_id = cached_sugar.font_id;
-            if cached_sugar.font_id == FontId(FONT_ID_REGULAR) {
-                if let Some(style) = &stack[i].style {
-                    if style.is_bold_italic {
-                        font_id = FontId(FONT_ID_BOLD_ITALIC);
-                    } else if style.is_bold {
-                        font_id = FontId(FONT_ID_BOLD);
-                    } else if style.is_italic {
-                        font_id = FontId(FONT_ID_ITALIC);
-                    }
-                }
-            }
+            let font_id = text_font_id(&cached_sugar, &stack[i]);
 
             if cached_sugar.char_width > 1. {
                 sugar_char_width += 1.;
@@ -466,11 +802,43 @@ impl Sugarloaf {
                     .h_align(glyph_brush::HorizontalAlign::Left),
             };
 
-            sections.push(section);
+            // Text of shaped cells is drawn by the glyphs of their run,
+            // color and builtin glyphs fill the cells they take.
+            if cached_sugar.is_color {
+                let glyph_id = self.text_brush.fonts()[font_id.0]
+                    .glyph_id(stack[i].content)
+                    .0;
+                color_glyphs.push(crate::components::text::ColorGlyph {
+                    font_id,
+                    glyph_id,
+                    bounds: ab_glyph::Rect {
+                        min: ab_glyph::point(section_pos_x, rect_pos_y),
+                        max: ab_glyph::point(
+                            section_pos_x + add_pos_x,
+                            rect_pos_y + text_bound,
+                        ),
+                    },
+                });
+            } else if cached_sugar.is_builtin {
+                let cell = builtin::Cell {
+                    x: section_pos_x,
+                    y: rect_pos_y,
+                    width: add_pos_x,
+                    height: text_bound,
+                };
+                glyph_rects.extend(builtin::rects(
+                    stack[i].content,
+                    cell,
+                    fg_color,
+                    self.get_scale(),
+                ));
+            } else if !shaped[i] {
+                sections.push(section);
+            }
 
-            let scaled_rect_pos_x = section_pos_x / self.ctx.scale;
-            let scaled_rect_pos_y = rect_pos_y / self.ctx.scale;
-            self.rects.push(Rect {
+            let scaled_rect_pos_x = section_pos_x / self.get_scale();
+            let scaled_rect_pos_y = rect_pos_y / self.get_scale();
+            rects.push(Rect {
                 position: [scaled_rect_pos_x, scaled_rect_pos_y],
                 color: bg_color,
                 size: [width_bound * quantity as f32, self.layout.sugarheight],
@@ -480,7 +848,7 @@ impl Sugarloaf {
                 // TODO:
                 //  let dec_position_y = match decoration.position.1 {
                 //     SugarDecorationPositionY::Bottom(pos_decoration_y) => {
-                //         scaled_rect_pos_y + ((pos_decoration_y) * self.ctx.scale)
+                //         scaled_rect_pos_y + ((pos_decoration_y) * self.get_scale())
                 //     }
                 //     SugarDecorationPositionY::Top(pos_decoration_y) => {
                 //         scaled_rect_pos_y + pos_decoration_y
@@ -495,11 +863,11 @@ impl Sugarloaf {
                 // A decoration with is_content_positioned has the width and height based on font_size
                 // and in this way is not affected by line_height (useful for decorations like Block and Beam)
                 // if decoration.is_content_positioned {
-                //     self.rects.push(Rect {
+                //     rects.push(Rect {
                 //         position: [
                 //             (scaled_rect_pos_x
                 //                 + (add_pos_x * decoration.relative_position.0)
-                //                     / self.ctx.scale),
+                //                     / self.get_scale()),
                 //             scaled_rect_pos_y,
                 //         ],
                 //         color: decoration.color,
@@ -509,11 +877,11 @@ impl Sugarloaf {
                 //         ],
                 //     });
                 // } else {
-                self.rects.push(Rect {
+                rects.push(Rect {
                     position: [
                         (scaled_rect_pos_x
                             + (add_pos_x * decoration.relative_position.0)
-                                / self.ctx.scale),
+                                / self.get_scale()),
                         dec_pos_y,
                     ],
                     color: decoration.color,
@@ -532,20 +900,170 @@ impl Sugarloaf {
             x += add_pos_x;
         }
 
-        for section in sections {
-            self.text_brush.queue(&section);
+        CachedRow {
+            key,
+            sections,
+            runs,
+            color_glyphs,
+            rects,
+            glyph_rects,
         }
-        self.text_y += self.font_bound.1;
+    }
+
+    /// Shape runs of cells drawn with the same text face and color, `y` is
+    /// the vertical center of the row.
+    ///
+    /// Only runs the font actually changes are kept, their glyphs are
+    /// positioned on the cells they came from so the grid stays aligned.
+    /// Returns which cells are covered by those runs.
+    fn shape_row(
+        &mut self,
+        stack: &mut SugarStack,
+        y: f32,
+    ) -> (Vec<bool>, Vec<ShapedRun>) {
+        let sugar_x = self.layout.sugarwidth * self.get_scale();
+        let sugar_width = self.layout.sugarwidth * 2.;
+        let text_bound = self.layout.sugarheight * self.get_scale();
+        let text_scale = self.layout.style.text_scale;
+
+        // Position of every cell and the face of the ones which can be shaped,
+        // spaces never take part of a ligature.
+        let mut cells = Vec::with_capacity(stack.len());
+        let mut x = self.layout.style.screen_position.0;
+        for sugar in stack.iter_mut() {
+            let cached_sugar = self.get_font_id(sugar);
+            let is_text = cached_sugar.font_id == FontId(FONT_ID_REGULAR)
+                && cached_sugar.char_width <= 1.
+                && !cached_sugar.is_builtin
+                && cached_sugar
+                    .monospaced_font_scale
+                    .map_or(true, |scale| scale == text_scale)
+                && sugar.content != ' ';
+            cells.push((x, is_text.then(|| text_font_id(&cached_sugar, sugar))));
+
+            x += if cached_sugar.char_width > 1. {
+                sugar_x * 2.
+            } else {
+                sugar_x
+            };
+        }
+
+        let mut shaped = vec![false; stack.len()];
+        let mut runs = vec![];
+        let mut start = 0;
+        while start < stack.len() {
+            let font_id = match cells[start].1 {
+                Some(font_id) => font_id,
+                None => {
+                    start += 1;
+                    continue;
+                }
+            };
+
+            let color = stack[start].foreground_color;
+            let end = (start..stack.len())
+                .find(|i| {
+                    cells[*i].1 != Some(font_id) || stack[*i].foreground_color != color
+                })
+                .unwrap_or(stack.len());
+            let run = start..end;
+            start = end;
+
+            // Ligatures take at least two cells, features set by the user can
+            // also replace single glyphs.
+            if run.len() < 2 && !self.shaper.has_features(font_id.0) {
+                continue;
+            }
+
+            let text: String = stack[run.clone()]
+                .iter()
+                .map(|sugar| sugar.content)
+                .collect();
+            let glyphs = match self.shaper.shape(font_id.0, &text) {
+                Some(glyphs) => glyphs.to_vec(),
+                None => continue,
+            };
+
+            let scale = PxScale::from(text_scale);
+            let extra = crate::components::text::Extra { color, z: 0.0 };
+
+            // The baseline is taken from laying out the first cell the same
+            // way unshaped cells are.
+            let first = &text[..text.chars().next().map_or(0, char::len_utf8)];
+            let reference = crate::components::text::Section {
+                screen_position: (cells[run.start].0, y),
+                bounds: (sugar_width, text_bound),
+                text: vec![crate::components::text::Text {
+                    text: first,
+                    scale,
+                    font_id,
+                    extra,
+                }],
+                layout: glyph_brush::Layout::default_single_line()
+                    .v_align(glyph_brush::VerticalAlign::Center)
+                    .h_align(glyph_brush::HorizontalAlign::Left),
+            };
+            let baseline = self
+                .text_brush
+                .glyphs(&reference)
+                .next()
+                .map_or(y, |glyph| glyph.glyph.position.y);
+
+            let font = self.text_brush.fonts()[font_id.0].as_scaled(scale);
+            let (h_scale, v_scale) = (font.h_scale_factor(), font.v_scale_factor());
+            let offsets: Vec<usize> =
+                text.char_indices().map(|(offset, _)| offset).collect();
+            let glyphs = glyphs
+                .iter()
+                .map(|glyph| SectionGlyph {
+                    section_index: 0,
+                    byte_index: offsets[glyph.cell],
+                    glyph: ab_glyph::Glyph {
+                        id: ab_glyph::GlyphId(glyph.id),
+                        scale,
+                        position: ab_glyph::point(
+                            cells[run.start + glyph.cell].0
+                                + glyph.x_offset as f32 * h_scale,
+                            baseline - glyph.y_offset as f32 * v_scale,
+                        ),
+                    },
+                    font_id,
+                })
+                .collect();
+
+            shaped[run.clone()].fill(true);
+            runs.push(ShapedRun {
+                glyphs,
+                extra,
+                bounds: ab_glyph::Rect {
+                    min: ab_glyph::point(cells[run.start].0, y - text_bound / 2.),
+                    max: ab_glyph::point(
+                        cells[run.end - 1].0 + sugar_x,
+                        y + text_bound / 2.,
+                    ),
+                },
+            });
+        }
+
+        (shaped, runs)
     }
 
     #[inline]
-    pub fn get_context(&self) -> &Context {
-        &self.ctx
+    pub fn get_context(&self) -> Option<&Context> {
+        match &self.backend {
+            Backend::Gpu(gpu) => Some(&gpu.ctx),
+            #[cfg(feature = "software")]
+            Backend::Software(_) => None,
+        }
     }
 
     #[inline]
     pub fn get_scale(&self) -> f32 {
-        self.ctx.scale
+        match &self.backend {
+            Backend::Gpu(gpu) => gpu.ctx.scale,
+            #[cfg(feature = "software")]
+            Backend::Software(canvas) => canvas.scale,
+        }
     }
 
     #[inline]
@@ -591,6 +1109,57 @@ impl Sugarloaf {
         self
     }
 
+    /// Set the WGSL shader the frame goes through before being presented,
+    /// the previous one is kept if it fails to compile.
+    pub fn set_custom_shader(&mut self, source: Option<&str>) -> Result<(), String> {
+        let gpu = match &mut self.backend {
+            Backend::Gpu(gpu) => gpu,
+            // Only checked, the software renderer can't run them.
+            #[cfg(feature = "software")]
+            Backend::Software(_) => {
+                return source.map_or(Ok(()), |source| {
+                    post_processing::compile(source).map(|_| ())
+                })
+            }
+        };
+
+        match source {
+            Some(source) => {
+                if gpu.post_processing.as_ref().map(|pp| pp.source()) != Some(source) {
+                    gpu.post_processing = Some(PostProcessing::new(&gpu.ctx, source)?);
+                }
+            }
+            None => gpu.post_processing = None,
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub fn set_cursor_position(&mut self, position: Option<(usize, usize)>) {
+        self.cursor_position = position;
+    }
+
+    /// Cell of the cursor in pixels, following the positions of
+    /// [`Sugarloaf::build_row`].
+    #[inline]
+    fn cursor_rect(&self) -> [f32; 4] {
+        match self.cursor_position {
+            Some((column, line)) => {
+                let sugar_x = self.layout.sugarwidth * self.get_scale();
+                let screen_position = self.layout.style.screen_position;
+                [
+                    screen_position.0 + column as f32 * sugar_x,
+                    screen_position.1 * 2.
+                        + (line as f32 + self.scroll_offset) * self.font_bound.1,
+                    sugar_x,
+                    self.layout.sugarheight * self.get_scale(),
+                ]
+            }
+            None => [0.; 4],
+        }
+    }
+
     #[inline]
     pub fn set_background_image(&mut self, image: &ImageProperties) -> &mut Self {
         let handle = Handle::from_path(image.path.to_owned());
@@ -617,66 +1186,38 @@ impl Sugarloaf {
     pub fn calculate_bounds(&mut self) {
         self.reset_state();
         self.rects = vec![];
+        self.glyph_rects = vec![];
 
-        match self.ctx.surface.get_current_texture() {
-            Ok(frame) => {
-                let mut encoder = self.ctx.device.create_command_encoder(
-                    &wgpu::CommandEncoderDescriptor { label: None },
-                );
-
-                let view = &frame
-                    .texture
-                    .create_view(&wgpu::TextureViewDescriptor::default());
-
-                encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-                    label: Some("sugarloaf::init -> Clear frame"),
-                    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                        view,
-                        resolve_target: None,
-                        ops: wgpu::Operations {
-                            load: wgpu::LoadOp::Clear(self.layout.background_color),
-                            store: true,
-                        },
-                    })],
-                    depth_stencil_attachment: None,
-                });
-
-                // Every time a font size change the cached bounds also changes
-                self.sugar_cache = HashMap::new();
+        // Every time a font size change the cached bounds also changes
+        self.sugar_cache = HashMap::new();
+        self.rows.clear();
 
-                let text_scale = self.layout.style.text_scale;
-                // Bounds are defined in runtime
-                if self.is_text_monospaced {
-                    self.font_bound =
-                        self.get_font_bounds(' ', FontId(FONT_ID_REGULAR), text_scale);
-                } else {
-                    self.font_bound =
-                        self.get_font_bounds('-', FontId(FONT_ID_REGULAR), text_scale);
-                }
+        let text_scale = self.layout.style.text_scale;
+        // Bounds are defined in runtime
+        if self.is_text_monospaced {
+            self.font_bound =
+                self.get_font_bounds(' ', FontId(FONT_ID_REGULAR), text_scale);
+        } else {
+            self.font_bound =
+                self.get_font_bounds('-', FontId(FONT_ID_REGULAR), text_scale);
+        }
 
-                self.layout.sugarwidth = self.font_bound.0;
-                self.layout.sugarheight = self.font_bound.1;
+        self.layout.sugarwidth = self.font_bound.0;
+        self.layout.sugarheight = self.font_bound.1;
 
-                self.layout.sugarwidth /= self.ctx.scale;
-                self.layout.sugarheight /= self.ctx.scale;
+        self.layout.sugarwidth /= self.get_scale();
+        self.layout.sugarheight /= self.get_scale();
 
-                self.layout
-                    .update_columns_lines_per_font_bound(self.font_bound.0);
+        self.layout
+            .update_columns_lines_per_font_bound(self.font_bound.0);
 
-                self.ctx.queue.submit(Some(encoder.finish()));
-                frame.present();
-            }
-            Err(error) => {
-                if error == wgpu::SurfaceError::OutOfMemory {
-                    panic!("Swapchain error: {error}. Rendering cannot continue.")
-                }
-            }
-        }
+        self.clear();
     }
 
     #[inline]
     fn reset_state(&mut self) {
         self.text_y = 0.0;
+        self.is_text_y_set = false;
     }
 
     // pub fn bytes(&self, width: u32, height: u32) -> Vec<u8> {
@@ -754,50 +1295,117 @@ impl Sugarloaf {
         color: [f32; 4],
         single_line: bool,
     ) -> &mut Self {
-        let font_id = FontId(font_id_usize);
-
-        let text = crate::components::text::Text {
-            text: &text_str,
-            scale: PxScale::from(scale * self.ctx.scale),
-            font_id,
-            extra: crate::components::text::Extra { color, z: 0.0 },
-        };
-
-        let layout = if single_line {
-            glyph_brush::Layout::default_single_line()
-                .v_align(glyph_brush::VerticalAlign::Center)
-                .h_align(glyph_brush::HorizontalAlign::Left)
-        } else {
-            glyph_brush::Layout::default()
-                .v_align(glyph_brush::VerticalAlign::Center)
-                .h_align(glyph_brush::HorizontalAlign::Left)
+        let text = OverlayText {
+            pos,
+            text: text_str,
+            font_id: font_id_usize,
+            scale,
+            color,
+            single_line,
         };
+        let scale = self.get_scale();
+        queue_text(&mut self.text_brush, &self.layout, scale, &text);
+        self
+    }
 
-        let section = &crate::components::text::Section {
-            screen_position: (pos.0 * self.ctx.scale, pos.1 * self.ctx.scale),
-            bounds: (self.layout.width, self.layout.height),
-            text: vec![text],
-            layout,
-        };
+    /// Rects drawn over the text of the frame, like the background of a
+    /// prompt shown on top of the terminal.
+    #[inline]
+    pub fn overlay_rects(&mut self, mut instances: Vec<Rect>) -> &mut Self {
+        self.overlay_rects.append(&mut instances);
+        self
+    }
 
-        self.text_brush.queue(section);
+    /// Like [`Sugarloaf::text`], drawn over the [`Sugarloaf::overlay_rects`].
+    #[inline]
+    pub fn overlay_text(
+        &mut self,
+        pos: (f32, f32),
+        text: String,
+        font_id: usize,
+        scale: f32,
+        color: [f32; 4],
+        single_line: bool,
+    ) -> &mut Self {
+        self.overlay_texts.push(OverlayText {
+            pos,
+            text,
+            font_id,
+            scale,
+            color,
+            single_line,
+        });
         self
     }
 
     #[inline]
     pub fn render(&mut self) {
+        self.render_frame(false);
+    }
+
+    /// Render like [`Sugarloaf::render`] and read the frame back. Custom
+    /// shaders are left out of it, their output changes with time.
+    #[cfg(not(target_arch = "wasm32"))]
+    pub fn render_screenshot(&mut self) -> Option<Screenshot> {
+        self.render_frame(true)
+    }
+
+    fn render_frame(&mut self, screenshot: bool) -> Option<Screenshot> {
         self.reset_state();
+        let cursor = self.cursor_rect();
+        let gpu = match &mut self.backend {
+            Backend::Gpu(gpu) => gpu,
+            #[cfg(feature = "software")]
+            Backend::Software(canvas) => {
+                canvas.clear(self.layout.background_color);
+                self.rects.append(&mut self.glyph_rects);
+                canvas.fill_rects(&self.rects);
+                self.rects = vec![];
+                let _ = self.text_brush.draw_queued_software(canvas);
+
+                if !self.overlay_rects.is_empty() || !self.overlay_texts.is_empty() {
+                    canvas.fill_rects(&self.overlay_rects);
+                    for text in self.overlay_texts.drain(..) {
+                        queue_text(
+                            &mut self.text_brush,
+                            &self.layout,
+                            canvas.scale,
+                            &text,
+                        );
+                    }
+                    self.overlay_rects = vec![];
+                    let _ = self.text_brush.draw_queued_software(canvas);
+                }
+                return screenshot.then(|| canvas.frame());
+            }
+        };
+        let mut pending_screenshot = None;
 
-        match self.ctx.surface.get_current_texture() {
+        match gpu.ctx.surface.get_current_texture() {
             Ok(frame) => {
-                let mut encoder = self.ctx.device.create_command_encoder(
+                let mut encoder = gpu.ctx.device.create_command_encoder(
                     &wgpu::CommandEncoderDescriptor { label: None },
                 );
 
-                let view = &frame
+                let frame_view = frame
                     .texture
                     .create_view(&wgpu::TextureViewDescriptor::default());
 
+                // With a custom shader everything is drawn into its texture
+                // and it paints the frame, screenshots take the same path
+                // to read that texture.
+                let is_custom_shader = gpu.post_processing.is_some();
+                let mut post_processing = gpu.post_processing.take();
+                if screenshot && post_processing.is_none() {
+                    post_processing = gpu.passthrough.take().or_else(|| {
+                        PostProcessing::new(&gpu.ctx, post_processing::PASSTHROUGH).ok()
+                    });
+                }
+                let view = match &mut post_processing {
+                    Some(pp) => pp.frame(&gpu.ctx),
+                    None => &frame_view,
+                };
+
                 encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
                     label: Some("sugarloaf::render -> Clear frame"),
                     color_attachments: &[Some(wgpu::RenderPassColorAttachment {
@@ -812,38 +1420,94 @@ impl Sugarloaf {
                 });
 
                 if let Some(bg_image) = &self.layout.background_image {
-                    self.layer_brush.prepare_ref(
-                        &mut encoder,
-                        &mut self.ctx,
-                        &[bg_image],
-                    );
+                    gpu.layer_brush
+                        .prepare_ref(&mut encoder, &mut gpu.ctx, &[bg_image]);
 
-                    self.layer_brush
+                    gpu.layer_brush
                         .render_with_encoder(0, view, &mut encoder, None);
                 }
 
-                self.rect_brush.render(
+                // Backgrounds overlap the cells around them, builtin glyphs
+                // go after all of them to keep their edges.
+                self.rects.append(&mut self.glyph_rects);
+                gpu.rect_brush.render(
                     &mut encoder,
                     view,
-                    (self.ctx.size.width, self.ctx.size.height),
+                    (gpu.ctx.size.width, gpu.ctx.size.height),
                     &self.rects,
-                    &mut self.ctx,
+                    &mut gpu.ctx,
                 );
 
                 self.rects = vec![];
 
                 let _ = self.text_brush.draw_queued(
-                    &self.ctx.device,
-                    &mut self.ctx.staging_belt,
+                    &gpu.ctx.device,
+                    &mut gpu.ctx.staging_belt,
                     &mut encoder,
                     view,
-                    (self.ctx.size.width, self.ctx.size.height),
+                    (gpu.ctx.size.width, gpu.ctx.size.height),
                 );
 
-                self.ctx.staging_belt.finish();
-                self.ctx.queue.submit(Some(encoder.finish()));
+                // The overlay goes in a second pass, its rects have to cover
+                // the text drawn so far.
+                if !self.overlay_rects.is_empty() || !self.overlay_texts.is_empty() {
+                    gpu.rect_brush.render(
+                        &mut encoder,
+                        view,
+                        (gpu.ctx.size.width, gpu.ctx.size.height),
+                        &self.overlay_rects,
+                        &mut gpu.ctx,
+                    );
+                    self.overlay_rects = vec![];
+
+                    for text in self.overlay_texts.drain(..) {
+                        queue_text(
+                            &mut self.text_brush,
+                            &self.layout,
+                            gpu.ctx.scale,
+                            &text,
+                        );
+                    }
+                    let _ = self.text_brush.draw_queued(
+                        &gpu.ctx.device,
+                        &mut gpu.ctx.staging_belt,
+                        &mut encoder,
+                        view,
+                        (gpu.ctx.size.width, gpu.ctx.size.height),
+                    );
+                }
+
+                if let Some(pp) = &mut post_processing {
+                    pp.render(
+                        &mut encoder,
+                        &frame_view,
+                        &mut gpu.ctx,
+                        cursor,
+                        self.layout.background_color,
+                    );
+                }
+                if screenshot {
+                    pending_screenshot = post_processing
+                        .as_ref()
+                        .and_then(|pp| pp.frame_texture())
+                        .and_then(|texture| {
+                            PendingScreenshot::copy(
+                                &gpu.ctx.device,
+                                &mut encoder,
+                                texture,
+                            )
+                        });
+                }
+                if is_custom_shader {
+                    gpu.post_processing = post_processing;
+                } else if screenshot {
+                    gpu.passthrough = post_processing;
+                }
+
+                gpu.ctx.staging_belt.finish();
+                gpu.ctx.queue.submit(Some(encoder.finish()));
                 frame.present();
-                self.ctx.staging_belt.recall();
+                gpu.ctx.staging_belt.recall();
             }
             Err(error) => {
                 if error == wgpu::SurfaceError::OutOfMemory {
@@ -851,5 +1515,53 @@ impl Sugarloaf {
                 }
             }
         }
+
+        pending_screenshot.and_then(|pending| pending.read(&gpu.ctx.device))
     }
 }
+
+/// Text queued with [`Sugarloaf::text`] or kept for the overlay.
+struct OverlayText {
+    pos: (f32, f32),
+    text: String,
+    font_id: usize,
+    scale: f32,
+    color: [f32; 4],
+    single_line: bool,
+}
+
+fn queue_text(
+    text_brush: &mut text::GlyphBrush<()>,
+    layout: &SugarloafLayout,
+    scale: f32,
+    overlay_text: &OverlayText,
+) {
+    let text = crate::components::text::Text {
+        text: &overlay_text.text,
+        scale: PxScale::from(overlay_text.scale * scale),
+        font_id: FontId(overlay_text.font_id),
+        extra: crate::components::text::Extra {
+            color: overlay_text.color,
+            z: 0.0,
+        },
+    };
+
+    let glyph_layout = if overlay_text.single_line {
+        glyph_brush::Layout::default_single_line()
+            .v_align(glyph_brush::VerticalAlign::Center)
+            .h_align(glyph_brush::HorizontalAlign::Left)
+    } else {
+        glyph_brush::Layout::default()
+            .v_align(glyph_brush::VerticalAlign::Center)
+            .h_align(glyph_brush::HorizontalAlign::Left)
+    };
+
+    let section = &crate::components::text::Section {
+        screen_position: (overlay_text.pos.0 * scale, overlay_text.pos.1 * scale),
+        bounds: (layout.width, layout.height),
+        text: vec![text],
+        layout: glyph_layout,
+    };
+
+    text_brush.queue(section);
+}
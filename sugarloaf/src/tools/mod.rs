@@ -1,4 +1,5 @@
 pub mod counter;
+pub mod screenshot;
 
 #[cfg(target_arch = "wasm32")]
 pub const CANVAS_ELEMENT_ID: &str = "sugarloaf-canvas";
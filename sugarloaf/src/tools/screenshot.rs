@@ -0,0 +1,189 @@
+use std::path::Path;
+
+/// Frame read back from the GPU, as RGBA rows from the top left.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Screenshot {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl Screenshot {
+    pub fn save_png<P: AsRef<Path>>(&self, path: P) -> Result<(), String> {
+        image::save_buffer_with_format(
+            path,
+            &self.data,
+            self.width,
+            self.height,
+            image::ColorType::Rgba8,
+            image::ImageFormat::Png,
+        )
+        .map_err(|err| err.to_string())
+    }
+}
+
+/// Copy of a texture on its way to the CPU, rows are padded to
+/// `COPY_BYTES_PER_ROW_ALIGNMENT`.
+pub(crate) struct PendingScreenshot {
+    buffer: wgpu::Buffer,
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    is_bgra: bool,
+}
+
+impl PendingScreenshot {
+    /// Record the copy of `texture`, only 8-bit RGBA and BGRA formats can
+    /// be read.
+    pub fn copy(
+        device: &wgpu::Device,
+        encoder: &mut wgpu::CommandEncoder,
+        texture: &wgpu::Texture,
+    ) -> Option<PendingScreenshot> {
+        let is_bgra = match texture.format() {
+            wgpu::TextureFormat::Bgra8Unorm | wgpu::TextureFormat::Bgra8UnormSrgb => true,
+            wgpu::TextureFormat::Rgba8Unorm | wgpu::TextureFormat::Rgba8UnormSrgb => {
+                false
+            }
+            format => {
+                log::warn!("screenshots of {format:?} textures aren't supported");
+                return None;
+            }
+        };
+
+        let (width, height) = (texture.width(), texture.height());
+        let align = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+        let padded_bytes_per_row = (width * 4 + align - 1) / align * align;
+
+        let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("screenshot"),
+            size: padded_bytes_per_row as u64 * height as u64,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+
+        encoder.copy_texture_to_buffer(
+            wgpu::ImageCopyTexture {
+                texture,
+                mip_level: 0,
+                origin: wgpu::Origin3d::ZERO,
+                aspect: wgpu::TextureAspect::All,
+            },
+            wgpu::ImageCopyBuffer {
+                buffer: &buffer,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: Some(padded_bytes_per_row),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+        );
+
+        Some(PendingScreenshot {
+            buffer,
+            width,
+            height,
+            padded_bytes_per_row,
+            is_bgra,
+        })
+    }
+
+    /// Wait for the copy, it has to be submitted before.
+    pub fn read(self, device: &wgpu::Device) -> Option<Screenshot> {
+        let slice = self.buffer.slice(..);
+        let (sender, receiver) = std::sync::mpsc::channel();
+        slice.map_async(wgpu::MapMode::Read, move |result| {
+            let _ = sender.send(result);
+        });
+        device.poll(wgpu::Maintain::Wait);
+
+        if let Err(err) = receiver.recv().ok()? {
+            log::error!("unable to read the screenshot: {err:?}");
+            return None;
+        }
+
+        let data = unpad_rows(
+            &slice.get_mapped_range(),
+            self.width,
+            self.height,
+            self.padded_bytes_per_row,
+            self.is_bgra,
+        );
+        self.buffer.unmap();
+
+        Some(Screenshot {
+            width: self.width,
+            height: self.height,
+            data,
+        })
+    }
+}
+
+fn unpad_rows(
+    padded: &[u8],
+    width: u32,
+    height: u32,
+    padded_bytes_per_row: u32,
+    is_bgra: bool,
+) -> Vec<u8> {
+    let bytes_per_row = width as usize * 4;
+    let mut data = Vec::with_capacity(bytes_per_row * height as usize);
+    for row in padded
+        .chunks(padded_bytes_per_row as usize)
+        .take(height as usize)
+    {
+        data.extend_from_slice(&row[..bytes_per_row]);
+    }
+
+    if is_bgra {
+        for pixel in data.chunks_exact_mut(4) {
+            pixel.swap(0, 2);
+        }
+    }
+
+    data
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rows_lose_their_padding_and_bgra_is_swapped() {
+        // 2x2 pixels with rows padded to 12 bytes.
+        let padded = [
+            1, 2, 3, 4, 5, 6, 7, 8, 0, 0, 0, 0, //
+            9, 10, 11, 12, 13, 14, 15, 16, 0, 0, 0, 0,
+        ];
+
+        assert_eq!(
+            unpad_rows(&padded, 2, 2, 12, false),
+            [1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16]
+        );
+        assert_eq!(
+            unpad_rows(&padded, 2, 2, 12, true),
+            [3, 2, 1, 4, 7, 6, 5, 8, 11, 10, 9, 12, 15, 14, 13, 16]
+        );
+    }
+
+    #[test]
+    fn screenshots_are_saved_as_png() {
+        let screenshot = Screenshot {
+            width: 2,
+            height: 1,
+            data: vec![255, 0, 0, 255, 0, 0, 255, 128],
+        };
+        let path = std::env::temp_dir().join("sugarloaf-screenshot-test.png");
+        screenshot.save_png(&path).unwrap();
+
+        let image = image::open(&path).unwrap().to_rgba8();
+        assert_eq!(image.dimensions(), (2, 1));
+        assert_eq!(image.into_raw(), screenshot.data);
+        let _ = std::fs::remove_file(path);
+    }
+}
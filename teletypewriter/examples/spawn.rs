@@ -2,15 +2,14 @@
 
 #[cfg(unix)]
 fn main() -> std::io::Result<()> {
-    use std::borrow::Cow;
     use std::io::Read;
     use std::io::Write;
     // use std::io::BufRead;
     use std::io::BufReader;
-    use teletypewriter::{create_pty_with_fork, ProcessReadWrite, Pty};
+    use teletypewriter::{create_pty_with_fork, ProcessReadWrite, Pty, PtyCommand};
 
-    let shell = Cow::Borrowed("bash");
-    let mut process: Pty = create_pty_with_fork(&shell, 80, 25)?;
+    let command = PtyCommand::new("bash");
+    let mut process: Pty = create_pty_with_fork(&command, 80, 25)?;
 
     process.writer().write_all(b"1").unwrap();
     process.writer().write_all(b"2").unwrap();
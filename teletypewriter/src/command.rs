@@ -0,0 +1,158 @@
+use std::path::{Path, PathBuf};
+
+/// Program to run in a pseudoterminal, used the same way by
+/// `create_pty_with_fork` and `create_pty_with_spawn` on Unix and by
+/// `create_pty` on Windows.
+///
+/// ```
+/// use teletypewriter::PtyCommand;
+///
+/// let command = PtyCommand::new("htop")
+///     .arg("--tree")
+///     .cwd("/tmp")
+///     .env("LANG", "C.UTF-8")
+///     .env_remove("SSH_AUTH_SOCK")
+///     .term("xterm-256color");
+/// assert_eq!(command.get_program(), "htop");
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct PtyCommand {
+    program: String,
+    args: Vec<String>,
+    cwd: Option<PathBuf>,
+    env: Vec<(String, Option<String>)>,
+    login: bool,
+    term: Option<String>,
+}
+
+impl PtyCommand {
+    /// Run `program`, an empty one runs the shell of the user (powershell
+    /// on Windows).
+    pub fn new(program: impl Into<String>) -> PtyCommand {
+        PtyCommand {
+            program: program.into(),
+            ..PtyCommand::default()
+        }
+    }
+
+    /// Run the shell of the user.
+    #[inline]
+    pub fn shell() -> PtyCommand {
+        PtyCommand::default()
+    }
+
+    pub fn arg(mut self, arg: impl Into<String>) -> PtyCommand {
+        self.args.push(arg.into());
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> PtyCommand
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// Working directory of the program, the one of the current process
+    /// otherwise.
+    pub fn cwd(mut self, cwd: impl Into<PathBuf>) -> PtyCommand {
+        self.cwd = Some(cwd.into());
+        self
+    }
+
+    /// Set `key` in the environment of the program, on top of the one of
+    /// the current process.
+    pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> PtyCommand {
+        self.env.push((key.into(), Some(value.into())));
+        self
+    }
+
+    /// Remove `key` from the environment of the program.
+    pub fn env_remove(mut self, key: impl Into<String>) -> PtyCommand {
+        self.env.push((key.into(), None));
+        self
+    }
+
+    /// Run it as a login shell, with its name prefixed by `-` as `argv[0]`
+    /// like login(1) does. Only on Unix.
+    pub fn login(mut self, login: bool) -> PtyCommand {
+        self.login = login;
+        self
+    }
+
+    /// Value of `TERM` for the program.
+    pub fn term(mut self, term: impl Into<String>) -> PtyCommand {
+        self.term = Some(term.into());
+        self
+    }
+
+    #[inline]
+    pub fn get_program(&self) -> &str {
+        &self.program
+    }
+
+    #[inline]
+    pub fn get_args(&self) -> &[String] {
+        &self.args
+    }
+
+    #[inline]
+    pub fn get_cwd(&self) -> Option<&Path> {
+        self.cwd.as_deref()
+    }
+
+    #[inline]
+    pub fn is_login(&self) -> bool {
+        self.login
+    }
+
+    /// Changes of the environment in order, `TERM` last, a `None` value
+    /// removes the variable.
+    pub fn get_envs(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.env
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_deref()))
+            .chain(self.term.as_deref().map(|term| ("TERM", Some(term))))
+    }
+
+    /// `argv[0]` of the program, its file name with a `-` in front of it
+    /// for login shells.
+    pub(crate) fn arg0(&self, program: &str) -> String {
+        if !self.login {
+            return program.to_string();
+        }
+
+        let name = Path::new(program)
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_else(|| program.into());
+        format!("-{name}")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_the_command() {
+        let command = PtyCommand::new("/bin/zsh")
+            .args(["-c", "make"])
+            .cwd("/tmp")
+            .env("A", "1")
+            .env_remove("B")
+            .term("rio")
+            .login(true);
+
+        assert_eq!(command.get_args(), ["-c", "make"]);
+        assert_eq!(command.get_cwd(), Some(Path::new("/tmp")));
+        assert_eq!(
+            command.get_envs().collect::<Vec<_>>(),
+            [("A", Some("1")), ("B", None), ("TERM", Some("rio"))]
+        );
+        assert_eq!(command.arg0("/bin/zsh"), "-zsh");
+        assert_eq!(PtyCommand::shell().arg0("/bin/zsh"), "/bin/zsh");
+    }
+}
@@ -1,5 +1,8 @@
 extern crate libc;
 
+mod command;
+pub use command::PtyCommand;
+
 #[cfg(not(windows))]
 mod unix;
 #[cfg(not(windows))]
@@ -10,6 +13,7 @@ pub mod windows;
 #[cfg(windows)]
 pub use self::windows::*;
 
+use std::fmt;
 use std::io;
 
 #[repr(C)]
@@ -45,10 +49,70 @@ pub trait ProcessReadWrite {
     fn deregister(&mut self, _: &corcovado::Poll) -> io::Result<()>;
 }
 
+/// How a child exited.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ExitStatus {
+    /// Exit code, `None` when a signal terminated it or it's unknown.
+    pub code: Option<i32>,
+    /// Signal that terminated it, only on Unix.
+    pub signal: Option<i32>,
+    /// Whether it dumped core when the signal terminated it.
+    pub core_dumped: bool,
+}
+
+impl ExitStatus {
+    pub fn from_code(code: i32) -> ExitStatus {
+        ExitStatus {
+            code: Some(code),
+            ..ExitStatus::default()
+        }
+    }
+
+    #[inline]
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+impl fmt::Display for ExitStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (self.code, self.signal) {
+            (Some(code), _) => write!(f, "exited with code {code}")?,
+            (None, Some(signal)) => {
+                write!(f, "killed by signal {signal}")?;
+                #[cfg(not(windows))]
+                if let Some(description) = unix::signal_description(signal) {
+                    write!(f, " ({description})")?;
+                }
+            }
+            (None, None) => write!(f, "exited")?,
+        }
+
+        if self.core_dumped {
+            write!(f, ", core dumped")?;
+        }
+        Ok(())
+    }
+}
+
+/// Process in the foreground of a pseudoterminal, the leader of its
+/// foreground process group.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ForegroundProcess {
+    pub pid: i32,
+    /// Name of the program, empty when it can't be read.
+    pub name: String,
+    /// Working directory, `None` when it can't be read.
+    pub cwd: Option<std::path::PathBuf>,
+    /// Whether it's the process the pseudoterminal was created with, usually
+    /// the shell, so nothing else runs in the foreground.
+    pub is_shell: bool,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum ChildEvent {
     /// Indicates the child has exited.
-    Exited,
+    Exited(ExitStatus),
 }
 
 pub trait EventedPty: ProcessReadWrite {
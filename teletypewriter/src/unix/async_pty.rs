@@ -0,0 +1,207 @@
+use super::{create_pty_with_spawn, Child, Pty};
+use crate::{ExitStatus, PtyCommand, WinsizeBuilder};
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{ready, Context, Poll};
+use tokio::io::unix::AsyncFd;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::signal::unix::{signal, SignalKind};
+
+/// [`Pty`] for tokio runtimes, readable and writable with `AsyncRead` and
+/// `AsyncWrite`, also through `&AsyncPty` so the child can be waited for
+/// meanwhile.
+///
+/// ```no_run
+/// # async fn run() -> std::io::Result<()> {
+/// use teletypewriter::{AsyncPty, PtyCommand};
+/// use tokio::io::AsyncReadExt;
+///
+/// let pty = AsyncPty::spawn(&PtyCommand::new("ls"), 80, 25)?;
+/// let mut output = vec![];
+/// (&pty).read_to_end(&mut output).await?;
+/// let status = pty.wait().await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncPty {
+    file: AsyncFd<File>,
+    child: Child,
+    status: Mutex<Option<ExitStatus>>,
+}
+
+impl AsyncPty {
+    /// Drive `pty` from the tokio runtime of the current thread.
+    pub fn new(pty: Pty) -> io::Result<AsyncPty> {
+        let Pty { child, file, .. } = pty;
+        Ok(AsyncPty {
+            file: AsyncFd::new(file)?,
+            child,
+            status: Mutex::new(None),
+        })
+    }
+
+    /// Run `command` in a new pseudoterminal.
+    pub fn spawn(command: &PtyCommand, columns: u16, rows: u16) -> io::Result<AsyncPty> {
+        AsyncPty::new(create_pty_with_spawn(command, columns, rows)?)
+    }
+
+    #[inline]
+    pub fn pid(&self) -> libc::pid_t {
+        *self.child.pid
+    }
+
+    pub async fn resize(&self, winsize: WinsizeBuilder) -> io::Result<()> {
+        self.child.set_winsize(winsize)
+    }
+
+    /// Wait for the child to exit, every call gets its status.
+    pub async fn wait(&self) -> io::Result<ExitStatus> {
+        // Listen before checking so an exit in between isn't missed.
+        let mut sigchld = signal(SignalKind::child())?;
+        loop {
+            if let Some(status) = self.try_wait()? {
+                return Ok(status);
+            }
+            sigchld.recv().await;
+        }
+    }
+
+    /// Status of the child if it exited already.
+    pub fn try_wait(&self) -> io::Result<Option<ExitStatus>> {
+        let mut status = self.status.lock().unwrap();
+        if status.is_none() {
+            *status = self
+                .child
+                .waitpid()
+                .map_err(|err| io::Error::new(io::ErrorKind::Other, err))?;
+        }
+        Ok(*status)
+    }
+
+    fn poll_read_priv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        loop {
+            let mut guard = ready!(self.file.poll_read_ready(cx))?;
+            let unfilled = buf.initialize_unfilled();
+            match guard.try_io(|file| file.get_ref().read(unfilled)) {
+                Ok(Ok(len)) => {
+                    buf.advance(len);
+                    return Poll::Ready(Ok(()));
+                }
+                // Linux fails with `EIO` once the child side hangs up, it's the end
+                // of the output.
+                #[cfg(target_os = "linux")]
+                Ok(Err(err)) if err.raw_os_error() == Some(libc::EIO) => {
+                    return Poll::Ready(Ok(()));
+                }
+                Ok(Err(err)) => return Poll::Ready(Err(err)),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    fn poll_write_priv(
+        &self,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        loop {
+            let mut guard = ready!(self.file.poll_write_ready(cx))?;
+            match guard.try_io(|file| file.get_ref().write(buf)) {
+                Ok(result) => return Poll::Ready(result),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+impl AsyncRead for AsyncPty {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.poll_read_priv(cx, buf)
+    }
+}
+
+impl AsyncRead for &AsyncPty {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.poll_read_priv(cx, buf)
+    }
+}
+
+impl AsyncWrite for AsyncPty {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.poll_write_priv(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for &AsyncPty {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.poll_write_priv(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    #[tokio::test]
+    async fn reads_writes_and_waits() {
+        let command =
+            PtyCommand::new("sh").args(["-c", "read line; echo got $line; exit 4"]);
+        let mut pty = AsyncPty::spawn(&command, 80, 25).unwrap();
+        pty.resize(WinsizeBuilder {
+            rows: 30,
+            cols: 100,
+            width: 0,
+            height: 0,
+        })
+        .await
+        .unwrap();
+        pty.write_all(b"rio\n").await.unwrap();
+
+        let mut output = vec![];
+        (&pty).read_to_end(&mut output).await.unwrap();
+        assert!(String::from_utf8_lossy(&output).contains("got rio"));
+
+        let status = pty.wait().await.unwrap();
+        assert_eq!(status, ExitStatus::from_code(4));
+        assert_eq!(pty.wait().await.unwrap(), status);
+    }
+}
@@ -1,26 +1,36 @@
 #![cfg(unix)]
 
+#[cfg(feature = "tokio")]
+mod async_pty;
 #[cfg(target_os = "macos")]
 mod macos;
 mod signals;
 
+#[cfg(feature = "tokio")]
+pub use async_pty::AsyncPty;
+
 extern crate libc;
 
-use crate::{ChildEvent, EventedPty, ProcessReadWrite, Winsize, WinsizeBuilder};
+use crate::{
+    ChildEvent, EventedPty, ExitStatus, ForegroundProcess, ProcessReadWrite, PtyCommand,
+    Winsize, WinsizeBuilder,
+};
 use corcovado::unix::EventedFd;
 #[cfg(target_os = "macos")]
 use macos::*;
 use signal_hook::consts as sigconsts;
 use signals::Signals;
-use std::ffi::{CStr, CString};
+use std::ffi::{CStr, CString, OsStr, OsString};
 use std::fs::File;
 use std::io;
 use std::io::{Error, ErrorKind};
 use std::mem::MaybeUninit;
 use std::ops::Deref;
 use std::os::fd::{AsRawFd, FromRawFd, RawFd};
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
 use std::os::unix::process::CommandExt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
 use std::ptr;
 use std::sync::Arc;
@@ -60,30 +70,103 @@ extern "C" {
     fn ptsname(fd: *mut libc::c_int) -> *mut libc::c_char;
 }
 
-#[cfg(target_os = "macos")]
-fn default_shell_command(shell: &str) {
-    let command_shell_string = CString::new(shell).unwrap();
-    let command_pointer = command_shell_string.as_ptr();
-    let args = CString::new("--login").unwrap();
-    let args_pointer = args.as_ptr();
-    unsafe {
-        libc::execvp(command_pointer, vec![args_pointer].as_ptr());
+/// `PATH` used by `execvp` when the environment doesn't have one.
+const DEFAULT_PATH: &str = "/usr/bin:/bin";
+
+/// `PtyCommand` as C strings, prepared before forking since the child
+/// shouldn't allocate.
+struct ForkCommand {
+    /// Path of the program, looked up in the `PATH` of its environment.
+    program: CString,
+    argv: Vec<CString>,
+    cwd: Option<CString>,
+    /// `KEY=VALUE` environment of the program. The forked child can't
+    /// allocate nor touch `environ` safely, so it's built beforehand.
+    envp: Vec<CString>,
+}
+
+impl ForkCommand {
+    fn new(
+        command: &PtyCommand,
+        program: &str,
+        user: &ShellUser,
+    ) -> Result<ForkCommand, Error> {
+        let c_string = |value: &[u8]| {
+            CString::new(value).map_err(|err| Error::new(ErrorKind::InvalidInput, err))
+        };
+
+        let mut argv = vec![c_string(command.arg0(program).as_bytes())?];
+        for arg in command.get_args() {
+            argv.push(c_string(arg.as_bytes())?);
+        }
+
+        let mut env: Vec<(OsString, OsString)> = std::env::vars_os().collect();
+        let mut set_env = |key: &str, value: Option<&str>| {
+            env.retain(|(name, _)| name != key);
+            if let Some(value) = value {
+                env.push((OsString::from(key), OsString::from(value)));
+            }
+        };
+        set_env("USER", Some(&user.user));
+        set_env("HOME", Some(&user.home));
+        for (key, value) in command.get_envs() {
+            set_env(key, value);
+        }
+
+        let path = env
+            .iter()
+            .find(|(name, _)| name == "PATH")
+            .map(|(_, value)| value.as_os_str());
+        let program = find_program(program, path);
+
+        let envp = env
+            .iter()
+            .map(|(key, value)| {
+                c_string(&[key.as_bytes(), b"=", value.as_bytes()].concat())
+            })
+            .collect::<Result<Vec<CString>, Error>>()?;
+
+        Ok(ForkCommand {
+            program: c_string(program.as_os_str().as_bytes())?,
+            argv,
+            cwd: command
+                .get_cwd()
+                .map(|cwd| c_string(cwd.as_os_str().as_bytes()))
+                .transpose()?,
+            envp,
+        })
+    }
+
+    /// Replace the forked child with the program, it exits with 127 like a
+    /// shell when that fails. Only async-signal-safe calls are made here.
+    fn exec(&self, argv: &[*const libc::c_char], envp: &[*const libc::c_char]) -> ! {
+        unsafe {
+            if let Some(cwd) = &self.cwd {
+                libc::chdir(cwd.as_ptr());
+            }
+
+            libc::execve(self.program.as_ptr(), argv.as_ptr(), envp.as_ptr());
+            libc::_exit(127)
+        }
     }
 }
 
-#[cfg(not(target_os = "macos"))]
-fn default_shell_command(shell: &str) {
-    let command_shell_string = CString::new(shell).unwrap();
-    let command_pointer = command_shell_string.as_ptr();
-    // let home = std::env::var("HOME").unwrap();
-    // let args = CString::new(home).unwrap();
-    // let args_pointer = args.as_ptr() as *const i8;
-    unsafe {
-        libc::execvp(
-            command_pointer,
-            vec![command_pointer, std::ptr::null()].as_ptr(),
-        );
+/// `program` in one of the directories of `path`, `execve` doesn't look it
+/// up like `execvp` does.
+fn find_program(program: &str, path: Option<&OsStr>) -> PathBuf {
+    if program.contains('/') {
+        return PathBuf::from(program);
     }
+
+    let is_executable = |candidate: &Path| {
+        candidate.metadata().is_ok_and(|metadata| {
+            metadata.is_file() && metadata.permissions().mode() & 0o111 != 0
+        })
+    };
+    std::env::split_paths(path.unwrap_or(OsStr::new(DEFAULT_PATH)))
+        .map(|directory| directory.join(program))
+        .find(|candidate| is_executable(candidate))
+        .unwrap_or_else(|| PathBuf::from(program))
 }
 
 pub struct Pty {
@@ -394,9 +477,7 @@ impl ShellUser {
 /// It returns two [`Pty`] along with respective process name [`String`] and process id (`libc::pid_`)
 ///
 pub fn create_pty_with_spawn(
-    shell: &str,
-    args: Vec<String>,
-    working_directory: &Option<String>,
+    command: &PtyCommand,
     columns: u16,
     rows: u16,
 ) -> Result<Pty, Error> {
@@ -430,6 +511,7 @@ pub fn create_pty_with_spawn(
         return Err(Error::new(ErrorKind::Other, "openpty failed"));
     }
 
+    let shell = command.get_program();
     let mut shell_program = shell;
 
     let user = match ShellUser::from_env() {
@@ -444,11 +526,12 @@ pub fn create_pty_with_spawn(
         shell_program = &user.shell;
     }
 
-    log::info!("spawn {:?} {:?}", shell_program, args);
+    log::info!("spawn {:?} {:?}", shell_program, command.get_args());
 
     let mut builder = {
         let mut cmd = Command::new(shell_program);
-        cmd.args(args);
+        cmd.arg0(command.arg0(shell_program));
+        cmd.args(command.get_args());
         cmd
     };
 
@@ -459,11 +542,8 @@ pub fn create_pty_with_spawn(
         if std::path::PathBuf::from("/.flatpak-info").exists() {
             builder = Command::new("flatpak-spawn");
             let mut with_args = vec!["--host".to_string(), "--watch-bus".to_string()];
-            if let Some(directory) = working_directory {
-                with_args.push(format!(
-                    "--directory={}",
-                    std::path::Path::new(directory).display()
-                ));
+            if let Some(directory) = command.get_cwd() {
+                with_args.push(format!("--directory={}", directory.display()));
             }
 
             // Map only base environment variables
@@ -474,14 +554,25 @@ pub fn create_pty_with_spawn(
             }
 
             with_args.push("--env=TERM_PROGRAM=rio".to_string());
+            for (key, value) in command.get_envs() {
+                match value {
+                    Some(value) => with_args.push(format!("--env={key}={value}")),
+                    None => with_args.push(format!("--unset-env={key}")),
+                }
+            }
 
-            let output = std::process::Command::new("flatpak-spawn")
-                .args(["--host", "sh", "-c", "echo $SHELL"])
-                .output()?;
-            let shell = String::from_utf8_lossy(&output.stdout);
+            if shell.is_empty() {
+                let output = std::process::Command::new("flatpak-spawn")
+                    .args(["--host", "sh", "-c", "echo $SHELL"])
+                    .output()?;
+                let shell = String::from_utf8_lossy(&output.stdout);
 
-            with_args.push(shell.trim().to_string());
-            with_args.push("-l".to_string());
+                with_args.push(shell.trim().to_string());
+                with_args.push("-l".to_string());
+            } else {
+                with_args.push(shell.to_string());
+            }
+            with_args.extend(command.get_args().iter().cloned());
 
             builder.args(with_args);
 
@@ -499,6 +590,12 @@ pub fn create_pty_with_spawn(
 
     builder.env("USER", user.user);
     builder.env("HOME", user.home);
+    for (key, value) in command.get_envs() {
+        match value {
+            Some(value) => builder.env(key, value),
+            None => builder.env_remove(key),
+        };
+    }
 
     unsafe {
         builder.pre_exec(move || {
@@ -528,7 +625,7 @@ pub fn create_pty_with_spawn(
     }
 
     // Handle set working directory option.
-    if let Some(dir) = &working_directory {
+    if let Some(dir) = command.get_cwd() {
         builder.current_dir(dir);
     }
 
@@ -578,7 +675,11 @@ pub fn create_pty_with_spawn(
 ///
 /// It returns two [`Pty`] along with respective process name [`String`] and process id (`libc::pid_`)
 ///
-pub fn create_pty_with_fork(shell: &str, columns: u16, rows: u16) -> Result<Pty, Error> {
+pub fn create_pty_with_fork(
+    command: &PtyCommand,
+    columns: u16,
+    rows: u16,
+) -> Result<Pty, Error> {
     let mut main = 0;
     let winsize = Winsize {
         ws_row: rows as libc::c_ushort,
@@ -588,6 +689,7 @@ pub fn create_pty_with_fork(shell: &str, columns: u16, rows: u16) -> Result<Pty,
     };
     let term = create_termp(true);
 
+    let shell = command.get_program();
     let mut shell_program = shell;
 
     let user = match ShellUser::from_env() {
@@ -603,7 +705,18 @@ pub fn create_pty_with_fork(shell: &str, columns: u16, rows: u16) -> Result<Pty,
         shell_program = &user.shell;
     }
 
-    log::info!("fork {:?}", shell_program);
+    log::info!("fork {:?} {:?}", shell_program, command.get_args());
+
+    let fork_command = ForkCommand::new(command, shell_program, &user)?;
+    let mut argv: Vec<*const libc::c_char> =
+        fork_command.argv.iter().map(|arg| arg.as_ptr()).collect();
+    argv.push(ptr::null());
+    let mut envp: Vec<*const libc::c_char> = fork_command
+        .envp
+        .iter()
+        .map(|entry| entry.as_ptr())
+        .collect();
+    envp.push(ptr::null());
 
     match unsafe {
         forkpty(
@@ -613,13 +726,7 @@ pub fn create_pty_with_fork(shell: &str, columns: u16, rows: u16) -> Result<Pty,
             &winsize as *const _,
         )
     } {
-        0 => {
-            default_shell_command(shell_program);
-            Err(Error::new(
-                ErrorKind::Other,
-                format!("forkpty has reach unreachable with {}", shell_program),
-            ))
-        }
+        0 => fork_command.exec(&argv, &envp),
         id if id > 0 => {
             // TODO: Currently we fork the process and don't wait to know if led to failure
             // Whenever it happens it will just simply shut down the teletyperwriter
@@ -721,7 +828,7 @@ impl Child {
 
     /// Return the child’s exit status if it has already exited. If the child is still running, return Ok(None).
     /// https://linux.die.net/man/2/waitpid
-    pub fn waitpid(&self) -> Result<Option<i32>, String> {
+    pub fn waitpid(&self) -> Result<Option<ExitStatus>, String> {
         let mut status = 0 as libc::c_int;
         // If WNOHANG was specified in options and there were no children in a waitable state, then waitid() returns 0 immediately and the state of the siginfo_t structure pointed to by infop is unspecified. To distinguish this case from that where a child was in a waitable state, zero out the si_pid field before the call and check for a nonzero value in this field after the call returns.
         let res =
@@ -734,7 +841,7 @@ impl Child {
             return Ok(None);
         }
 
-        Ok(Some(status))
+        Ok(Some(exit_status(status)))
     }
 
     pub fn close(&self) {
@@ -744,6 +851,33 @@ impl Child {
     }
 }
 
+/// Decode the status reported by `waitpid`.
+fn exit_status(status: libc::c_int) -> ExitStatus {
+    if libc::WIFSIGNALED(status) {
+        return ExitStatus {
+            code: None,
+            signal: Some(libc::WTERMSIG(status)),
+            core_dumped: libc::WCOREDUMP(status),
+        };
+    }
+
+    ExitStatus {
+        code: libc::WIFEXITED(status).then(|| libc::WEXITSTATUS(status)),
+        ..ExitStatus::default()
+    }
+}
+
+/// Description of `signal`, like "Segmentation fault" for SIGSEGV.
+pub(crate) fn signal_description(signal: libc::c_int) -> Option<String> {
+    let description = unsafe { libc::strsignal(signal) };
+    if description.is_null() {
+        return None;
+    }
+
+    let description = unsafe { CStr::from_ptr(description) };
+    Some(description.to_string_lossy().into_owned())
+}
+
 pub fn kill_pid(pid: i32) {
     unsafe {
         libc::kill(pid, libc::SIGHUP);
@@ -794,7 +928,7 @@ impl EventedPty for Pty {
                     None
                 }
                 Ok(None) => None,
-                Ok(Some(..)) => Some(ChildEvent::Exited),
+                Ok(Some(status)) => Some(ChildEvent::Exited(status)),
             }
         })
     }
@@ -873,12 +1007,18 @@ pub fn tty_ptsname(fd: libc::c_int) -> Result<String, String> {
     Ok(str_buf)
 }
 
-pub fn foreground_process_name(main_fd: RawFd, shell_pid: u32) -> String {
-    let mut pid = unsafe { libc::tcgetpgrp(main_fd) };
+/// Leader of the foreground process group of the pseudoterminal, the
+/// shell when it can't be read.
+pub fn foreground_process_id(main_fd: RawFd, shell_pid: u32) -> libc::pid_t {
+    let pid = unsafe { libc::tcgetpgrp(main_fd) };
     if pid < 0 {
-        pid = shell_pid as libc::pid_t;
+        shell_pid as libc::pid_t
+    } else {
+        pid
     }
+}
 
+pub fn process_name(pid: libc::pid_t) -> String {
     #[cfg(not(any(target_os = "macos", target_os = "freebsd")))]
     let comm_path = format!("/proc/{}/comm", pid);
     #[cfg(target_os = "freebsd")]
@@ -886,9 +1026,7 @@ pub fn foreground_process_name(main_fd: RawFd, shell_pid: u32) -> String {
 
     #[cfg(not(target_os = "macos"))]
     let name = match std::fs::read(comm_path) {
-        Ok(comm_str) => String::from_utf8_lossy(&comm_str)
-            .parse()
-            .unwrap_or_default(),
+        Ok(comm_str) => String::from_utf8_lossy(&comm_str).trim_end().to_string(),
         Err(..) => String::from(""),
     };
 
@@ -898,15 +1036,7 @@ pub fn foreground_process_name(main_fd: RawFd, shell_pid: u32) -> String {
     name
 }
 
-pub fn foreground_process_path(
-    main_fd: RawFd,
-    shell_pid: u32,
-) -> Result<PathBuf, Box<dyn std::error::Error>> {
-    let mut pid = unsafe { libc::tcgetpgrp(main_fd) };
-    if pid < 0 {
-        pid = shell_pid as libc::pid_t;
-    }
-
+pub fn process_cwd(pid: libc::pid_t) -> Result<PathBuf, Box<dyn std::error::Error>> {
     #[cfg(not(any(target_os = "macos", target_os = "freebsd")))]
     let link_path = format!("/proc/{}/cwd", pid);
     #[cfg(target_os = "freebsd")]
@@ -921,6 +1051,60 @@ pub fn foreground_process_path(
     Ok(cwd)
 }
 
+pub fn foreground_process_name(main_fd: RawFd, shell_pid: u32) -> String {
+    process_name(foreground_process_id(main_fd, shell_pid))
+}
+
+pub fn foreground_process_path(
+    main_fd: RawFd,
+    shell_pid: u32,
+) -> Result<PathBuf, Box<dyn std::error::Error>> {
+    process_cwd(foreground_process_id(main_fd, shell_pid))
+}
+
+/// Notices when another process group takes the foreground of a
+/// pseudoterminal, `changed` is cheap enough to call after every read or on
+/// a timer.
+#[derive(Debug)]
+pub struct ForegroundTracker {
+    main_fd: RawFd,
+    shell_pid: u32,
+    pid: Option<libc::pid_t>,
+}
+
+impl ForegroundTracker {
+    pub fn new(main_fd: RawFd, shell_pid: u32) -> ForegroundTracker {
+        ForegroundTracker {
+            main_fd,
+            shell_pid,
+            pid: None,
+        }
+    }
+
+    /// The foreground process if it's not the one of the last call.
+    pub fn changed(&mut self) -> Option<ForegroundProcess> {
+        let pid = foreground_process_id(self.main_fd, self.shell_pid);
+        if self.pid == Some(pid) {
+            return None;
+        }
+
+        self.pid = Some(pid);
+        Some(ForegroundProcess {
+            pid,
+            name: process_name(pid),
+            cwd: process_cwd(pid).ok(),
+            is_shell: pid == self.shell_pid as libc::pid_t,
+        })
+    }
+
+    /// Whether the shell had the foreground at the last call.
+    #[inline]
+    pub fn is_shell(&self) -> bool {
+        self.pid
+            .map_or(true, |pid| pid == self.shell_pid as libc::pid_t)
+    }
+}
+
 /// Start a new process in the background.
 pub fn spawn_daemon<I, S>(
     program: &str,
@@ -961,3 +1145,72 @@ where
             .map(|_| ())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exit_status_from_waitpid() {
+        let status = exit_status(3 << 8);
+        assert_eq!(status, ExitStatus::from_code(3));
+        assert!(!status.success());
+        assert_eq!(status.to_string(), "exited with code 3");
+
+        let status = exit_status(libc::SIGSEGV | 0x80);
+        assert_eq!(status.code, None);
+        assert_eq!(status.signal, Some(libc::SIGSEGV));
+        assert!(status.core_dumped);
+        assert!(status
+            .to_string()
+            .starts_with(&format!("killed by signal {}", libc::SIGSEGV)));
+        assert!(status.to_string().ends_with(", core dumped"));
+    }
+
+    #[test]
+    fn tracks_the_foreground_process() {
+        let pty =
+            create_pty_with_spawn(&PtyCommand::new("sleep").arg("5"), 80, 25).unwrap();
+        let mut tracker = ForegroundTracker::new(*pty.child.id, *pty.child.pid as u32);
+
+        let process = tracker.changed().unwrap();
+        assert_eq!(process.pid, *pty.child.pid);
+        assert_eq!(process.name, "sleep");
+        assert!(process.cwd.is_some());
+        assert!(process.is_shell);
+        assert_eq!(tracker.changed(), None);
+        assert!(tracker.is_shell());
+
+        kill_pid(*pty.child.pid);
+    }
+
+    #[test]
+    fn fork_command_is_built_before_forking() {
+        let user = ShellUser {
+            user: String::from("rio"),
+            home: String::from("/home/rio"),
+            shell: String::from("sh"),
+        };
+        let command = PtyCommand::new("sh")
+            .env("RIO_FORK_TEST", "1")
+            .env("USER", "other")
+            .env_remove("HOME");
+        let fork_command = ForkCommand::new(&command, "sh", &user).unwrap();
+
+        assert!(fork_command.program.to_bytes().ends_with(b"/sh"));
+        let envp: Vec<&[u8]> = fork_command.envp.iter().map(|e| e.to_bytes()).collect();
+        assert!(envp.contains(&&b"RIO_FORK_TEST=1"[..]));
+        assert!(envp.contains(&&b"USER=other"[..]));
+        assert!(!envp.iter().any(|entry| entry.starts_with(b"HOME=")));
+    }
+
+    #[test]
+    fn failed_exec_exits_the_forked_child() {
+        let pty = create_pty_with_fork(&PtyCommand::new("rio-missing-program"), 80, 25)
+            .unwrap();
+
+        let mut status = 0;
+        unsafe { libc::waitpid(*pty.child.pid, &mut status, 0) };
+        assert_eq!(exit_status(status), ExitStatus::from_code(127));
+    }
+}
@@ -5,11 +5,17 @@ use std::sync::atomic::{AtomicPtr, Ordering};
 
 use windows_sys::Win32::Foundation::{BOOLEAN, HANDLE};
 use windows_sys::Win32::System::Threading::{
-    RegisterWaitForSingleObject, UnregisterWait, INFINITE, WT_EXECUTEINWAITTHREAD,
-    WT_EXECUTEONLYONCE,
+    GetExitCodeProcess, RegisterWaitForSingleObject, UnregisterWait, INFINITE,
+    WT_EXECUTEINWAITTHREAD, WT_EXECUTEONLYONCE,
 };
 
-use crate::ChildEvent;
+use crate::{ChildEvent, ExitStatus};
+
+/// Context of the callback, the child handle is still open when it runs.
+struct ChildExitContext {
+    event_tx: Sender<ChildEvent>,
+    child_handle: HANDLE,
+}
 
 /// WinAPI callback to run when child process exits.
 extern "system" fn child_exit_callback(ctx: *mut c_void, timed_out: BOOLEAN) {
@@ -17,8 +23,14 @@ extern "system" fn child_exit_callback(ctx: *mut c_void, timed_out: BOOLEAN) {
         return;
     }
 
-    let event_tx: Box<_> = unsafe { Box::from_raw(ctx as *mut Sender<ChildEvent>) };
-    let _ = event_tx.send(ChildEvent::Exited);
+    let context: Box<_> = unsafe { Box::from_raw(ctx as *mut ChildExitContext) };
+    let mut exit_code = 0;
+    let status = match unsafe { GetExitCodeProcess(context.child_handle, &mut exit_code) }
+    {
+        0 => ExitStatus::default(),
+        _ => ExitStatus::from_code(exit_code as i32),
+    };
+    let _ = context.event_tx.send(ChildEvent::Exited(status));
 }
 
 pub struct ChildExitWatcher {
@@ -31,14 +43,17 @@ impl ChildExitWatcher {
         let (event_tx, event_rx) = channel::<ChildEvent>();
 
         let mut wait_handle: HANDLE = 0;
-        let sender_ref = Box::new(event_tx);
+        let context = Box::new(ChildExitContext {
+            event_tx,
+            child_handle,
+        });
 
         let success = unsafe {
             RegisterWaitForSingleObject(
                 &mut wait_handle,
                 child_handle,
                 Some(child_exit_callback),
-                Box::into_raw(sender_ref).cast(),
+                Box::into_raw(context).cast(),
                 INFINITE,
                 WT_EXECUTEINWAITTHREAD | WT_EXECUTEONLYONCE,
             )
@@ -103,9 +118,10 @@ mod tests {
         poll.poll(&mut events, Some(WAIT_TIMEOUT)).unwrap();
         assert_eq!(events.iter().next().unwrap().token(), child_events_token);
         // Verify that at least one `ChildEvent::Exited` was received.
+        // `Child::kill` terminates it with 1 as exit code.
         assert_eq!(
             child_exit_watcher.event_rx().try_recv(),
-            Ok(ChildEvent::Exited)
+            Ok(ChildEvent::Exited(ExitStatus::from_code(1)))
         );
     }
 }
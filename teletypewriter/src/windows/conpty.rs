@@ -1,5 +1,6 @@
 use crate::Winsize;
 use log::*;
+use std::ffi::c_void;
 use std::io::Error;
 use std::os::windows::io::IntoRawHandle;
 use std::{mem, ptr};
@@ -16,13 +17,14 @@ use windows_sys::{s, w};
 
 use windows_sys::Win32::System::Threading::{
     CreateProcessW, InitializeProcThreadAttributeList, UpdateProcThreadAttribute,
-    EXTENDED_STARTUPINFO_PRESENT, PROCESS_INFORMATION,
+    CREATE_UNICODE_ENVIRONMENT, EXTENDED_STARTUPINFO_PRESENT, PROCESS_INFORMATION,
     PROC_THREAD_ATTRIBUTE_PSEUDOCONSOLE, STARTF_USESTDHANDLES, STARTUPINFOEXW,
     STARTUPINFOW,
 };
 
 use crate::windows::child::ChildExitWatcher;
-use crate::windows::{cmdline, win32_string, Pty};
+use crate::windows::{cmdline, env_block, win32_string, Pty};
+use crate::PtyCommand;
 
 /// Load the pseudoconsole API from conpty.dll if possible, otherwise use the
 /// standard Windows API.
@@ -103,12 +105,7 @@ impl Drop for Conpty {
 // The ConPTY handle can be sent between threads.
 unsafe impl Send for Conpty {}
 
-pub fn new(
-    shell: &str,
-    working_directory: &Option<String>,
-    columns: u16,
-    rows: u16,
-) -> Option<Pty> {
+pub fn new(command: &PtyCommand, columns: u16, rows: u16) -> Option<Pty> {
     let api = ConptyApi::new();
     let mut pty_handle: HPCON = 0;
 
@@ -213,8 +210,9 @@ pub fn new(
         }
     }
 
-    let cmdline = win32_string(&cmdline(shell));
-    let cwd = working_directory.as_ref().map(win32_string);
+    let cmdline = win32_string(&cmdline(command));
+    let cwd = command.get_cwd().map(win32_string);
+    let env = env_block(command);
 
     let mut proc_info: PROCESS_INFORMATION = unsafe { mem::zeroed() };
     unsafe {
@@ -224,8 +222,9 @@ pub fn new(
             ptr::null_mut(),
             ptr::null_mut(),
             false as i32,
-            EXTENDED_STARTUPINFO_PRESENT,
-            ptr::null_mut(),
+            EXTENDED_STARTUPINFO_PRESENT | CREATE_UNICODE_ENVIRONMENT,
+            env.as_ref()
+                .map_or_else(ptr::null, |env| env.as_ptr() as *const c_void),
             cwd.as_ref().map_or_else(ptr::null, |s| s.as_ptr()),
             &mut startup_info_ex.StartupInfo as *mut STARTUPINFOW,
             &mut proc_info as *mut PROCESS_INFORMATION,
@@ -10,7 +10,10 @@ use std::os::windows::ffi::OsStrExt;
 use std::sync::mpsc::TryRecvError;
 
 use crate::windows::child::ChildExitWatcher;
-use crate::{ChildEvent, EventedPty, ProcessReadWrite, Winsize, WinsizeBuilder};
+use crate::{
+    ChildEvent, EventedPty, ExitStatus, ProcessReadWrite, PtyCommand, Winsize,
+    WinsizeBuilder,
+};
 
 use conpty::Conpty as Backend;
 use pipes::{EventedAnonRead as ReadPipe, EventedAnonWrite as WritePipe};
@@ -29,14 +32,8 @@ pub struct Pty {
 
 // Creates conpty instead of pty
 // Windows Pseudo Console (ConPTY)
-pub fn create_pty(
-    shell: &str,
-    _args: Vec<String>,
-    working_directory: &Option<String>,
-    columns: u16,
-    rows: u16,
-) -> Pty {
-    conpty::new(shell, working_directory, columns, rows)
+pub fn create_pty(command: &PtyCommand, columns: u16, rows: u16) -> Pty {
+    conpty::new(command, columns, rows)
         .ok_or_else(|| panic!("failed to spawn conpty"))
         .unwrap()
 }
@@ -213,22 +210,65 @@ impl EventedPty for Pty {
         match self.child_watcher.event_rx().try_recv() {
             Ok(ev) => Some(ev),
             Err(TryRecvError::Empty) => None,
-            Err(TryRecvError::Disconnected) => Some(ChildEvent::Exited),
+            Err(TryRecvError::Disconnected) => {
+                Some(ChildEvent::Exited(ExitStatus::default()))
+            }
         }
     }
 }
 
-fn cmdline(shell: &str) -> String {
-    if !shell.is_empty() {
-        return shell.to_string();
-    }
+fn cmdline(command: &PtyCommand) -> String {
+    let program = match command.get_program() {
+        "" => "powershell",
+        program => program,
+    };
 
-    once("powershell")
-        // .chain(shell.args().iter().map(|a| a.as_ref()))
+    once(program)
+        .chain(command.get_args().iter().map(|arg| arg.as_str()))
+        .map(quote_arg)
         .collect::<Vec<_>>()
         .join(" ")
 }
 
+/// Quote `arg` for the command line when it has spaces or quotes.
+fn quote_arg(arg: &str) -> String {
+    if !arg.is_empty() && !arg.contains([' ', '\t', '"']) {
+        return arg.to_string();
+    }
+
+    format!("\"{}\"", arg.replace('"', "\\\""))
+}
+
+/// Environment block of the process, the one of Rio with the changes of
+/// `command`, or `None` when there are no changes.
+fn env_block(command: &PtyCommand) -> Option<Vec<u16>> {
+    let mut envs = command.get_envs().peekable();
+    envs.peek()?;
+
+    let mut vars: Vec<(String, String)> = std::env::vars_os()
+        .map(|(key, value)| {
+            (
+                key.to_string_lossy().into_owned(),
+                value.to_string_lossy().into_owned(),
+            )
+        })
+        .collect();
+    for (key, value) in envs {
+        vars.retain(|(var, _)| !var.eq_ignore_ascii_case(key));
+        if let Some(value) = value {
+            vars.push((key.to_string(), value.to_string()));
+        }
+    }
+    vars.sort_by_key(|(key, _)| key.to_uppercase());
+
+    let mut block: Vec<u16> = vars
+        .iter()
+        .flat_map(|(key, value)| win32_string(&format!("{key}={value}")))
+        .collect();
+    block.push(0);
+    Some(block)
+}
+
 /// Converts the string slice into a Windows-standard representation for "W"-
 /// suffixed function variants, which accept UTF-16 encoded string values.
 pub fn win32_string<S: AsRef<OsStr> + ?Sized>(value: &S) -> Vec<u16> {